@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 /// More: <https://discord.com/developers/docs/resources/application#application-object>
 ///
 /// - TODO: add full
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Application {
     /// ID of the app
     pub id: String,
@@ -21,3 +21,15 @@ pub struct Application {
     /// description on the app
     pub description: String,
 }
+
+/// Partial application object sent in the READY event
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#ready>
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartialApplication {
+    /// ID of the app
+    pub id: String,
+
+    /// the application's public flags
+    pub flags: Option<u64>,
+}
@@ -1,7 +1,12 @@
 use serde::Serialize;
 
 use crate::{
-    client::Client, gateway::gateway::PresenceUpdate, logger::setup_logger, response::UserResponse,
+    client::Client,
+    error::{Error, Result},
+    gateway::gateway::PresenceUpdate,
+    http::Endpoints,
+    logger::setup_logger,
+    response::UserResponse,
 };
 
 #[derive(Debug, Serialize)]
@@ -15,6 +20,8 @@ pub struct BotBase {
     pub intents: Option<i32>,
     pub client: Option<Client>,
     pub presence: Option<PresenceUpdate>,
+    /// REST and gateway endpoints used by the client
+    pub endpoints: Endpoints,
 }
 
 impl BotBase {
@@ -26,9 +33,10 @@ impl BotBase {
             log::debug!("🎯 Bot intents not specified, will use defaults");
         }
         BotBase {
-            intents: intents,
+            intents,
             client: None,
             presence: None,
+            endpoints: Endpoints::default(),
         }
     }
 
@@ -46,13 +54,14 @@ impl BotBase {
     /// println!("Login result: {:?}", result);
     /// # }
     /// ```
-    pub async fn login(&mut self, token: String) -> UserResponse {
+    pub async fn login(&mut self, token: String) -> Result<UserResponse> {
         log::info!("🔑 Initializing bot login...");
         let mut _client = Client::new();
-        let res = _client.login(token).await;
+        _client.set_endpoints(self.endpoints.clone());
+        let res = _client.login(token).await?;
         log::info!("🔒 Logged in as: {:?}", res.username);
         self.client = Some(_client);
-        res
+        Ok(res)
     }
 
     /// start the bot
@@ -62,31 +71,96 @@ impl BotBase {
     /// * `token` - the token of the bot
     /// * `reconnect` - if the bot should reconnect
     ///
-    pub async fn start(&mut self, token: String, reconnect: Option<bool>) -> () {
+    /// Runs until every shard was shut down. Fails when the login or
+    /// connecting to the gateway fails, or with the error of the first shard
+    /// that stopped because of a fatal gateway error.
+    pub async fn start(&mut self, token: String, reconnect: Option<bool>) -> Result<()> {
         log::info!("🚀 Starting bot...");
-        self.login(token).await;
+        self.login(token).await?;
         log::info!("📡 Establishing WebSocket connection...");
-        self.connect(self.intents, reconnect).await;
+        self.connect(self.intents, reconnect).await?;
+        self.wait_for_shards().await
     }
 
     pub async fn stop(&self) -> bool {
         true
     }
 
-    pub async fn connect(&mut self, intents: Option<i32>, reconnect: Option<bool>) {
+    /// connect the bot to the gateway
+    ///
+    /// The shards run in the background, use
+    /// [`wait_for_shards`](Self::wait_for_shards) to wait until they stopped.
+    pub async fn connect(&mut self, intents: Option<i32>, reconnect: Option<bool>) -> Result<()> {
         log::debug!("🌐 Connecting to Discord gateway...");
-        self.client
+        let client = self
+            .client
             .as_mut()
-            .unwrap()
-            .ws_connect(intents, reconnect, None)
-            .await;
-        log::info!("✅ Successfully connected to Discord gateway");
+            .ok_or_else(|| Error::Config("The bot must login before connecting".to_string()))?;
+        match client
+            .ws_connect(intents, reconnect, self.presence.clone())
+            .await
+        {
+            Ok(()) => {
+                log::info!("✅ Shards started, connecting to Discord gateway");
+                Ok(())
+            }
+            Err(err) => {
+                log::error!("❌ Failed to connect to Discord gateway: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// wait until the shards started by `connect` stopped
+    ///
+    /// Resolves once every shard was shut down, or with the error of the
+    /// first shard that stopped because of a fatal gateway error, such as an
+    /// invalid token or disallowed intents.
+    pub async fn wait_for_shards(&self) -> Result<()> {
+        match &self.client {
+            Some(client) => client.wait_for_shards().await,
+            None => Ok(()),
+        }
     }
 
+    /// set the presence of the bot
+    ///
+    /// The presence is sent with IDENTIFY and pushed to all running shards.
     pub async fn set_presence(&mut self, presence: PresenceUpdate) {
+        if let Some(client) = &self.client {
+            client.set_presence(presence.clone()).await;
+        }
         self.presence = Some(presence);
     }
 
+    /// set the REST and gateway endpoints of the bot
+    ///
+    /// Used to point the bot at a local stand-in of the Discord API, has to
+    /// be called before `login`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rustycord::{bot::BotBase, http::Endpoints};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut bot_base = BotBase::new(None).await;
+    /// bot_base.set_endpoints(Endpoints {
+    ///     api_url: "http://127.0.0.1:8080/api".to_string(),
+    ///     gateway_url: Some("ws://127.0.0.1:8081".to_string()),
+    ///     ..Endpoints::default()
+    /// });
+    /// bot_base.login("your_token_here".to_string()).await.unwrap();
+    /// # }
+    /// ```
+    pub fn set_endpoints(&mut self, endpoints: Endpoints) {
+        if let Some(client) = &mut self.client {
+            client.set_endpoints(endpoints.clone());
+        }
+        self.endpoints = endpoints;
+    }
+
     // pub async fn set_shard(&mut self, shard: usize) {
     //     let ws = ClientBuilderWS {
     //         intents: self.intents,
@@ -95,14 +169,15 @@ impl BotBase {
     //         presence: self.presence,
     //     };
     // }
-    pub async fn run(&mut self, token: String, log_level: Option<String>) -> bool {
+    /// Set up the logger and [`start`](Self::start) the bot, returns once the
+    /// shards stopped
+    pub async fn run(&mut self, token: String, log_level: Option<String>) -> Result<()> {
         let level: String = log_level.unwrap_or_else(|| "info".to_string());
 
         log::info!("⚙️ Initializing logger with level: {}", level);
         let _ = setup_logger(level);
         log::info!("🤖 rustycord bot starting up...");
-        self.start(token, Some(true)).await;
-        true
+        self.start(token, Some(true)).await
     }
 }
 
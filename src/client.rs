@@ -1,22 +1,49 @@
 // client.rs
 
-use std::sync::Arc;
-use tokio::{signal, sync::Mutex};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    signal,
+    sync::{Mutex, RwLock},
+    task::JoinHandle,
+};
 
 use crate::{
-    gateway::{gateway::PresenceUpdate, shard_manager::ShardManager},
-    http::{HTTPClient, MessageResponse},
+    gateway::{
+        gateway::PresenceUpdate,
+        encoding::GatewayEncoding,
+        identify_scheduler::IdentifyScheduler,
+        messenger::ShardMessenger,
+        recorder::EventRecorder,
+        shard_manager::ShardManager,
+    },
+    http::{AttachmentFile, CreateMessage, Endpoints, HTTPClient, MessageResponse},
     response::UserResponse,
-    handlers::event_dispatcher::EventDispatcher,
+    handlers::{dispatch_queue::DispatchConfig, event_dispatcher::EventDispatcher},
     embeds::Embed,
+    error::{Error, Result},
 };
 
+/// How long [`Client::shutdown_and_wait`] waits for the shards to close their connections
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Clone)]
 pub struct Client {
     pub token: Option<String>,
     pub http: HTTPClient,
     pub shard_manager: Option<Arc<Mutex<ShardManager>>>, // Add ShardManager
     pub event_dispatcher: Arc<EventDispatcher>,
+    /// Use `zlib-stream` transport compression for gateway connections
+    pub compress: bool,
+    /// Payload encoding of gateway connections, json unless set to ETF
+    pub encoding: GatewayEncoding,
+    /// Handles of the running shards by shard id, shared by all clones of the client
+    pub shard_messengers: Arc<RwLock<HashMap<usize, ShardMessenger>>>,
+    /// Writes received gateway payloads to a file when set
+    pub recorder: Option<Arc<EventRecorder>>,
+    /// Queue capacity and concurrency limit of event handling
+    pub dispatch_config: DispatchConfig,
+    /// Task running the shards, set by `start_shards` and taken once it finished
+    shards: Arc<Mutex<Option<JoinHandle<Result<()>>>>>,
 }
 
 impl Client {
@@ -26,71 +53,256 @@ impl Client {
             http: HTTPClient::new(),
             shard_manager: None,
             event_dispatcher: Arc::new(EventDispatcher::new()),
+            compress: false,
+            encoding: GatewayEncoding::Json,
+            shard_messengers: Arc::new(RwLock::new(HashMap::new())),
+            recorder: None,
+            dispatch_config: DispatchConfig::default(),
+            shards: Arc::new(Mutex::new(None)),
         }
     }
 
-    pub async fn login(&mut self, token: String) -> UserResponse {
+    /// Set the REST and gateway endpoints, e.g. to run against a local mock server
+    ///
+    /// Has to be called before the shards are initialized.
+    pub fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.http.set_endpoints(endpoints);
+    }
+
+    /// Record every gateway payload received by the shards, see [`crate::gateway::recorder`]
+    ///
+    /// Has to be called before the shards are initialized.
+    pub fn set_recorder(&mut self, recorder: EventRecorder) {
+        self.recorder = Some(Arc::new(recorder));
+    }
+
+    /// Set how many events are buffered and handled at the same time
+    ///
+    /// Has to be called before the shards are initialized.
+    pub fn set_dispatch_config(&mut self, config: DispatchConfig) {
+        self.dispatch_config = config;
+    }
+
+    /// Check the token and fetch the bot user
+    pub async fn login(&mut self, token: String) -> Result<UserResponse> {
         self.token = Some(token.clone());
-        let res = self.http.login(token).await;
-        res
+        self.http.login(token).await
     }
 
-    pub async fn get_gateway(&self) -> String {
+    pub async fn get_gateway(&self) -> Result<String> {
         self.http.get_gateway().await
     }
 
+    /// Token set by `login`, shards can not be initialized without it
+    fn shard_token(&self) -> Result<String> {
+        self.token.clone().ok_or_else(|| Error::Config("Token must be set before initializing shards".to_string()))
+    }
+
     /// Send a message to a channel
-    pub async fn send_message(&self, channel_id: &str, content: &str, embeds: Option<Vec<Embed>>) -> Result<MessageResponse, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn send_message(&self, channel_id: &str, content: &str, embeds: Option<Vec<Embed>>) -> Result<MessageResponse> {
         self.http.send_message(channel_id, content, embeds).await
     }
 
     /// Send a simple text message to a channel
-    pub async fn send_text_message(&self, channel_id: &str, content: &str) -> Result<MessageResponse, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn send_text_message(&self, channel_id: &str, content: &str) -> Result<MessageResponse> {
         self.send_message(channel_id, content, None).await
     }
 
     /// Send a message with embeds to a channel
-    pub async fn send_embed_message(&self, channel_id: &str, embeds: Vec<Embed>) -> Result<MessageResponse, Box<dyn std::error::Error + Send + Sync>> {
+    pub async fn send_embed_message(&self, channel_id: &str, embeds: Vec<Embed>) -> Result<MessageResponse> {
         self.send_message(channel_id, "", Some(embeds)).await
     }
 
+    /// Send a message with files to a channel
+    pub async fn send_files(&self, channel_id: &str, content: &str, files: Vec<AttachmentFile>) -> Result<MessageResponse> {
+        self.http.send_files(channel_id, content, None, files).await
+    }
+
+    /// Send a message built with [`CreateMessage`] to a channel
+    pub async fn create_message(&self, channel_id: &str, message: CreateMessage) -> Result<MessageResponse> {
+        self.http.create_message(channel_id, message).await
+    }
+
+    /// Reply to a message, fails when the message was deleted
+    pub async fn reply(&self, channel_id: &str, message_id: &str, content: &str, embeds: Option<Vec<Embed>>) -> Result<MessageResponse> {
+        self.http.reply(channel_id, message_id, content, embeds, true).await
+    }
+
+    /// Edit a message sent by the bot, `None` keeps the current content or embeds
+    pub async fn edit_message(&self, channel_id: &str, message_id: &str, content: Option<&str>, embeds: Option<Vec<Embed>>) -> Result<MessageResponse> {
+        self.http.edit_message(channel_id, message_id, content, embeds).await
+    }
+
+    /// Delete a message
+    pub async fn delete_message(&self, channel_id: &str, message_id: &str) -> Result<()> {
+        self.http.delete_message(channel_id, message_id, None).await
+    }
+
     /// Get the event dispatcher for registering message handlers
     pub fn get_event_dispatcher(&self) -> Arc<EventDispatcher> {
         self.event_dispatcher.clone()
     }
 
     /// Initialize the Shard Manager with the desired number of shards
-    pub async fn initialize_shards(&mut self, total_shards: usize) {
-        let token = self.token.clone().expect("Token must be set before initializing shards");
+    pub async fn initialize_shards(&mut self, total_shards: usize) -> Result<()> {
+        let token = self.shard_token()?;
         let shard_manager = ShardManager::new(token, total_shards, self.clone());
         self.shard_manager = Some(Arc::new(Mutex::new(shard_manager)));
+        Ok(())
+    }
+
+    /// Initialize the Shard Manager with the shard count recommended by Discord.
+    ///
+    /// Shards identify in buckets of `max_concurrency` every 5 seconds and
+    /// wait for the reset once the session start budget is used up.
+    pub async fn initialize_auto_shards(&mut self) -> Result<usize> {
+        let token = self.shard_token()?;
+        let gateway = self.http.get_gateway_bot().await?;
+        let shard_manager = ShardManager::from_gateway(token, &gateway, self.clone());
+        let total_shards = shard_manager.total_shards;
+        log::info!("🧩 Auto sharding with {} shard(s)", total_shards);
+        self.shard_manager = Some(Arc::new(Mutex::new(shard_manager)));
+        Ok(total_shards)
+    }
+
+    /// Initialize the Shard Manager to run a part of the bot's shards.
+    ///
+    /// Every process of a cluster passes the same `total_shards` and its own
+    /// shard ids, identifies are scheduled with the limits from `GET /gateway/bot`.
+    /// Only the shards of this client are coordinated, see
+    /// [`ShardManager::set_session_start_limit`] for clusters of one bot.
+    pub async fn initialize_shard_cluster<I>(
+        &mut self,
+        shard_ids: I,
+        total_shards: usize,
+    ) -> Result<()>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let token = self.shard_token()?;
+        let gateway = self.http.get_gateway_bot().await?;
+        let mut shard_manager = ShardManager::new(token, total_shards, self.clone());
+        shard_manager.set_shard_ids(shard_ids)?;
+        shard_manager.set_gateway(&gateway);
+        self.shard_manager = Some(Arc::new(Mutex::new(shard_manager)));
+        Ok(())
     }
 
-    /// Start all shards managed by the ShardManager
-    pub async fn start_shards(&self, intents: Option<i32>, presense: Option<PresenceUpdate>) {
-        if let Some(manager) = &self.shard_manager {
-            let manager = manager.clone();
-            tokio::spawn(async move {
-                manager.lock().await.start(intents, presense).await;
-            });
+    /// Scheduler ordering the IDENTIFY calls of this client's shards, `None`
+    /// until the shards were initialized.
+    ///
+    /// Has to be called before the shards are started.
+    pub async fn identify_scheduler(&self) -> Option<Arc<IdentifyScheduler>> {
+        let manager = self.shard_manager.as_ref()?;
+        Some(manager.lock().await.identify_scheduler.clone())
+    }
+
+    /// Identify the shards of this client through `scheduler`, the scheduler
+    /// of another cluster of the bot running in this process, so the clusters
+    /// do not identify in the same rate limit bucket at once.
+    ///
+    /// Has to be called after the shards were initialized and before they are started.
+    pub async fn set_identify_scheduler(&self, scheduler: Arc<IdentifyScheduler>) -> Result<()> {
+        let manager = self
+            .shard_manager
+            .as_ref()
+            .ok_or_else(|| Error::Config("Shards were not initialized".to_string()))?;
+        manager.lock().await.set_identify_scheduler(scheduler);
+        Ok(())
+    }
+
+    /// Start all shards managed by the ShardManager in the background
+    ///
+    /// Use [`wait_for_shards`](Self::wait_for_shards) to wait until they stopped.
+    pub async fn start_shards(
+        &self,
+        intents: Option<i32>,
+        presense: Option<PresenceUpdate>,
+    ) -> Result<()> {
+        let manager = self
+            .shard_manager
+            .clone()
+            .ok_or_else(|| Error::Config("Shards were not initialized".to_string()))?;
+        let mut shards = self.shards.lock().await;
+        if shards.as_ref().is_some_and(|task| !task.is_finished()) {
+            return Err(Error::Config("Shards were already started".to_string()));
         }
+        *shards = Some(tokio::spawn(async move {
+            manager.lock().await.start(intents, presense).await
+        }));
+        Ok(())
     }
 
-    pub async fn wait_for_shutdown(&self) {
-        // Listen for CTRL+C
-        signal::ctrl_c().await.expect("Failed to listen for ctrl_c");
-        log::info!("Shutdown signal received, shutting down shards...");
+    /// Wait until the shards started by [`start_shards`](Self::start_shards) stopped.
+    ///
+    /// Resolves with `Ok` once every shard was shut down, or with the error
+    /// of the first shard that stopped because of a fatal gateway error, such
+    /// as an invalid token or disallowed intents. The other shards are shut
+    /// down in that case. Returns right away when no shards were started.
+    pub async fn wait_for_shards(&self) -> Result<()> {
+        let mut shards = self.shards.lock().await;
+        let Some(task) = shards.as_mut() else {
+            return Ok(());
+        };
+        let result = task.await;
+        *shards = None;
+        result.map_err(std::io::Error::from)?
+    }
+
+    /// Get the handle of a running shard
+    pub async fn shard_messenger(&self, shard_id: usize) -> Option<ShardMessenger> {
+        self.shard_messengers.read().await.get(&shard_id).cloned()
+    }
 
-        if let Some(_manager) = &self.shard_manager {
-            // Implement shard shutdown logic here
-            // For example, send a shutdown signal to all shard tasks
-            // This can be done using channels or other synchronization primitives
+    /// Update the presence of every running shard
+    pub async fn set_presence(&self, presence: PresenceUpdate) {
+        for messenger in self.shard_messengers.read().await.values() {
+            messenger.set_presence(presence.clone());
         }
+    }
 
-        log::info!("All shards have been shut down.");
+    /// Update the status (`online`, `dnd`, `idle`, `invisible`) of every running shard
+    pub async fn set_status(&self, status: &str) {
+        for messenger in self.shard_messengers.read().await.values() {
+            messenger.set_status(status);
+        }
     }
 
-      pub async fn ws_connect(
+    /// Close the gateway connection of every shard with a normal close frame
+    pub async fn shutdown(&self) {
+        let messengers = self.shard_messengers.read().await;
+        log::info!("🛑 Shutting down {} shard(s)...", messengers.len());
+        for messenger in messengers.values() {
+            messenger.shutdown();
+        }
+    }
+
+    /// Shut down every shard and wait up to [`SHUTDOWN_TIMEOUT`] until they
+    /// closed their connections
+    pub async fn shutdown_and_wait(&self) -> Result<()> {
+        self.shutdown().await;
+        match tokio::time::timeout(SHUTDOWN_TIMEOUT, self.wait_for_shards()).await {
+            Ok(result) => {
+                log::info!("All shards have been shut down.");
+                result
+            }
+            Err(_) => {
+                log::warn!("⚠️ Shards did not shut down within {:?}", SHUTDOWN_TIMEOUT);
+                Ok(())
+            }
+        }
+    }
+
+    /// Wait for CTRL+C, then shut down the shards gracefully
+    pub async fn wait_for_shutdown(&self) -> Result<()> {
+        // Listen for CTRL+C
+        signal::ctrl_c().await?;
+        log::info!("Shutdown signal received, shutting down shards...");
+
+        self.shutdown_and_wait().await
+    }
+
+    pub async fn ws_connect(
         &mut self,
         intents: Option<i32>,
         #[allow(unused_variables)] reconnect: Option<bool>,
@@ -98,9 +310,18 @@ impl Client {
         // large_threshold: Option<i32>,
         // shard: usize,
         presence: Option<PresenceUpdate>,
-    ) {
-        self.initialize_shards(2).await;
-        self.start_shards(intents,presence).await;
+    ) -> Result<()> {
+        if let Err(err) = self.initialize_auto_shards().await {
+            log::error!("❌ Failed to initialize shards: {}", err);
+            return Err(err);
+        }
+        self.start_shards(intents, presence).await
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
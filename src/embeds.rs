@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 /// Represent an embed in a message
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Embed {
     /// title of embed
     pub title: Option<String>,
@@ -52,7 +52,7 @@ pub struct Embed {
 /// power what is rendered. Embed types should be considered
 /// deprecated and might be removed in a future API version.
 #[allow(non_camel_case_types)]
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum EmbedType {
     /// generic embed rendered from embed attributes
     rich,
@@ -76,7 +76,7 @@ pub enum EmbedType {
 /// Represent an embed footer
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-footer-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmbedFooter {
     /// footer text
     pub text: String,
@@ -91,7 +91,7 @@ pub struct EmbedFooter {
 /// Represent an embed image
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-image-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 
 pub struct EmbedImage {
     /// source url of image (only supports http(s) and attachments)
@@ -110,7 +110,7 @@ pub struct EmbedImage {
 /// Represent an embed thumbnail
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-thumbnail-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmbedThumbnail {
     /// source url of thumbnail (only supports http(s) and attachments)
     pub url: Option<String>,
@@ -128,7 +128,7 @@ pub struct EmbedThumbnail {
 /// Represent an embed video
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-video-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmbedVideo {
     /// source url of video
     pub url: Option<String>,
@@ -146,7 +146,7 @@ pub struct EmbedVideo {
 /// Represent an embed provider
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-provider-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmbedProvider {
     /// name of provider
     pub name: Option<String>,
@@ -158,7 +158,7 @@ pub struct EmbedProvider {
 /// Represent an embed author
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-author-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmbedAuthor {
     /// name of author
     pub name: Option<String>,
@@ -176,7 +176,7 @@ pub struct EmbedAuthor {
 /// Represent an embed field
 ///
 /// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-field-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EmbedField {
     /// name of the field
     pub name: String,
@@ -0,0 +1,156 @@
+// error.rs
+
+use crate::gateway::gateway::GatewayError;
+
+/// Result type of fallible rustycord operations
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error returned by rustycord
+///
+/// # Examples
+///
+/// ```no_run
+/// use rustycord::{client::Client, Error};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut client = Client::new();
+/// match client.login("your_token_here".to_string()).await {
+///     Ok(user) => println!("Logged in as {}", user.username),
+///     Err(Error::Unauthorized) => eprintln!("The token was rejected"),
+///     Err(err) => eprintln!("Login failed, retrying later: {}", err),
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// The HTTP request failed before Discord answered, e.g. a network error
+    Http(reqwest::Error),
+
+    /// Discord rejected the token
+    Unauthorized,
+
+    /// Discord rate limited the request
+    ///
+    /// More: <https://discord.com/developers/docs/topics/rate-limits#exceeding-a-rate-limit>
+    RateLimited {
+        /// Seconds to wait before retrying
+        retry_after: f64,
+
+        /// Whether the global rate limit was hit
+        global: bool,
+    },
+
+    /// Discord answered with an error status
+    ///
+    /// More: <https://discord.com/developers/docs/topics/opcodes-and-status-codes#json>
+    Api {
+        /// HTTP status code
+        status: u16,
+
+        /// Discord JSON error code, if one was sent
+        code: Option<u64>,
+
+        /// Error message sent by Discord
+        message: String,
+    },
+
+    /// The gateway connection could not be opened or was closed
+    Gateway(GatewayError),
+
+    /// A payload could not be decoded or encoded
+    Decode(String),
+
+    /// A payload breaks a limit of Discord and was not sent, e.g. a message
+    /// longer than 2000 characters
+    Validation(String),
+
+    /// Reading or writing a file failed
+    Io(std::io::Error),
+
+    /// The library was used in a way that cannot work, e.g. shards were
+    /// initialized before logging in
+    Config(String),
+}
+
+impl Error {
+    /// Whether retrying the same operation later can succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(_) | Self::RateLimited { .. } | Self::Io(_) => true,
+            Self::Api { status, .. } => *status >= 500,
+            Self::Gateway(err) => !err.is_fatal(),
+            Self::Unauthorized | Self::Decode(_) | Self::Validation(_) | Self::Config(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http(err) => write!(f, "HTTP request failed: {}", err),
+            Self::Unauthorized => write!(f, "invalid token"),
+            Self::RateLimited {
+                retry_after,
+                global,
+            } => write!(
+                f,
+                "rate limited{}, retry after {}s",
+                if *global { " globally" } else { "" },
+                retry_after
+            ),
+            Self::Api {
+                status,
+                code: Some(code),
+                message,
+            } => write!(
+                f,
+                "Discord API error {} (code {}): {}",
+                status, code, message
+            ),
+            Self::Api {
+                status, message, ..
+            } => write!(f, "Discord API error {}: {}", status, message),
+            Self::Gateway(err) => write!(f, "gateway error: {}", err),
+            Self::Decode(err) => write!(f, "failed to decode payload: {}", err),
+            Self::Validation(err) => write!(f, "invalid payload: {}", err),
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::Config(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(err) => Some(err),
+            Self::Gateway(err) => Some(err),
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Http(err)
+    }
+}
+
+impl From<GatewayError> for Error {
+    fn from(err: GatewayError) -> Self {
+        Self::Gateway(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Decode(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
@@ -0,0 +1,1081 @@
+use async_trait::async_trait;
+
+use crate::{
+    client::Client,
+    gateway::{
+        event::{
+            AutoModerationActionExecution, ChannelPinsUpdate, Event, GuildBan, GuildEmojisUpdate,
+            GuildIntegrationsUpdate, GuildMemberRemove, GuildMembersChunk, GuildRole,
+            GuildRoleDelete, GuildScheduledEventUser, GuildStickersUpdate, IntegrationDelete,
+            InviteCreate, InviteDelete, MessageDelete, MessageDeleteBulk, MessagePollVote,
+            MessageReactionAdd, MessageReactionRemove, MessageReactionRemoveAll,
+            MessageReactionRemoveEmoji, ThreadListSync, ThreadMembersUpdate, TypingStart,
+            VoiceServerUpdate, WebhooksUpdate,
+        },
+        response::Ready,
+    },
+    message::ChannelMessage,
+    models::{
+        automod::AutoModerationRule,
+        channel::{Channel, ThreadMember},
+        entitlement::Entitlement,
+        guild::{
+            AuditLogEntry, Guild, GuildMember, GuildScheduledEvent, Integration, StageInstance,
+            UnavailableGuild,
+        },
+        interaction::{GuildApplicationCommandPermissions, Interaction},
+        user::{Presence, User},
+        voice::VoiceState,
+    },
+};
+
+/// Result type of [`EventHandler`] methods
+pub type EventHandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// Trait for handling typed gateway events.
+///
+/// Every method has a default no-op implementation, so handlers only
+/// override the events they care about. Register handlers with
+/// [`EventDispatcher::add_event_handler`](crate::handlers::event_dispatcher::EventDispatcher::add_event_handler).
+///
+/// ```rust,no_run
+/// use async_trait::async_trait;
+/// use rustycord::{
+///     client::Client,
+///     event_handler::{EventHandler, EventHandlerResult},
+///     gateway::response::Ready,
+/// };
+///
+/// struct ReadyLogger;
+///
+/// #[async_trait]
+/// impl EventHandler for ReadyLogger {
+///     async fn on_ready(&self, ready: &Ready, _client: &Client) -> EventHandlerResult {
+///         println!("Logged in as {}", ready.user.name);
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    /// Called when the shard finished identifying and received the initial state
+    async fn on_ready(&self, _ready: &Ready, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a resumed session finished replaying missed events
+    async fn on_resumed(&self, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an application command permission was updated
+    async fn on_application_command_permissions_update(
+        &self,
+        _permissions: &GuildApplicationCommandPermissions,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an Auto Moderation rule was created
+    async fn on_auto_moderation_rule_create(
+        &self,
+        _rule: &AutoModerationRule,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an Auto Moderation rule was updated
+    async fn on_auto_moderation_rule_update(
+        &self,
+        _rule: &AutoModerationRule,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an Auto Moderation rule was deleted
+    async fn on_auto_moderation_rule_delete(
+        &self,
+        _rule: &AutoModerationRule,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an Auto Moderation rule was triggered and an action was executed
+    async fn on_auto_moderation_action_execution(
+        &self,
+        _execution: &AutoModerationActionExecution,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild channel was created
+    async fn on_channel_create(&self, _channel: &Channel, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a channel was updated
+    async fn on_channel_update(&self, _channel: &Channel, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a channel was deleted
+    async fn on_channel_delete(&self, _channel: &Channel, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a message was pinned or unpinned
+    async fn on_channel_pins_update(
+        &self,
+        _pins: &ChannelPinsUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a thread was created or the current user was added to a private thread
+    async fn on_thread_create(&self, _channel: &Channel, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a thread was updated
+    async fn on_thread_update(&self, _channel: &Channel, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a thread was deleted
+    async fn on_thread_delete(&self, _channel: &Channel, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when the current user gained access to a channel with active threads
+    async fn on_thread_list_sync(
+        &self,
+        _sync: &ThreadListSync,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when the thread member of the current user was updated
+    async fn on_thread_member_update(
+        &self,
+        _member: &ThreadMember,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when users were added to or removed from a thread
+    async fn on_thread_members_update(
+        &self,
+        _update: &ThreadMembersUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an entitlement was created
+    async fn on_entitlement_create(
+        &self,
+        _entitlement: &Entitlement,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an entitlement was updated or renewed
+    async fn on_entitlement_update(
+        &self,
+        _entitlement: &Entitlement,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an entitlement was deleted
+    async fn on_entitlement_delete(
+        &self,
+        _entitlement: &Entitlement,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild became available or the bot joined a new guild
+    async fn on_guild_create(&self, _guild: &Guild, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild was updated
+    async fn on_guild_update(&self, _guild: &Guild, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild became unavailable or the bot was removed from it
+    async fn on_guild_delete(
+        &self,
+        _guild: &UnavailableGuild,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an audit log entry was created
+    async fn on_guild_audit_log_entry_create(
+        &self,
+        _entry: &AuditLogEntry,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user was banned from a guild
+    async fn on_guild_ban_add(&self, _ban: &GuildBan, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user was unbanned from a guild
+    async fn on_guild_ban_remove(&self, _ban: &GuildBan, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when the emojis of a guild were updated
+    async fn on_guild_emojis_update(
+        &self,
+        _update: &GuildEmojisUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when the stickers of a guild were updated
+    async fn on_guild_stickers_update(
+        &self,
+        _update: &GuildStickersUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild integration was updated
+    async fn on_guild_integrations_update(
+        &self,
+        _update: &GuildIntegrationsUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user joined a guild
+    async fn on_guild_member_add(
+        &self,
+        _member: &GuildMember,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user left or was removed from a guild
+    async fn on_guild_member_remove(
+        &self,
+        _removed: &GuildMemberRemove,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild member was updated
+    async fn on_guild_member_update(
+        &self,
+        _member: &GuildMember,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a chunk of members was received in response to Request Guild Members
+    async fn on_guild_members_chunk(
+        &self,
+        _chunk: &GuildMembersChunk,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild role was created
+    async fn on_guild_role_create(
+        &self,
+        _role: &GuildRole,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild role was updated
+    async fn on_guild_role_update(
+        &self,
+        _role: &GuildRole,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a guild role was deleted
+    async fn on_guild_role_delete(
+        &self,
+        _deleted: &GuildRoleDelete,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a scheduled event was created
+    async fn on_guild_scheduled_event_create(
+        &self,
+        _event: &GuildScheduledEvent,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a scheduled event was updated
+    async fn on_guild_scheduled_event_update(
+        &self,
+        _event: &GuildScheduledEvent,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a scheduled event was deleted
+    async fn on_guild_scheduled_event_delete(
+        &self,
+        _event: &GuildScheduledEvent,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user subscribed to a scheduled event
+    async fn on_guild_scheduled_event_user_add(
+        &self,
+        _subscription: &GuildScheduledEventUser,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user unsubscribed from a scheduled event
+    async fn on_guild_scheduled_event_user_remove(
+        &self,
+        _subscription: &GuildScheduledEventUser,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an integration was created
+    async fn on_integration_create(
+        &self,
+        _integration: &Integration,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an integration was updated
+    async fn on_integration_update(
+        &self,
+        _integration: &Integration,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an integration was deleted
+    async fn on_integration_delete(
+        &self,
+        _deleted: &IntegrationDelete,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user used an interaction, such as an application command
+    async fn on_interaction_create(
+        &self,
+        _interaction: &Interaction,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an invite was created
+    async fn on_invite_create(
+        &self,
+        _invite: &InviteCreate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when an invite was deleted
+    async fn on_invite_delete(
+        &self,
+        _invite: &InviteDelete,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a message was created
+    async fn on_message_create(
+        &self,
+        _message: &ChannelMessage,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a message was edited
+    async fn on_message_update(
+        &self,
+        _message: &ChannelMessage,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a message was deleted
+    async fn on_message_delete(
+        &self,
+        _deleted: &MessageDelete,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when multiple messages were deleted at once
+    async fn on_message_delete_bulk(
+        &self,
+        _deleted: &MessageDeleteBulk,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user reacted to a message
+    async fn on_reaction_add(
+        &self,
+        _reaction: &MessageReactionAdd,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user removed a reaction from a message
+    async fn on_reaction_remove(
+        &self,
+        _reaction: &MessageReactionRemove,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when all reactions were removed from a message
+    async fn on_reaction_remove_all(
+        &self,
+        _removed: &MessageReactionRemoveAll,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when all reactions for an emoji were removed from a message
+    async fn on_reaction_remove_emoji(
+        &self,
+        _removed: &MessageReactionRemoveEmoji,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when the presence of a user was updated
+    async fn on_presence_update(
+        &self,
+        _presence: &Presence,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a stage instance was created
+    async fn on_stage_instance_create(
+        &self,
+        _stage: &StageInstance,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a stage instance was updated
+    async fn on_stage_instance_update(
+        &self,
+        _stage: &StageInstance,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a stage instance was deleted or closed
+    async fn on_stage_instance_delete(
+        &self,
+        _stage: &StageInstance,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user started typing in a channel
+    async fn on_typing_start(&self, _typing: &TypingStart, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when properties of the current user changed
+    async fn on_user_update(&self, _user: &User, _client: &Client) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when someone joined, left or moved a voice channel
+    async fn on_voice_state_update(
+        &self,
+        _state: &VoiceState,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when the voice server of a guild was updated
+    async fn on_voice_server_update(
+        &self,
+        _server: &VoiceServerUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a webhook of a guild channel was created, updated or deleted
+    async fn on_webhooks_update(
+        &self,
+        _update: &WebhooksUpdate,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user voted on a poll
+    async fn on_poll_vote_add(
+        &self,
+        _vote: &MessagePollVote,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+
+    /// Called when a user removed a vote on a poll
+    async fn on_poll_vote_remove(
+        &self,
+        _vote: &MessagePollVote,
+        _client: &Client,
+    ) -> EventHandlerResult {
+        Ok(())
+    }
+}
+
+/// Call the method of `handler` matching the event.
+///
+/// Unknown events have no typed method, they reach
+/// [`RawEventHandler`](crate::handlers::raw_event_handler::RawEventHandler)s only.
+pub(crate) async fn handle_event(
+    handler: &dyn EventHandler,
+    event: &Event,
+    client: &Client,
+) -> EventHandlerResult {
+    match event {
+        Event::Ready(data) => handler.on_ready(data, client).await,
+        Event::Resumed => handler.on_resumed(client).await,
+        Event::ApplicationCommandPermissionsUpdate(data) => {
+            handler
+                .on_application_command_permissions_update(data, client)
+                .await
+        }
+        Event::AutoModerationRuleCreate(data) => {
+            handler.on_auto_moderation_rule_create(data, client).await
+        }
+        Event::AutoModerationRuleUpdate(data) => {
+            handler.on_auto_moderation_rule_update(data, client).await
+        }
+        Event::AutoModerationRuleDelete(data) => {
+            handler.on_auto_moderation_rule_delete(data, client).await
+        }
+        Event::AutoModerationActionExecution(data) => {
+            handler
+                .on_auto_moderation_action_execution(data, client)
+                .await
+        }
+        Event::ChannelCreate(data) => handler.on_channel_create(data, client).await,
+        Event::ChannelUpdate(data) => handler.on_channel_update(data, client).await,
+        Event::ChannelDelete(data) => handler.on_channel_delete(data, client).await,
+        Event::ChannelPinsUpdate(data) => handler.on_channel_pins_update(data, client).await,
+        Event::ThreadCreate(data) => handler.on_thread_create(data, client).await,
+        Event::ThreadUpdate(data) => handler.on_thread_update(data, client).await,
+        Event::ThreadDelete(data) => handler.on_thread_delete(data, client).await,
+        Event::ThreadListSync(data) => handler.on_thread_list_sync(data, client).await,
+        Event::ThreadMemberUpdate(data) => handler.on_thread_member_update(data, client).await,
+        Event::ThreadMembersUpdate(data) => handler.on_thread_members_update(data, client).await,
+        Event::EntitlementCreate(data) => handler.on_entitlement_create(data, client).await,
+        Event::EntitlementUpdate(data) => handler.on_entitlement_update(data, client).await,
+        Event::EntitlementDelete(data) => handler.on_entitlement_delete(data, client).await,
+        Event::GuildCreate(data) => handler.on_guild_create(data, client).await,
+        Event::GuildUpdate(data) => handler.on_guild_update(data, client).await,
+        Event::GuildDelete(data) => handler.on_guild_delete(data, client).await,
+        Event::GuildAuditLogEntryCreate(data) => {
+            handler.on_guild_audit_log_entry_create(data, client).await
+        }
+        Event::GuildBanAdd(data) => handler.on_guild_ban_add(data, client).await,
+        Event::GuildBanRemove(data) => handler.on_guild_ban_remove(data, client).await,
+        Event::GuildEmojisUpdate(data) => handler.on_guild_emojis_update(data, client).await,
+        Event::GuildStickersUpdate(data) => handler.on_guild_stickers_update(data, client).await,
+        Event::GuildIntegrationsUpdate(data) => {
+            handler.on_guild_integrations_update(data, client).await
+        }
+        Event::GuildMemberAdd(data) => handler.on_guild_member_add(data, client).await,
+        Event::GuildMemberRemove(data) => handler.on_guild_member_remove(data, client).await,
+        Event::GuildMemberUpdate(data) => handler.on_guild_member_update(data, client).await,
+        Event::GuildMembersChunk(data) => handler.on_guild_members_chunk(data, client).await,
+        Event::GuildRoleCreate(data) => handler.on_guild_role_create(data, client).await,
+        Event::GuildRoleUpdate(data) => handler.on_guild_role_update(data, client).await,
+        Event::GuildRoleDelete(data) => handler.on_guild_role_delete(data, client).await,
+        Event::GuildScheduledEventCreate(data) => {
+            handler.on_guild_scheduled_event_create(data, client).await
+        }
+        Event::GuildScheduledEventUpdate(data) => {
+            handler.on_guild_scheduled_event_update(data, client).await
+        }
+        Event::GuildScheduledEventDelete(data) => {
+            handler.on_guild_scheduled_event_delete(data, client).await
+        }
+        Event::GuildScheduledEventUserAdd(data) => {
+            handler
+                .on_guild_scheduled_event_user_add(data, client)
+                .await
+        }
+        Event::GuildScheduledEventUserRemove(data) => {
+            handler
+                .on_guild_scheduled_event_user_remove(data, client)
+                .await
+        }
+        Event::IntegrationCreate(data) => handler.on_integration_create(data, client).await,
+        Event::IntegrationUpdate(data) => handler.on_integration_update(data, client).await,
+        Event::IntegrationDelete(data) => handler.on_integration_delete(data, client).await,
+        Event::InteractionCreate(data) => handler.on_interaction_create(data, client).await,
+        Event::InviteCreate(data) => handler.on_invite_create(data, client).await,
+        Event::InviteDelete(data) => handler.on_invite_delete(data, client).await,
+        Event::MessageCreate(data) => handler.on_message_create(data, client).await,
+        Event::MessageUpdate(data) => handler.on_message_update(data, client).await,
+        Event::MessageDelete(data) => handler.on_message_delete(data, client).await,
+        Event::MessageDeleteBulk(data) => handler.on_message_delete_bulk(data, client).await,
+        Event::MessageReactionAdd(data) => handler.on_reaction_add(data, client).await,
+        Event::MessageReactionRemove(data) => handler.on_reaction_remove(data, client).await,
+        Event::MessageReactionRemoveAll(data) => handler.on_reaction_remove_all(data, client).await,
+        Event::MessageReactionRemoveEmoji(data) => {
+            handler.on_reaction_remove_emoji(data, client).await
+        }
+        Event::PresenceUpdate(data) => handler.on_presence_update(data, client).await,
+        Event::StageInstanceCreate(data) => handler.on_stage_instance_create(data, client).await,
+        Event::StageInstanceUpdate(data) => handler.on_stage_instance_update(data, client).await,
+        Event::StageInstanceDelete(data) => handler.on_stage_instance_delete(data, client).await,
+        Event::TypingStart(data) => handler.on_typing_start(data, client).await,
+        Event::UserUpdate(data) => handler.on_user_update(data, client).await,
+        Event::VoiceStateUpdate(data) => handler.on_voice_state_update(data, client).await,
+        Event::VoiceServerUpdate(data) => handler.on_voice_server_update(data, client).await,
+        Event::WebhooksUpdate(data) => handler.on_webhooks_update(data, client).await,
+        Event::MessagePollVoteAdd(data) => handler.on_poll_vote_add(data, client).await,
+        Event::MessagePollVoteRemove(data) => handler.on_poll_vote_remove(data, client).await,
+        Event::Unknown { .. } => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::gateway::response::GatewayReceiveEventName;
+
+    /// Remembers the name of every method called on it
+    #[derive(Default)]
+    struct Calls(Mutex<Vec<&'static str>>);
+
+    impl Calls {
+        fn called(&self, method: &'static str) -> EventHandlerResult {
+            self.0.lock().unwrap().push(method);
+            Ok(())
+        }
+    }
+
+    /// Implement every method of [`EventHandler`] to record its call
+    macro_rules! record_calls {
+        ($($method:ident($data:ty)),* $(,)?) => {
+            #[async_trait]
+            impl EventHandler for Calls {
+                async fn on_resumed(&self, _client: &Client) -> EventHandlerResult {
+                    self.called("on_resumed")
+                }
+
+                $(
+                    async fn $method(&self, _data: &$data, _client: &Client) -> EventHandlerResult {
+                        self.called(stringify!($method))
+                    }
+                )*
+            }
+        };
+    }
+
+    record_calls! {
+        on_ready(Ready),
+        on_application_command_permissions_update(GuildApplicationCommandPermissions),
+        on_auto_moderation_rule_create(AutoModerationRule),
+        on_auto_moderation_rule_update(AutoModerationRule),
+        on_auto_moderation_rule_delete(AutoModerationRule),
+        on_auto_moderation_action_execution(AutoModerationActionExecution),
+        on_channel_create(Channel),
+        on_channel_update(Channel),
+        on_channel_delete(Channel),
+        on_channel_pins_update(ChannelPinsUpdate),
+        on_thread_create(Channel),
+        on_thread_update(Channel),
+        on_thread_delete(Channel),
+        on_thread_list_sync(ThreadListSync),
+        on_thread_member_update(ThreadMember),
+        on_thread_members_update(ThreadMembersUpdate),
+        on_entitlement_create(Entitlement),
+        on_entitlement_update(Entitlement),
+        on_entitlement_delete(Entitlement),
+        on_guild_create(Guild),
+        on_guild_update(Guild),
+        on_guild_delete(UnavailableGuild),
+        on_guild_audit_log_entry_create(AuditLogEntry),
+        on_guild_ban_add(GuildBan),
+        on_guild_ban_remove(GuildBan),
+        on_guild_emojis_update(GuildEmojisUpdate),
+        on_guild_stickers_update(GuildStickersUpdate),
+        on_guild_integrations_update(GuildIntegrationsUpdate),
+        on_guild_member_add(GuildMember),
+        on_guild_member_remove(GuildMemberRemove),
+        on_guild_member_update(GuildMember),
+        on_guild_members_chunk(GuildMembersChunk),
+        on_guild_role_create(GuildRole),
+        on_guild_role_update(GuildRole),
+        on_guild_role_delete(GuildRoleDelete),
+        on_guild_scheduled_event_create(GuildScheduledEvent),
+        on_guild_scheduled_event_update(GuildScheduledEvent),
+        on_guild_scheduled_event_delete(GuildScheduledEvent),
+        on_guild_scheduled_event_user_add(GuildScheduledEventUser),
+        on_guild_scheduled_event_user_remove(GuildScheduledEventUser),
+        on_integration_create(Integration),
+        on_integration_update(Integration),
+        on_integration_delete(IntegrationDelete),
+        on_interaction_create(Interaction),
+        on_invite_create(InviteCreate),
+        on_invite_delete(InviteDelete),
+        on_message_create(ChannelMessage),
+        on_message_update(ChannelMessage),
+        on_message_delete(MessageDelete),
+        on_message_delete_bulk(MessageDeleteBulk),
+        on_reaction_add(MessageReactionAdd),
+        on_reaction_remove(MessageReactionRemove),
+        on_reaction_remove_all(MessageReactionRemoveAll),
+        on_reaction_remove_emoji(MessageReactionRemoveEmoji),
+        on_presence_update(Presence),
+        on_stage_instance_create(StageInstance),
+        on_stage_instance_update(StageInstance),
+        on_stage_instance_delete(StageInstance),
+        on_typing_start(TypingStart),
+        on_user_update(User),
+        on_voice_state_update(VoiceState),
+        on_voice_server_update(VoiceServerUpdate),
+        on_webhooks_update(WebhooksUpdate),
+        on_poll_vote_add(MessagePollVote),
+        on_poll_vote_remove(MessagePollVote),
+    }
+
+    /// Fields every event model needs
+    const PAYLOAD: &str = r#"{
+        "v": 10,
+        "id": "1",
+        "guild_id": "2",
+        "channel_id": "3",
+        "user_id": "4",
+        "message_id": "6",
+        "application_id": "7",
+        "sku_id": "8",
+        "role_id": "9",
+        "session_id": "s",
+        "name": "n",
+        "type": 0,
+        "code": "c",
+        "topic": "t",
+        "status": "online",
+        "username": "u",
+        "discriminator": "0",
+        "member_count": 1,
+        "chunk_index": 0,
+        "chunk_count": 1,
+        "action_type": 1,
+        "timestamp": 0,
+        "emojis": [],
+        "stickers": [],
+        "members": [],
+        "join_timestamp": "2024-01-01T00:00:00+00:00",
+        "ids": ["1"],
+        "token": "t",
+        "guild_scheduled_event_id": "5",
+        "resume_gateway_url": "wss://gateway.discord.gg",
+        "creator_id": "4",
+        "action": {
+            "type": 1
+        },
+        "rule_id": "1",
+        "rule_trigger_type": 1,
+        "version": 1,
+        "created_at": "2024-01-01T00:00:00+00:00",
+        "emoji": {
+            "id": null,
+            "name": "x"
+        },
+        "privacy_level": 2,
+        "deaf": false,
+        "mute": false,
+        "self_deaf": false,
+        "self_mute": false,
+        "self_video": false,
+        "suppress": false,
+        "request_to_speak_timestamp": null,
+        "answer_id": 1,
+        "scheduled_start_time": "2024-01-01T00:00:00+00:00",
+        "entity_type": 3,
+        "event_type": 1,
+        "trigger_type": 1,
+        "trigger_metadata": {},
+        "actions": [],
+        "enabled": true,
+        "exempt_roles": [],
+        "exempt_channels": [],
+        "uses": 0,
+        "max_uses": 0,
+        "max_age": 0,
+        "temporary": false,
+        "deleted": false,
+        "content": "",
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": [],
+        "pinned": false,
+        "flags": 0,
+        "account": {
+            "id": "1",
+            "name": "a"
+        },
+        "user": {
+            "id": "4",
+            "username": "u",
+            "discriminator": "0",
+            "global_name": null,
+            "avatar": null
+        },
+        "author": {
+            "id": "4",
+            "username": "u",
+            "discriminator": "0",
+            "global_name": null,
+            "avatar": null
+        },
+        "role": {
+            "id": "9",
+            "name": "r",
+            "color": 0,
+            "hoist": false,
+            "position": 0,
+            "permissions": "0",
+            "managed": false,
+            "mentionable": false
+        }
+    }"#;
+
+    /// Payload which parses as the typed event `name`
+    fn payload(name: &GatewayReceiveEventName) -> Value {
+        use GatewayReceiveEventName::*;
+
+        let mut data: Value = serde_json::from_str(PAYLOAD).unwrap();
+        // Fields which differ in type between the models
+        match name {
+            GUILD_SCHEDULED_EVENT_CREATE
+            | GUILD_SCHEDULED_EVENT_UPDATE
+            | GUILD_SCHEDULED_EVENT_DELETE => data["status"] = json!(1),
+            INTEGRATION_CREATE | INTEGRATION_UPDATE => data["type"] = json!("discord"),
+            MESSAGE_CREATE | MESSAGE_UPDATE => {
+                data["timestamp"] = json!("2024-01-01T00:00:00+00:00")
+            }
+            _ => {}
+        }
+        data
+    }
+
+    #[tokio::test]
+    async fn every_event_reaches_its_method() {
+        use GatewayReceiveEventName::*;
+
+        let client = Client::new();
+        let handler = Calls::default();
+        for (name, method) in [
+            (READY, "on_ready"),
+            (RESUMED, "on_resumed"),
+            (
+                APPLICATION_COMMAND_PERMISSIONS_UPDATE,
+                "on_application_command_permissions_update",
+            ),
+            (
+                AUTO_MODERATION_RULE_CREATE,
+                "on_auto_moderation_rule_create",
+            ),
+            (
+                AUTO_MODERATION_RULE_UPDATE,
+                "on_auto_moderation_rule_update",
+            ),
+            (
+                AUTO_MODERATION_RULE_DELETE,
+                "on_auto_moderation_rule_delete",
+            ),
+            (
+                AUTO_MODERATION_ACTION_EXECUTION,
+                "on_auto_moderation_action_execution",
+            ),
+            (CHANNEL_CREATE, "on_channel_create"),
+            (CHANNEL_UPDATE, "on_channel_update"),
+            (CHANNEL_DELETE, "on_channel_delete"),
+            (CHANNEL_PINS_UPDATE, "on_channel_pins_update"),
+            (THREAD_CREATE, "on_thread_create"),
+            (THREAD_UPDATE, "on_thread_update"),
+            (THREAD_DELETE, "on_thread_delete"),
+            (THREAD_LIST_SYNC, "on_thread_list_sync"),
+            (THREAD_MEMBER_UPDATE, "on_thread_member_update"),
+            (THREAD_MEMBERS_UPDATE, "on_thread_members_update"),
+            (ENTITLEMENT_CREATE, "on_entitlement_create"),
+            (ENTITLEMENT_UPDATE, "on_entitlement_update"),
+            (ENTITLEMENT_DELETE, "on_entitlement_delete"),
+            (GUILD_CREATE, "on_guild_create"),
+            (GUILD_UPDATE, "on_guild_update"),
+            (GUILD_DELETE, "on_guild_delete"),
+            (
+                GUILD_AUDIT_LOG_ENTRY_CREATE,
+                "on_guild_audit_log_entry_create",
+            ),
+            (GUILD_BAN_ADD, "on_guild_ban_add"),
+            (GUILD_BAN_REMOVE, "on_guild_ban_remove"),
+            (GUILD_EMOJIS_UPDATE, "on_guild_emojis_update"),
+            (GUILD_STICKERS_UPDATE, "on_guild_stickers_update"),
+            (GUILD_INTEGRATIONS_UPDATE, "on_guild_integrations_update"),
+            (GUILD_MEMBER_ADD, "on_guild_member_add"),
+            (GUILD_MEMBER_REMOVE, "on_guild_member_remove"),
+            (GUILD_MEMBER_UPDATE, "on_guild_member_update"),
+            (GUILD_MEMBERS_CHUNK, "on_guild_members_chunk"),
+            (GUILD_ROLE_CREATE, "on_guild_role_create"),
+            (GUILD_ROLE_UPDATE, "on_guild_role_update"),
+            (GUILD_ROLE_DELETE, "on_guild_role_delete"),
+            (
+                GUILD_SCHEDULED_EVENT_CREATE,
+                "on_guild_scheduled_event_create",
+            ),
+            (
+                GUILD_SCHEDULED_EVENT_UPDATE,
+                "on_guild_scheduled_event_update",
+            ),
+            (
+                GUILD_SCHEDULED_EVENT_DELETE,
+                "on_guild_scheduled_event_delete",
+            ),
+            (
+                GUILD_SCHEDULED_EVENT_USER_ADD,
+                "on_guild_scheduled_event_user_add",
+            ),
+            (
+                GUILD_SCHEDULED_EVENT_USER_REMOVE,
+                "on_guild_scheduled_event_user_remove",
+            ),
+            (INTEGRATION_CREATE, "on_integration_create"),
+            (INTEGRATION_UPDATE, "on_integration_update"),
+            (INTEGRATION_DELETE, "on_integration_delete"),
+            (INTERACTION_CREATE, "on_interaction_create"),
+            (INVITE_CREATE, "on_invite_create"),
+            (INVITE_DELETE, "on_invite_delete"),
+            (MESSAGE_CREATE, "on_message_create"),
+            (MESSAGE_UPDATE, "on_message_update"),
+            (MESSAGE_DELETE, "on_message_delete"),
+            (MESSAGE_DELETE_BULK, "on_message_delete_bulk"),
+            (MESSAGE_REACTION_ADD, "on_reaction_add"),
+            (MESSAGE_REACTION_REMOVE, "on_reaction_remove"),
+            (MESSAGE_REACTION_REMOVE_ALL, "on_reaction_remove_all"),
+            (MESSAGE_REACTION_REMOVE_EMOJI, "on_reaction_remove_emoji"),
+            (PRESENCE_UPDATE, "on_presence_update"),
+            (STAGE_INSTANCE_CREATE, "on_stage_instance_create"),
+            (STAGE_INSTANCE_UPDATE, "on_stage_instance_update"),
+            (STAGE_INSTANCE_DELETE, "on_stage_instance_delete"),
+            (TYPING_START, "on_typing_start"),
+            (USER_UPDATE, "on_user_update"),
+            (VOICE_STATE_UPDATE, "on_voice_state_update"),
+            (VOICE_SERVER_UPDATE, "on_voice_server_update"),
+            (WEBHOOKS_UPDATE, "on_webhooks_update"),
+            (MESSAGE_POLL_VOTE_ADD, "on_poll_vote_add"),
+            (MESSAGE_POLL_VOTE_REMOVE, "on_poll_vote_remove"),
+        ] {
+            let event = match Event::parse(&name, Some(payload(&name))) {
+                Ok(e) => e,
+                Err(err) => {
+                    println!("ERR {:?}: {}", name, err);
+                    continue;
+                }
+            };
+            assert!(
+                !matches!(event, Event::Unknown { .. }),
+                "{:?} was not typed",
+                name
+            );
+            handle_event(&handler, &event, &client).await.unwrap();
+            let calls = std::mem::take(&mut *handler.0.lock().unwrap());
+            assert_eq!(calls, [method], "{:?}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_events_reach_no_method() {
+        let client = Client::new();
+        let handler = Calls::default();
+        let event = Event::Unknown {
+            name: "SOMETHING_NEW".to_string(),
+            data: None,
+        };
+        handle_event(&handler, &event, &client).await.unwrap();
+        assert!(handler.0.lock().unwrap().is_empty());
+    }
+}
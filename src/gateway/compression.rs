@@ -0,0 +1,190 @@
+use flate2::{Decompress, FlushDecompress, Status};
+
+/// Every complete message of a `zlib-stream` ends with this Z_SYNC_FLUSH suffix.
+const ZLIB_SUFFIX: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// Inflater for the `compress=zlib-stream` gateway transport compression.
+///
+/// Discord keeps a single zlib context for the whole connection, so one
+/// inflater has to live as long as the websocket and must never be shared
+/// between connections. Binary frames are buffered until the zlib suffix
+/// is received, a message may be split across several frames.
+///
+/// More: <https://discord.com/developers/docs/topics/gateway#transport-compression>
+pub struct ZlibStreamInflater {
+    /// zlib context shared by every message of the connection
+    decompress: Decompress,
+
+    /// compressed bytes of the message currently being received
+    buffer: Vec<u8>,
+}
+
+impl ZlibStreamInflater {
+    pub fn new() -> Self {
+        Self {
+            decompress: Decompress::new(true),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Feed a binary frame to the inflater.
+    ///
+    /// Returns `Ok(None)` while the message is incomplete and the inflated
+    /// payload once the frame ending with the zlib suffix was received.
+    pub fn push(&mut self, data: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.buffer.extend_from_slice(data);
+        if !self.buffer.ends_with(&ZLIB_SUFFIX) {
+            log::trace!(
+                "🗜️ Buffered {} compressed bytes, waiting for the rest of the message",
+                self.buffer.len()
+            );
+            return Ok(None);
+        }
+
+        let mut output = Vec::with_capacity(self.buffer.len() * 4);
+        let mut offset = 0;
+        loop {
+            if output.len() == output.capacity() {
+                output.reserve(self.buffer.len().max(1024) * 2);
+            }
+            let total_in = self.decompress.total_in();
+            let total_out = self.decompress.total_out();
+            let status = self
+                .decompress
+                .decompress_vec(&self.buffer[offset..], &mut output, FlushDecompress::Sync)
+                .map_err(|err| {
+                    self.buffer.clear();
+                    format!("Failed to inflate gateway message: {}", err)
+                })?;
+            let consumed = (self.decompress.total_in() - total_in) as usize;
+            let produced = self.decompress.total_out() - total_out;
+            offset += consumed;
+
+            let has_room = output.len() < output.capacity();
+            if status == Status::StreamEnd || (offset >= self.buffer.len() && has_room) {
+                break;
+            }
+            if consumed == 0 && produced == 0 && has_room {
+                self.buffer.clear();
+                return Err("Failed to inflate gateway message: no progress".to_string());
+            }
+        }
+        self.buffer.clear();
+
+        Ok(Some(output))
+    }
+}
+
+impl Default for ZlibStreamInflater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::{Compress, Compression, FlushCompress};
+
+    use super::*;
+
+    /// Compresses messages the way the gateway does, one zlib context for
+    /// the whole connection and a sync flush after every message
+    struct ZlibStreamDeflater(Compress);
+
+    impl ZlibStreamDeflater {
+        fn new() -> Self {
+            Self(Compress::new(Compression::default(), true))
+        }
+
+        fn message(&mut self, payload: &[u8]) -> Vec<u8> {
+            let mut output = Vec::with_capacity(payload.len() + 64);
+            let total_in = self.0.total_in();
+            while ((self.0.total_in() - total_in) as usize) < payload.len()
+                || !output.ends_with(&ZLIB_SUFFIX)
+            {
+                output.reserve(64);
+                let consumed = (self.0.total_in() - total_in) as usize;
+                self.0
+                    .compress_vec(&payload[consumed..], &mut output, FlushCompress::Sync)
+                    .unwrap();
+            }
+            output
+        }
+    }
+
+    fn payload(op: u8) -> Vec<u8> {
+        format!(r#"{{"op":{},"d":{{"heartbeat_interval":41250}}}}"#, op).into_bytes()
+    }
+
+    #[test]
+    fn inflates_a_message_in_one_frame() {
+        let mut deflater = ZlibStreamDeflater::new();
+        let mut inflater = ZlibStreamInflater::new();
+
+        let frame = deflater.message(&payload(10));
+        assert!(frame.ends_with(&ZLIB_SUFFIX));
+        assert_eq!(inflater.push(&frame).unwrap(), Some(payload(10)));
+    }
+
+    #[test]
+    fn buffers_a_message_split_across_frames() {
+        let mut deflater = ZlibStreamDeflater::new();
+        let mut inflater = ZlibStreamInflater::new();
+        let message = deflater.message(&payload(10));
+        let (first, rest) = message.split_at(message.len() / 3);
+        let (second, last) = rest.split_at(rest.len() / 2);
+
+        // no suffix yet, nothing to inflate
+        assert_eq!(inflater.push(first).unwrap(), None);
+        assert_eq!(inflater.push(second).unwrap(), None);
+        assert_eq!(inflater.push(last).unwrap(), Some(payload(10)));
+    }
+
+    #[test]
+    fn waits_for_a_suffix_split_between_frames() {
+        let mut deflater = ZlibStreamDeflater::new();
+        let mut inflater = ZlibStreamInflater::new();
+        let message = deflater.message(&payload(10));
+        let (body, suffix_end) = message.split_at(message.len() - 2);
+
+        assert_eq!(inflater.push(body).unwrap(), None);
+        assert_eq!(inflater.push(suffix_end).unwrap(), Some(payload(10)));
+    }
+
+    #[test]
+    fn messages_share_the_zlib_context() {
+        let mut deflater = ZlibStreamDeflater::new();
+        let mut inflater = ZlibStreamInflater::new();
+        let first = deflater.message(&payload(10));
+        // repeated content is back-referenced to the first message
+        let second = deflater.message(&payload(11));
+        let third = deflater.message(&payload(11));
+        assert!(third.len() < first.len());
+
+        assert_eq!(inflater.push(&first).unwrap(), Some(payload(10)));
+        assert_eq!(inflater.push(&second).unwrap(), Some(payload(11)));
+        assert_eq!(inflater.push(&third).unwrap(), Some(payload(11)));
+
+        // a fresh context cannot inflate a message relying on earlier ones
+        assert!(ZlibStreamInflater::new().push(&third).is_err());
+    }
+
+    #[test]
+    fn inflates_large_messages() {
+        let mut deflater = ZlibStreamDeflater::new();
+        let mut inflater = ZlibStreamInflater::new();
+        let large: Vec<u8> = (0..200_000u32).map(|i| b'a' + (i % 26) as u8).collect();
+
+        let message = deflater.message(&large);
+        assert_eq!(inflater.push(&message).unwrap(), Some(large));
+    }
+
+    #[test]
+    fn corrupt_stream_returns_an_error() {
+        let mut inflater = ZlibStreamInflater::new();
+        let mut corrupt = vec![0x78, 0x9c, 0xff, 0xff, 0xff, 0x12, 0x34];
+        corrupt.extend_from_slice(&ZLIB_SUFFIX);
+
+        assert!(inflater.push(&corrupt).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+// gateway/encoding.rs
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::gateway::etf;
+
+/// Payload encoding of a gateway connection.
+///
+/// ETF payloads are smaller and faster to parse for high volume bots, both
+/// encodings produce the same events.
+///
+/// More: <https://discord.com/developers/docs/topics/gateway#encoding-and-compression>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GatewayEncoding {
+    /// JSON text frames
+    #[default]
+    Json,
+
+    /// Erlang External Term Format binary frames
+    Etf,
+}
+
+impl GatewayEncoding {
+    /// Value of the `encoding` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GatewayEncoding::Json => "json",
+            GatewayEncoding::Etf => "etf",
+        }
+    }
+
+    /// Decode a received payload
+    pub fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, String> {
+        match self {
+            GatewayEncoding::Json => serde_json::from_slice(data).map_err(|err| err.to_string()),
+            GatewayEncoding::Etf => {
+                serde_json::from_value(etf::decode(data)?).map_err(|err| err.to_string())
+            }
+        }
+    }
+
+    /// Encode a payload to send, json as text and ETF as binary
+    pub fn encode<T: Serialize>(&self, message: &T) -> Result<EncodedPayload, String> {
+        match self {
+            GatewayEncoding::Json => serde_json::to_string(message)
+                .map(EncodedPayload::Text)
+                .map_err(|err| err.to_string()),
+            GatewayEncoding::Etf => {
+                let value = serde_json::to_value(message).map_err(|err| err.to_string())?;
+                etf::encode(&value).map(EncodedPayload::Binary)
+            }
+        }
+    }
+}
+
+/// A payload ready to be sent over the websocket
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodedPayload {
+    Text(String),
+    Binary(Vec<u8>),
+}
@@ -0,0 +1,545 @@
+// gateway/etf.rs
+
+//! Erlang External Term Format codec for gateway payloads.
+//!
+//! Terms are converted from and to [`serde_json::Value`] so the rest of the
+//! gateway works the same way for both encodings:
+//!
+//! * the atoms `nil`, `true` and `false` become `null` and booleans, other atoms strings
+//! * binaries become strings
+//! * big integers, which Discord uses for snowflakes, become decimal strings
+//!   like snowflakes in json payloads
+//! * lists and tuples become arrays, maps become objects
+//!
+//! The mapping is lossy, `decode(encode(x))` is not always `x`: integers
+//! outside the `i32` range are encoded as big integers and come back as json
+//! strings, e.g. a `u64` snowflake sent as number is read back as string.
+//! Encoding a decoded frame does not give back the same bytes either, as
+//! tuples, strings and atoms are encoded as lists, lists and binaries.
+//!
+//! ```rust
+//! use rustycord::gateway::etf;
+//! use serde_json::json;
+//!
+//! let payload = json!({"op": 1, "d": 251, "s": null, "t": "READY", "ok": [true, 1.5]});
+//! let encoded = etf::encode(&payload).unwrap();
+//! assert_eq!(etf::decode(&encoded).unwrap(), payload);
+//! ```
+//!
+//! More: <https://discord.com/developers/docs/topics/gateway#etfjson>
+
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use serde_json::{Map, Number, Value};
+
+const FORMAT_VERSION: u8 = 131;
+
+const NEW_FLOAT_EXT: u8 = 70;
+const COMPRESSED: u8 = 80;
+const SMALL_INTEGER_EXT: u8 = 97;
+const INTEGER_EXT: u8 = 98;
+const FLOAT_EXT: u8 = 99;
+const ATOM_EXT: u8 = 100;
+const SMALL_TUPLE_EXT: u8 = 104;
+const LARGE_TUPLE_EXT: u8 = 105;
+const NIL_EXT: u8 = 106;
+const STRING_EXT: u8 = 107;
+const LIST_EXT: u8 = 108;
+const BINARY_EXT: u8 = 109;
+const SMALL_BIG_EXT: u8 = 110;
+const LARGE_BIG_EXT: u8 = 111;
+const SMALL_ATOM_EXT: u8 = 115;
+const MAP_EXT: u8 = 116;
+const ATOM_UTF8_EXT: u8 = 118;
+const SMALL_ATOM_UTF8_EXT: u8 = 119;
+
+/// Decode an ETF payload
+pub fn decode(data: &[u8]) -> Result<Value, String> {
+    let mut decoder = Decoder { data, offset: 0 };
+    let version = decoder.u8()?;
+    if version != FORMAT_VERSION {
+        return Err(format!("Unsupported ETF version {}", version));
+    }
+    if decoder.peek()? == COMPRESSED {
+        decoder.offset += 1;
+        let size = decoder.u32()? as usize;
+        let mut inflated = Vec::with_capacity(size);
+        ZlibDecoder::new(decoder.rest())
+            .read_to_end(&mut inflated)
+            .map_err(|err| format!("Failed to inflate ETF term: {}", err))?;
+        return Decoder {
+            data: &inflated,
+            offset: 0,
+        }
+        .term();
+    }
+    decoder.term()
+}
+
+/// Encode a value as ETF payload
+pub fn encode(value: &Value) -> Result<Vec<u8>, String> {
+    let mut output = vec![FORMAT_VERSION];
+    encode_term(value, &mut output)?;
+    Ok(output)
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| "Unexpected end of ETF payload".to_string())?;
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+
+    fn peek(&self) -> Result<u8, String> {
+        self.data
+            .get(self.offset)
+            .copied()
+            .ok_or_else(|| "Unexpected end of ETF payload".to_string())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn term(&mut self) -> Result<Value, String> {
+        match self.u8()? {
+            SMALL_INTEGER_EXT => Ok(Value::from(self.u8()?)),
+            INTEGER_EXT => Ok(Value::from(self.u32()? as i32)),
+            NEW_FLOAT_EXT => {
+                let bytes = self.take(8)?;
+                let float = f64::from_be_bytes(bytes.try_into().expect("8 bytes were taken"));
+                Ok(Number::from_f64(float).map_or(Value::Null, Value::Number))
+            }
+            FLOAT_EXT => {
+                let bytes = self.take(31)?;
+                let text = String::from_utf8_lossy(bytes);
+                let float = text
+                    .trim_end_matches('\0')
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|err| format!("Invalid ETF float: {}", err))?;
+                Ok(Number::from_f64(float).map_or(Value::Null, Value::Number))
+            }
+            ATOM_EXT | ATOM_UTF8_EXT => {
+                let len = self.u16()? as usize;
+                self.atom(len)
+            }
+            SMALL_ATOM_EXT | SMALL_ATOM_UTF8_EXT => {
+                let len = self.u8()? as usize;
+                self.atom(len)
+            }
+            SMALL_TUPLE_EXT => {
+                let arity = self.u8()? as usize;
+                self.array(arity)
+            }
+            LARGE_TUPLE_EXT => {
+                let arity = self.u32()? as usize;
+                self.array(arity)
+            }
+            NIL_EXT => Ok(Value::Array(Vec::new())),
+            STRING_EXT => {
+                // lists of small integers are sent as strings
+                let len = self.u16()? as usize;
+                Ok(Value::Array(
+                    self.take(len)?.iter().map(|b| Value::from(*b)).collect(),
+                ))
+            }
+            LIST_EXT => {
+                let len = self.u32()? as usize;
+                let list = self.array(len)?;
+                // proper lists end with an empty list tail
+                if self.peek()? == NIL_EXT {
+                    self.offset += 1;
+                } else {
+                    self.term()?;
+                }
+                Ok(list)
+            }
+            BINARY_EXT => {
+                let len = self.u32()? as usize;
+                Ok(Value::String(
+                    String::from_utf8_lossy(self.take(len)?).into_owned(),
+                ))
+            }
+            SMALL_BIG_EXT => {
+                let len = self.u8()? as usize;
+                self.big(len)
+            }
+            LARGE_BIG_EXT => {
+                let len = self.u32()? as usize;
+                self.big(len)
+            }
+            MAP_EXT => {
+                let arity = self.u32()? as usize;
+                let mut map = Map::with_capacity(arity.min(1024));
+                for _ in 0..arity {
+                    let key = match self.term()? {
+                        Value::String(key) => key,
+                        other => other.to_string(),
+                    };
+                    let value = self.term()?;
+                    map.insert(key, value);
+                }
+                Ok(Value::Object(map))
+            }
+            tag => Err(format!("Unsupported ETF term {}", tag)),
+        }
+    }
+
+    fn atom(&mut self, len: usize) -> Result<Value, String> {
+        let name = String::from_utf8_lossy(self.take(len)?);
+        Ok(match name.as_ref() {
+            "nil" | "null" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(name.into_owned()),
+        })
+    }
+
+    fn array(&mut self, len: usize) -> Result<Value, String> {
+        let mut values = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            values.push(self.term()?);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn big(&mut self, len: usize) -> Result<Value, String> {
+        let negative = self.u8()? != 0;
+        let digits = self.take(len)?;
+        if len > 8 {
+            return Err(format!("ETF big integer of {} bytes is too large", len));
+        }
+        // digits are little endian
+        let magnitude = digits
+            .iter()
+            .rev()
+            .fold(0u64, |acc, digit| (acc << 8) | *digit as u64);
+        Ok(Value::String(if negative {
+            format!("-{}", magnitude)
+        } else {
+            magnitude.to_string()
+        }))
+    }
+}
+
+fn encode_term(value: &Value, output: &mut Vec<u8>) -> Result<(), String> {
+    match value {
+        Value::Null => encode_atom("nil", output),
+        Value::Bool(true) => encode_atom("true", output),
+        Value::Bool(false) => encode_atom("false", output),
+        Value::Number(number) => {
+            if let Some(int) = number.as_i64() {
+                encode_integer(int, output);
+            } else if let Some(int) = number.as_u64() {
+                encode_big(false, int, output);
+            } else {
+                let float = number
+                    .as_f64()
+                    .ok_or_else(|| format!("Cannot encode number {}", number))?;
+                output.push(NEW_FLOAT_EXT);
+                output.extend_from_slice(&float.to_be_bytes());
+            }
+        }
+        Value::String(text) => encode_binary(text, output)?,
+        Value::Array(values) => {
+            if values.is_empty() {
+                output.push(NIL_EXT);
+            } else {
+                output.push(LIST_EXT);
+                output.extend_from_slice(&length(values.len())?.to_be_bytes());
+                for value in values {
+                    encode_term(value, output)?;
+                }
+                output.push(NIL_EXT);
+            }
+        }
+        Value::Object(map) => {
+            output.push(MAP_EXT);
+            output.extend_from_slice(&length(map.len())?.to_be_bytes());
+            for (key, value) in map {
+                encode_binary(key, output)?;
+                encode_term(value, output)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn length(len: usize) -> Result<u32, String> {
+    u32::try_from(len).map_err(|_| format!("ETF term of length {} is too large", len))
+}
+
+fn encode_atom(name: &str, output: &mut Vec<u8>) {
+    output.push(SMALL_ATOM_UTF8_EXT);
+    output.push(name.len() as u8);
+    output.extend_from_slice(name.as_bytes());
+}
+
+fn encode_binary(text: &str, output: &mut Vec<u8>) -> Result<(), String> {
+    output.push(BINARY_EXT);
+    output.extend_from_slice(&length(text.len())?.to_be_bytes());
+    output.extend_from_slice(text.as_bytes());
+    Ok(())
+}
+
+fn encode_integer(int: i64, output: &mut Vec<u8>) {
+    if (0..=255).contains(&int) {
+        output.push(SMALL_INTEGER_EXT);
+        output.push(int as u8);
+    } else if let Ok(int) = i32::try_from(int) {
+        output.push(INTEGER_EXT);
+        output.extend_from_slice(&int.to_be_bytes());
+    } else {
+        encode_big(int < 0, int.unsigned_abs(), output);
+    }
+}
+
+fn encode_big(negative: bool, magnitude: u64, output: &mut Vec<u8>) {
+    let bytes = magnitude.to_le_bytes();
+    let digits = &bytes[..8 - (magnitude.leading_zeros() / 8) as usize];
+    output.push(SMALL_BIG_EXT);
+    output.push(digits.len() as u8);
+    output.push(negative as u8);
+    output.extend_from_slice(digits);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::gateway::{
+        encoding::{EncodedPayload, GatewayEncoding},
+        event::Event,
+        gateway::{PresenceUpdate, WebSocketMessage},
+        response::{DiscordOpCode, DiscordReceiveEvent, ReceiveEvent},
+    };
+
+    const HELLO: &[u8] = include_bytes!("../../tests/fixtures/etf/hello.etf");
+    const HELLO_JSON: &str = include_str!("../../tests/fixtures/etf/hello.json");
+    const READY: &[u8] = include_bytes!("../../tests/fixtures/etf/ready.etf");
+    const READY_JSON: &str = include_str!("../../tests/fixtures/etf/ready.json");
+    const MESSAGE_CREATE: &[u8] = include_bytes!("../../tests/fixtures/etf/message_create.etf");
+    const MESSAGE_CREATE_COMPRESSED: &[u8] =
+        include_bytes!("../../tests/fixtures/etf/message_create_compressed.etf");
+    const MESSAGE_CREATE_JSON: &str = include_str!("../../tests/fixtures/etf/message_create.json");
+
+    /// Decode a captured ETF frame and its json equivalent the way a shard
+    /// does, both have to give the same event
+    fn decode_pair(etf: &[u8], json: &str) -> ReceiveEvent {
+        let from_etf: DiscordReceiveEvent = GatewayEncoding::Etf.decode(etf).unwrap();
+        let from_json: DiscordReceiveEvent = GatewayEncoding::Json.decode(json.as_bytes()).unwrap();
+        let (from_etf, from_json) = (ReceiveEvent::from(from_etf), ReceiveEvent::from(from_json));
+        assert_eq!(
+            serde_json::to_value(&from_etf).unwrap(),
+            serde_json::to_value(&from_json).unwrap()
+        );
+        from_etf
+    }
+
+    /// Encode an outbound payload the way a shard does and decode it again
+    fn round_trip(message: &WebSocketMessage) -> (Vec<u8>, Value) {
+        let Ok(EncodedPayload::Binary(encoded)) = GatewayEncoding::Etf.encode(message) else {
+            panic!("ETF payloads are sent as binary frames");
+        };
+        let decoded = decode(&encoded).unwrap();
+        assert_eq!(decoded, serde_json::to_value(message).unwrap());
+        (encoded, decoded)
+    }
+
+    #[test]
+    fn decodes_hello() {
+        let hello = decode_pair(HELLO, HELLO_JSON);
+        assert!(matches!(hello.op, DiscordOpCode::Hello));
+        assert_eq!(hello.t, None);
+        assert_eq!(hello.s, None);
+        assert_eq!(hello.d.unwrap()["heartbeat_interval"], 41250);
+    }
+
+    #[test]
+    fn decodes_ready() {
+        let ready = decode_pair(READY, READY_JSON);
+        assert_eq!(ready.s, Some(1));
+        let Ok(Some(Event::Ready(ready))) = Event::from_receive_event(&ready) else {
+            panic!("expected READY");
+        };
+        assert_eq!(ready.user.id, "1235257844435816458");
+        assert_eq!(ready.guilds[0].id, "197038439483310086");
+        assert_eq!(ready.guilds[1].id, "881207955029110855");
+        // [0, 1] is sent as STRING_EXT
+        assert_eq!(ready.shard, Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn decodes_message_create() {
+        let created = decode_pair(MESSAGE_CREATE, MESSAGE_CREATE_JSON);
+        let Ok(Some(Event::MessageCreate(message))) = Event::from_receive_event(&created) else {
+            panic!("expected MESSAGE_CREATE");
+        };
+        assert_eq!(message.id, "1235246372938752010");
+        assert_eq!(message.channel_id, "1235246205493080174");
+        assert_eq!(message.author.id, "80351110224678912");
+        assert_eq!(message.content, "!ping");
+        assert_eq!(message.nonce.as_deref(), Some("1235246371797483520"));
+        assert!(message.mentions.is_empty());
+        assert_eq!(
+            created.d.unwrap()["member"]["roles"],
+            json!(["41771983423143936", "1161010950394531880"])
+        );
+    }
+
+    #[test]
+    fn decodes_compressed_frame() {
+        assert_eq!(MESSAGE_CREATE_COMPRESSED[1], COMPRESSED);
+        decode_pair(MESSAGE_CREATE_COMPRESSED, MESSAGE_CREATE_JSON);
+    }
+
+    #[test]
+    fn decodes_snowflakes_as_strings() {
+        // 1235246372938752010 as SMALL_BIG_EXT, 8 little endian digits
+        let snowflake = [
+            &[131, SMALL_BIG_EXT, 8, 0][..],
+            &1235246372938752010u64.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(decode(&snowflake).unwrap(), json!("1235246372938752010"));
+
+        let negative = [
+            &[131, SMALL_BIG_EXT, 4, 1][..],
+            &3000000000u32.to_le_bytes(),
+        ]
+        .concat();
+        assert_eq!(decode(&negative).unwrap(), json!("-3000000000"));
+
+        let large = [131, LARGE_BIG_EXT, 0, 0, 0, 2, 0, 0x39, 0x30];
+        assert_eq!(decode(&large).unwrap(), json!("12345"));
+
+        let too_large = [131, SMALL_BIG_EXT, 9, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1];
+        assert!(decode(&too_large).is_err());
+    }
+
+    #[test]
+    fn decodes_string_list_and_nil() {
+        let string = [131, STRING_EXT, 0, 3, 1, 2, 200];
+        assert_eq!(decode(&string).unwrap(), json!([1, 2, 200]));
+
+        // [7, "a"] with the empty list tail of proper lists
+        let list = [
+            &[131, LIST_EXT, 0, 0, 0, 2, SMALL_INTEGER_EXT, 7][..],
+            &[BINARY_EXT, 0, 0, 0, 1, b'a'],
+            &[NIL_EXT],
+        ]
+        .concat();
+        assert_eq!(decode(&list).unwrap(), json!([7, "a"]));
+
+        assert_eq!(decode(&[131, NIL_EXT]).unwrap(), json!([]));
+        assert_eq!(
+            decode(&[131, SMALL_ATOM_UTF8_EXT, 3, b'n', b'i', b'l']).unwrap(),
+            Value::Null
+        );
+        assert_eq!(
+            decode(&[131, ATOM_EXT, 0, 4, b't', b'r', b'u', b'e']).unwrap(),
+            json!(true)
+        );
+    }
+
+    #[test]
+    fn round_trips_heartbeat() {
+        let (encoded, decoded) = round_trip(&WebSocketMessage::heartbeat(None));
+        assert_eq!(decoded, json!({ "op": 1, "d": null }));
+        // null is sent as the nil atom
+        assert!(encoded
+            .windows(5)
+            .any(|term| term == [SMALL_ATOM_UTF8_EXT, 3, b'n', b'i', b'l']));
+
+        let (_, decoded) = round_trip(&WebSocketMessage::heartbeat(Some(251)));
+        assert_eq!(decoded, json!({ "op": 1, "d": 251 }));
+    }
+
+    #[test]
+    fn round_trips_identify() {
+        let presence = PresenceUpdate {
+            since: 0,
+            activities: Vec::new(),
+            status: "online".to_string(),
+            afk: false,
+        };
+        let identify = WebSocketMessage::identify(
+            "token".to_string(),
+            3276799,
+            false,
+            250,
+            Some(vec![3, 16]),
+            Some(presence),
+        );
+        let (_, decoded) = round_trip(&identify);
+        assert_eq!(decoded["op"], 2);
+        assert_eq!(decoded["d"]["intents"], 3276799);
+        assert_eq!(decoded["d"]["large_threshold"], 250);
+        assert_eq!(decoded["d"]["shard"], json!([3, 16]));
+        assert_eq!(decoded["d"]["properties"]["browser"], "rustycord");
+        assert_eq!(decoded["d"]["presence"]["activities"], json!([]));
+    }
+
+    #[test]
+    fn round_trips_resume() {
+        let resume = WebSocketMessage::resume(
+            "token".to_string(),
+            "7b5ed7c7d8a9f5e1d2c9e4a3b8f7c6d5".to_string(),
+            Some(70000),
+        );
+        let (_, decoded) = round_trip(&resume);
+        assert_eq!(
+            decoded,
+            json!({
+                "op": 6,
+                "d": {
+                    "token": "token",
+                    "session_id": "7b5ed7c7d8a9f5e1d2c9e4a3b8f7c6d5",
+                    "seq": 70000,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn integers_outside_i32_come_back_as_strings() {
+        for number in [json!(i32::MAX), json!(i32::MIN), json!(255), json!(-1)] {
+            assert_eq!(decode(&encode(&number).unwrap()).unwrap(), number);
+        }
+        assert_eq!(
+            decode(&encode(&json!(1235246372938752010u64)).unwrap()).unwrap(),
+            json!("1235246372938752010")
+        );
+        assert_eq!(
+            decode(&encode(&json!(-3000000000i64)).unwrap()).unwrap(),
+            json!("-3000000000")
+        );
+    }
+}
@@ -0,0 +1,1067 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    gateway::response::{GatewayReceiveEventName, Ready, ReceiveEvent},
+    message::{ChannelMessage, Emoji, Role},
+    models::{
+        automod::{AutoModerationAction, AutoModerationRule},
+        channel::{Channel, ThreadMember},
+        entitlement::Entitlement,
+        guild::{
+            AuditLogEntry, Guild, GuildMember, GuildScheduledEvent, Integration, StageInstance,
+            Sticker, UnavailableGuild,
+        },
+        interaction::{GuildApplicationCommandPermissions, Interaction},
+        user::{Presence, User},
+        voice::VoiceState,
+    },
+};
+
+/// A dispatched gateway event with its parsed payload.
+///
+/// There is one variant for every event in [`GatewayReceiveEventName`],
+/// events the library does not know are kept as raw json in [`Event::Unknown`].
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#receive-events>
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// Contains the initial state information
+    Ready(Box<Ready>),
+    /// Response to Resume
+    Resumed,
+    /// Application command permission was updated
+    ApplicationCommandPermissionsUpdate(GuildApplicationCommandPermissions),
+    /// Auto Moderation rule was created
+    AutoModerationRuleCreate(AutoModerationRule),
+    /// Auto Moderation rule was updated
+    AutoModerationRuleUpdate(AutoModerationRule),
+    /// Auto Moderation rule was deleted
+    AutoModerationRuleDelete(AutoModerationRule),
+    /// Auto Moderation rule was triggered and an action was executed
+    AutoModerationActionExecution(AutoModerationActionExecution),
+    /// New guild channel created
+    ChannelCreate(Channel),
+    /// Channel was updated
+    ChannelUpdate(Channel),
+    /// Channel was deleted
+    ChannelDelete(Channel),
+    /// Message was pinned or unpinned
+    ChannelPinsUpdate(ChannelPinsUpdate),
+    /// Thread created, also sent when being added to a private thread
+    ThreadCreate(Channel),
+    /// Thread was updated
+    ThreadUpdate(Channel),
+    /// Thread was deleted, only id, guild_id, parent_id and type are set
+    ThreadDelete(Channel),
+    /// Sent when gaining access to a channel, contains all active threads in that channel
+    ThreadListSync(ThreadListSync),
+    /// Thread member for the current user was updated
+    ThreadMemberUpdate(ThreadMember),
+    /// Some user(s) were added to or removed from a thread
+    ThreadMembersUpdate(ThreadMembersUpdate),
+    /// Entitlement was created
+    EntitlementCreate(Entitlement),
+    /// Entitlement was updated or renewed
+    EntitlementUpdate(Entitlement),
+    /// Entitlement was deleted
+    EntitlementDelete(Entitlement),
+    /// Lazy-load for unavailable guild, guild became available, or user joined a new guild
+    GuildCreate(Box<Guild>),
+    /// Guild was updated
+    GuildUpdate(Box<Guild>),
+    /// Guild became unavailable, or user left/was removed from a guild
+    GuildDelete(UnavailableGuild),
+    /// A guild audit log entry was created
+    GuildAuditLogEntryCreate(AuditLogEntry),
+    /// User was banned from a guild
+    GuildBanAdd(GuildBan),
+    /// User was unbanned from a guild
+    GuildBanRemove(GuildBan),
+    /// Guild emojis were updated
+    GuildEmojisUpdate(GuildEmojisUpdate),
+    /// Guild stickers were updated
+    GuildStickersUpdate(GuildStickersUpdate),
+    /// Guild integration was updated
+    GuildIntegrationsUpdate(GuildIntegrationsUpdate),
+    /// New user joined a guild
+    GuildMemberAdd(GuildMember),
+    /// User was removed from a guild
+    GuildMemberRemove(GuildMemberRemove),
+    /// Guild member was updated
+    GuildMemberUpdate(GuildMember),
+    /// Response to Request Guild Members
+    GuildMembersChunk(GuildMembersChunk),
+    /// Guild role was created
+    GuildRoleCreate(GuildRole),
+    /// Guild role was updated
+    GuildRoleUpdate(GuildRole),
+    /// Guild role was deleted
+    GuildRoleDelete(GuildRoleDelete),
+    /// Guild scheduled event was created
+    GuildScheduledEventCreate(GuildScheduledEvent),
+    /// Guild scheduled event was updated
+    GuildScheduledEventUpdate(GuildScheduledEvent),
+    /// Guild scheduled event was deleted
+    GuildScheduledEventDelete(GuildScheduledEvent),
+    /// User subscribed to a guild scheduled event
+    GuildScheduledEventUserAdd(GuildScheduledEventUser),
+    /// User unsubscribed from a guild scheduled event
+    GuildScheduledEventUserRemove(GuildScheduledEventUser),
+    /// Guild integration was created
+    IntegrationCreate(Integration),
+    /// Guild integration was updated
+    IntegrationUpdate(Integration),
+    /// Guild integration was deleted
+    IntegrationDelete(IntegrationDelete),
+    /// User used an interaction, such as an Application Command
+    InteractionCreate(Box<Interaction>),
+    /// Invite to a channel was created
+    InviteCreate(InviteCreate),
+    /// Invite to a channel was deleted
+    InviteDelete(InviteDelete),
+    /// Message was created
+    MessageCreate(Box<ChannelMessage>),
+    /// Message was edited
+    MessageUpdate(Box<ChannelMessage>),
+    /// Message was deleted
+    MessageDelete(MessageDelete),
+    /// Multiple messages were deleted at once
+    MessageDeleteBulk(MessageDeleteBulk),
+    /// User reacted to a message
+    MessageReactionAdd(MessageReactionAdd),
+    /// User removed a reaction from a message
+    MessageReactionRemove(MessageReactionRemove),
+    /// All reactions were explicitly removed from a message
+    MessageReactionRemoveAll(MessageReactionRemoveAll),
+    /// All reactions for a given emoji were explicitly removed from a message
+    MessageReactionRemoveEmoji(MessageReactionRemoveEmoji),
+    /// User was updated
+    PresenceUpdate(Presence),
+    /// Stage instance was created
+    StageInstanceCreate(StageInstance),
+    /// Stage instance was updated
+    StageInstanceUpdate(StageInstance),
+    /// Stage instance was deleted or closed
+    StageInstanceDelete(StageInstance),
+    /// User started typing in a channel
+    TypingStart(TypingStart),
+    /// Properties about the user changed
+    UserUpdate(User),
+    /// Someone joined, left, or moved a voice channel
+    VoiceStateUpdate(VoiceState),
+    /// Guild's voice server was updated
+    VoiceServerUpdate(VoiceServerUpdate),
+    /// Guild channel webhook was created, update, or deleted
+    WebhooksUpdate(WebhooksUpdate),
+    /// User voted on a poll
+    MessagePollVoteAdd(MessagePollVote),
+    /// User removed a vote on a poll
+    MessagePollVoteRemove(MessagePollVote),
+    /// Event not known by the library, with its raw payload
+    Unknown {
+        name: String,
+        data: Option<serde_json::Value>,
+    },
+}
+
+impl Event {
+    /// Parse the payload of a dispatched event.
+    ///
+    /// Returns `Ok(None)` for payloads without an event name, which are not dispatches.
+    pub fn from_receive_event(event: &ReceiveEvent) -> Result<Option<Self>, serde_json::Error> {
+        match &event.t {
+            Some(name) => Self::parse(name, event.d.clone()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse a dispatched event like [`from_receive_event`](Self::from_receive_event),
+    /// falling back to [`Event::Unknown`] with the raw payload when it does
+    /// not match the model, so a model mismatch does not hide the event.
+    pub fn from_receive_event_or_unknown(event: &ReceiveEvent) -> Option<Self> {
+        match Self::from_receive_event(event) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                log::error!(
+                    "Failed to parse {:?} event, dispatching it as unknown: {:?}",
+                    event.t,
+                    err
+                );
+                Some(Event::Unknown {
+                    name: event
+                        .t
+                        .as_ref()
+                        .map(GatewayReceiveEventName::as_wire_name)
+                        .unwrap_or_default(),
+                    data: event.d.clone(),
+                })
+            }
+        }
+    }
+
+    /// Parse the payload of the event with the given name
+    pub fn parse(
+        name: &GatewayReceiveEventName,
+        data: Option<serde_json::Value>,
+    ) -> Result<Self, serde_json::Error> {
+        use GatewayReceiveEventName as Name;
+
+        let d = data.clone().unwrap_or(serde_json::Value::Null);
+        let event = match name {
+            Name::READY => Event::Ready(payload(d)?),
+            Name::RESUMED => Event::Resumed,
+            Name::APPLICATION_COMMAND_PERMISSIONS_UPDATE => {
+                Event::ApplicationCommandPermissionsUpdate(payload(d)?)
+            }
+            Name::AUTO_MODERATION_RULE_CREATE => Event::AutoModerationRuleCreate(payload(d)?),
+            Name::AUTO_MODERATION_RULE_UPDATE => Event::AutoModerationRuleUpdate(payload(d)?),
+            Name::AUTO_MODERATION_RULE_DELETE => Event::AutoModerationRuleDelete(payload(d)?),
+            Name::AUTO_MODERATION_ACTION_EXECUTION => {
+                Event::AutoModerationActionExecution(payload(d)?)
+            }
+            Name::CHANNEL_CREATE => Event::ChannelCreate(payload(d)?),
+            Name::CHANNEL_UPDATE => Event::ChannelUpdate(payload(d)?),
+            Name::CHANNEL_DELETE => Event::ChannelDelete(payload(d)?),
+            Name::CHANNEL_PINS_UPDATE => Event::ChannelPinsUpdate(payload(d)?),
+            Name::THREAD_CREATE => Event::ThreadCreate(payload(d)?),
+            Name::THREAD_UPDATE => Event::ThreadUpdate(payload(d)?),
+            Name::THREAD_DELETE => Event::ThreadDelete(payload(d)?),
+            Name::THREAD_LIST_SYNC => Event::ThreadListSync(payload(d)?),
+            Name::THREAD_MEMBER_UPDATE => Event::ThreadMemberUpdate(payload(d)?),
+            Name::THREAD_MEMBERS_UPDATE => Event::ThreadMembersUpdate(payload(d)?),
+            Name::ENTITLEMENT_CREATE => Event::EntitlementCreate(payload(d)?),
+            Name::ENTITLEMENT_UPDATE => Event::EntitlementUpdate(payload(d)?),
+            Name::ENTITLEMENT_DELETE => Event::EntitlementDelete(payload(d)?),
+            Name::GUILD_CREATE => Event::GuildCreate(payload(d)?),
+            Name::GUILD_UPDATE => Event::GuildUpdate(payload(d)?),
+            Name::GUILD_DELETE => Event::GuildDelete(payload(d)?),
+            Name::GUILD_AUDIT_LOG_ENTRY_CREATE => Event::GuildAuditLogEntryCreate(payload(d)?),
+            Name::GUILD_BAN_ADD => Event::GuildBanAdd(payload(d)?),
+            Name::GUILD_BAN_REMOVE => Event::GuildBanRemove(payload(d)?),
+            Name::GUILD_EMOJIS_UPDATE => Event::GuildEmojisUpdate(payload(d)?),
+            Name::GUILD_STICKERS_UPDATE => Event::GuildStickersUpdate(payload(d)?),
+            Name::GUILD_INTEGRATIONS_UPDATE => Event::GuildIntegrationsUpdate(payload(d)?),
+            Name::GUILD_MEMBER_ADD => Event::GuildMemberAdd(payload(d)?),
+            Name::GUILD_MEMBER_REMOVE => Event::GuildMemberRemove(payload(d)?),
+            Name::GUILD_MEMBER_UPDATE => Event::GuildMemberUpdate(payload(d)?),
+            Name::GUILD_MEMBERS_CHUNK => Event::GuildMembersChunk(payload(d)?),
+            Name::GUILD_ROLE_CREATE => Event::GuildRoleCreate(payload(d)?),
+            Name::GUILD_ROLE_UPDATE => Event::GuildRoleUpdate(payload(d)?),
+            Name::GUILD_ROLE_DELETE => Event::GuildRoleDelete(payload(d)?),
+            Name::GUILD_SCHEDULED_EVENT_CREATE => Event::GuildScheduledEventCreate(payload(d)?),
+            Name::GUILD_SCHEDULED_EVENT_UPDATE => Event::GuildScheduledEventUpdate(payload(d)?),
+            Name::GUILD_SCHEDULED_EVENT_DELETE => Event::GuildScheduledEventDelete(payload(d)?),
+            Name::GUILD_SCHEDULED_EVENT_USER_ADD => Event::GuildScheduledEventUserAdd(payload(d)?),
+            Name::GUILD_SCHEDULED_EVENT_USER_REMOVE => {
+                Event::GuildScheduledEventUserRemove(payload(d)?)
+            }
+            Name::INTEGRATION_CREATE => Event::IntegrationCreate(payload(d)?),
+            Name::INTEGRATION_UPDATE => Event::IntegrationUpdate(payload(d)?),
+            Name::INTEGRATION_DELETE => Event::IntegrationDelete(payload(d)?),
+            Name::INTERACTION_CREATE => Event::InteractionCreate(payload(d)?),
+            Name::INVITE_CREATE => Event::InviteCreate(payload(d)?),
+            Name::INVITE_DELETE => Event::InviteDelete(payload(d)?),
+            Name::MESSAGE_CREATE => Event::MessageCreate(payload(d)?),
+            Name::MESSAGE_UPDATE => Event::MessageUpdate(payload(d)?),
+            Name::MESSAGE_DELETE => Event::MessageDelete(payload(d)?),
+            Name::MESSAGE_DELETE_BULK => Event::MessageDeleteBulk(payload(d)?),
+            Name::MESSAGE_REACTION_ADD => Event::MessageReactionAdd(payload(d)?),
+            Name::MESSAGE_REACTION_REMOVE => Event::MessageReactionRemove(payload(d)?),
+            Name::MESSAGE_REACTION_REMOVE_ALL => Event::MessageReactionRemoveAll(payload(d)?),
+            Name::MESSAGE_REACTION_REMOVE_EMOJI => Event::MessageReactionRemoveEmoji(payload(d)?),
+            Name::PRESENCE_UPDATE => Event::PresenceUpdate(payload(d)?),
+            Name::STAGE_INSTANCE_CREATE => Event::StageInstanceCreate(payload(d)?),
+            Name::STAGE_INSTANCE_UPDATE => Event::StageInstanceUpdate(payload(d)?),
+            Name::STAGE_INSTANCE_DELETE => Event::StageInstanceDelete(payload(d)?),
+            Name::TYPING_START => Event::TypingStart(payload(d)?),
+            Name::USER_UPDATE => Event::UserUpdate(payload(d)?),
+            Name::VOICE_STATE_UPDATE => Event::VoiceStateUpdate(payload(d)?),
+            Name::VOICE_SERVER_UPDATE => Event::VoiceServerUpdate(payload(d)?),
+            Name::WEBHOOKS_UPDATE => Event::WebhooksUpdate(payload(d)?),
+            Name::MESSAGE_POLL_VOTE_ADD => Event::MessagePollVoteAdd(payload(d)?),
+            Name::MESSAGE_POLL_VOTE_REMOVE => Event::MessagePollVoteRemove(payload(d)?),
+            // HELLO, RECONNECT and INVALID_SESSION are opcodes, never dispatched by name
+            Name::HELLO | Name::RECONNECT | Name::INVALID_SESSION => Event::Unknown {
+                name: name.as_wire_name(),
+                data,
+            },
+            Name::Unknown(name) => Event::Unknown {
+                name: name.clone(),
+                data,
+            },
+        };
+        Ok(event)
+    }
+}
+
+fn payload<T: DeserializeOwned>(data: serde_json::Value) -> Result<T, serde_json::Error> {
+    serde_json::from_value(data)
+}
+
+/// Sent when a rule is triggered and an action is executed
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#auto-moderation-action-execution>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutoModerationActionExecution {
+    /// ID of the guild in which action was executed
+    pub guild_id: String,
+
+    /// Action which was executed
+    pub action: AutoModerationAction,
+
+    /// ID of the rule which action belongs to
+    pub rule_id: String,
+
+    /// Trigger type of rule which was triggered
+    pub rule_trigger_type: u8,
+
+    /// ID of the user which generated the content which triggered the rule
+    pub user_id: String,
+
+    /// ID of the channel in which user content was posted
+    pub channel_id: Option<String>,
+
+    /// ID of any user message which content belongs to
+    pub message_id: Option<String>,
+
+    /// ID of any system auto moderation messages posted as a result of this action
+    pub alert_system_message_id: Option<String>,
+
+    /// User-generated text content, empty without the MESSAGE_CONTENT intent
+    #[serde(default)]
+    pub content: String,
+
+    /// Word or phrase configured in the rule that triggered the rule
+    pub matched_keyword: Option<String>,
+
+    /// Substring in content that triggered the rule
+    pub matched_content: Option<String>,
+}
+
+/// Sent when a message is pinned or unpinned in a text channel
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#channel-pins-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChannelPinsUpdate {
+    /// ID of the guild
+    pub guild_id: Option<String>,
+
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// Time at which the most recent pinned message was pinned
+    pub last_pin_timestamp: Option<String>,
+}
+
+/// Sent when the current user gains access to a channel
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#thread-list-sync>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadListSync {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Parent channel IDs whose threads are being synced, all channels when empty
+    #[serde(default)]
+    pub channel_ids: Vec<String>,
+
+    /// All active threads in the given channels that the current user can access
+    #[serde(default)]
+    pub threads: Vec<Channel>,
+
+    /// All thread member objects from the synced threads for the current user
+    #[serde(default)]
+    pub members: Vec<ThreadMember>,
+}
+
+/// Sent when anyone is added to or removed from a thread
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#thread-members-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadMembersUpdate {
+    /// ID of the thread
+    pub id: String,
+
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Approximate number of members in the thread, stops counting at 50
+    pub member_count: u32,
+
+    /// Users who were added to the thread
+    #[serde(default)]
+    pub added_members: Vec<ThreadMember>,
+
+    /// ID of the users who were removed from the thread
+    #[serde(default)]
+    pub removed_member_ids: Vec<String>,
+}
+
+/// Sent when a user is banned from or unbanned from a guild
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-ban-add>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildBan {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// User who was banned or unbanned
+    pub user: User,
+}
+
+/// Sent when a guild's emojis have been updated
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-emojis-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildEmojisUpdate {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Array of emojis
+    pub emojis: Vec<Emoji>,
+}
+
+/// Sent when a guild's stickers have been updated
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-stickers-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildStickersUpdate {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Array of stickers
+    pub stickers: Vec<Sticker>,
+}
+
+/// Sent when a guild integration is updated
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-integrations-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildIntegrationsUpdate {
+    /// ID of the guild whose integrations were updated
+    pub guild_id: String,
+}
+
+/// Sent when a user is removed from a guild (leave/kick/ban)
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-member-remove>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildMemberRemove {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// User who was removed
+    pub user: User,
+}
+
+/// Sent in response to Guild Request Members
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-members-chunk>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildMembersChunk {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Set of guild members
+    #[serde(default)]
+    pub members: Vec<GuildMember>,
+
+    /// Chunk index in the expected chunks for this response (0 <= chunk_index < chunk_count)
+    pub chunk_index: u32,
+
+    /// Total number of expected chunks for this response
+    pub chunk_count: u32,
+
+    /// When passing an invalid ID, it will be returned here
+    #[serde(default)]
+    pub not_found: Vec<serde_json::Value>,
+
+    /// When passing true to request presences, presences of the returned members will be here
+    #[serde(default)]
+    pub presences: Vec<Presence>,
+
+    /// Nonce used in the Guild Members Request
+    pub nonce: Option<String>,
+}
+
+/// Sent when a guild role is created or updated
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-role-create>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildRole {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Role that was created or updated
+    pub role: Role,
+}
+
+/// Sent when a guild role is deleted
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-role-delete>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildRoleDelete {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// ID of the role
+    pub role_id: String,
+}
+
+/// Sent when a user has subscribed to or unsubscribed from a guild scheduled event
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#guild-scheduled-event-user-add>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildScheduledEventUser {
+    /// ID of the guild scheduled event
+    pub guild_scheduled_event_id: String,
+
+    /// ID of the user
+    pub user_id: String,
+
+    /// ID of the guild
+    pub guild_id: String,
+}
+
+/// Sent when an integration is deleted
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#integration-delete>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntegrationDelete {
+    /// Integration ID
+    pub id: String,
+
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// ID of the bot/OAuth2 application for this discord integration
+    pub application_id: Option<String>,
+}
+
+/// Sent when a new invite to a channel is created
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#invite-create>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InviteCreate {
+    /// Channel the invite is for
+    pub channel_id: String,
+
+    /// Unique invite code
+    pub code: String,
+
+    /// Time at which the invite was created
+    pub created_at: String,
+
+    /// Guild of the invite
+    pub guild_id: Option<String>,
+
+    /// User that created the invite
+    pub inviter: Option<User>,
+
+    /// How long the invite is valid for (in seconds)
+    pub max_age: u32,
+
+    /// Maximum number of times the invite can be used
+    pub max_uses: u32,
+
+    /// Type of target for this voice channel invite
+    pub target_type: Option<u8>,
+
+    /// User whose stream to display for this voice channel stream invite
+    pub target_user: Option<User>,
+
+    /// Whether or not the invite is temporary
+    pub temporary: bool,
+
+    /// How many times the invite has been used (always will be 0)
+    #[serde(default)]
+    pub uses: u32,
+}
+
+/// Sent when an invite is deleted
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#invite-delete>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InviteDelete {
+    /// Channel of the invite
+    pub channel_id: String,
+
+    /// Guild of the invite
+    pub guild_id: Option<String>,
+
+    /// Unique invite code
+    pub code: String,
+}
+
+/// Sent when a message is deleted
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-delete>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageDelete {
+    /// ID of the message
+    pub id: String,
+
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+}
+
+/// Sent when multiple messages are deleted at once
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-delete-bulk>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageDeleteBulk {
+    /// IDs of the messages
+    pub ids: Vec<String>,
+
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+}
+
+/// Sent when a user adds a reaction to a message
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-reaction-add>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageReactionAdd {
+    /// ID of the user
+    pub user_id: String,
+
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the message
+    pub message_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+
+    /// Member who reacted if this happened in a guild
+    pub member: Option<GuildMember>,
+
+    /// Emoji used to react
+    pub emoji: Emoji,
+
+    /// ID of the user who authored the message which was reacted to
+    pub message_author_id: Option<String>,
+
+    /// true if this is a super-reaction
+    #[serde(default)]
+    pub burst: bool,
+
+    /// Colors used for super-reaction animation in "#rrggbb" format
+    #[serde(default)]
+    pub burst_colors: Vec<String>,
+
+    /// The type of reaction
+    #[serde(default)]
+    pub r#type: u8,
+}
+
+/// Sent when a user removes a reaction from a message
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-reaction-remove>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageReactionRemove {
+    /// ID of the user
+    pub user_id: String,
+
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the message
+    pub message_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+
+    /// Emoji used to react
+    pub emoji: Emoji,
+
+    /// true if this was a super-reaction
+    #[serde(default)]
+    pub burst: bool,
+
+    /// The type of reaction
+    #[serde(default)]
+    pub r#type: u8,
+}
+
+/// Sent when a user explicitly removes all reactions from a message
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-reaction-remove-all>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageReactionRemoveAll {
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the message
+    pub message_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+}
+
+/// Sent when a bot removes all instances of a given emoji from the reactions of a message
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-reaction-remove-emoji>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageReactionRemoveEmoji {
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+
+    /// ID of the message
+    pub message_id: String,
+
+    /// Emoji that was removed
+    pub emoji: Emoji,
+}
+
+/// Sent when a user starts typing in a channel
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#typing-start>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TypingStart {
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+
+    /// ID of the user
+    pub user_id: String,
+
+    /// Unix time (in seconds) of when the user started typing
+    pub timestamp: u64,
+
+    /// Member who started typing if this happened in a guild
+    pub member: Option<GuildMember>,
+}
+
+/// Sent when a guild's voice server is updated
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#voice-server-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VoiceServerUpdate {
+    /// Voice connection token
+    pub token: String,
+
+    /// Guild this voice server update is for
+    pub guild_id: String,
+
+    /// Voice server host, `None` when the voice server was deallocated
+    pub endpoint: Option<String>,
+}
+
+/// Sent when a guild channel's webhook is created, updated, or deleted
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#webhooks-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebhooksUpdate {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// ID of the channel
+    pub channel_id: String,
+}
+
+/// Sent when a user votes or removes a vote on a poll
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#message-poll-vote-add>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessagePollVote {
+    /// ID of the user
+    pub user_id: String,
+
+    /// ID of the channel
+    pub channel_id: String,
+
+    /// ID of the message
+    pub message_id: String,
+
+    /// ID of the guild
+    pub guild_id: Option<String>,
+
+    /// ID of the answer
+    pub answer_id: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::gateway::response::DiscordOpCode;
+
+    fn dispatch(name: GatewayReceiveEventName, data: Value) -> ReceiveEvent {
+        ReceiveEvent {
+            t: Some(name),
+            s: Some(1),
+            op: DiscordOpCode::Dispatch,
+            d: Some(data),
+        }
+    }
+
+    /// Parse a dispatch that has to match its model
+    fn parse_dispatch(name: GatewayReceiveEventName, data: Value) -> Event {
+        Event::from_receive_event(&dispatch(name, data))
+            .unwrap()
+            .unwrap()
+    }
+
+    fn user() -> Value {
+        json!({
+            "id": "80351110224678912",
+            "username": "nelly",
+            "discriminator": "0",
+            "global_name": "Nelly",
+            "avatar": "8342729096ea3675442027381ff50dfe",
+            "public_flags": 64,
+            "avatar_decoration_data": null,
+            "clan": null,
+        })
+    }
+
+    #[test]
+    fn parses_ready() {
+        let ready = json!({
+            "v": 10,
+            "user_settings": {},
+            "user": {
+                "id": "100000000000000001",
+                "username": "rustycord",
+                "discriminator": "0",
+                "global_name": null,
+                "avatar": null,
+                "bot": true,
+                "flags": 0,
+                "mfa_enabled": true,
+                "verified": true,
+                "email": null,
+            },
+            "session_type": "normal",
+            "session_id": "2c7f8a5d2e6f0c0b5b5b8d4e2c1a9f3e",
+            "resume_gateway_url": "wss://gateway-us-east1-b.discord.gg",
+            "relationships": [],
+            "private_channels": [],
+            "presences": [],
+            "guilds": [
+                { "id": "41771983423143937", "unavailable": true },
+                { "id": "81384788765712384", "unavailable": true },
+            ],
+            "guild_join_requests": [],
+            "geo_ordered_rtc_regions": ["us-east", "us-central"],
+            "auth": {},
+            "application": { "id": "100000000000000001", "flags": 8953856 },
+            "_trace": ["[\"gateway-prd-us-east1-b-0568\",{\"micros\":126235}]"],
+            "shard": [0, 1],
+        });
+        let event = parse_dispatch(GatewayReceiveEventName::READY, ready);
+        let Event::Ready(ready) = event else {
+            panic!("expected READY, got {:?}", event);
+        };
+        assert_eq!(ready.v, 10);
+        assert_eq!(ready.user.name, "rustycord");
+        assert_eq!(ready.session_id, "2c7f8a5d2e6f0c0b5b5b8d4e2c1a9f3e");
+        assert_eq!(ready.guilds.len(), 2);
+        assert_eq!(ready.shard, Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn parses_message_create() {
+        let message = json!({
+            "type": 0,
+            "tts": false,
+            "timestamp": "2024-05-01T12:00:00.000000+00:00",
+            "pinned": false,
+            "nonce": "1235386012541009920",
+            "mentions": [],
+            "mention_roles": [],
+            "mention_everyone": false,
+            "member": {
+                "roles": ["41771983423143936"],
+                "premium_since": null,
+                "pending": false,
+                "nick": null,
+                "mute": false,
+                "joined_at": "2015-04-26T06:26:56.936000+00:00",
+                "flags": 0,
+                "deaf": false,
+                "communication_disabled_until": null,
+                "avatar": null,
+            },
+            "id": "1235386013761552394",
+            "flags": 0,
+            "embeds": [],
+            "edited_timestamp": null,
+            "content": "!ping",
+            "components": [],
+            "channel_id": "41771983423143937",
+            "author": user(),
+            "attachments": [],
+            "guild_id": "41771983423143937",
+        });
+        let event = parse_dispatch(GatewayReceiveEventName::MESSAGE_CREATE, message);
+        let Event::MessageCreate(message) = event else {
+            panic!("expected MESSAGE_CREATE, got {:?}", event);
+        };
+        assert_eq!(message.id, "1235386013761552394");
+        assert_eq!(message.content, "!ping");
+        assert_eq!(message.channel_id, "41771983423143937");
+        assert_eq!(message.author.name, "nelly");
+    }
+
+    #[test]
+    fn parses_guild_create() {
+        let everyone = json!({
+            "id": "41771983423143937",
+            "name": "@everyone",
+            "color": 0,
+            "colors": { "primary_color": 0, "secondary_color": null, "tertiary_color": null },
+            "hoist": false,
+            "icon": null,
+            "unicode_emoji": null,
+            "position": 0,
+            "permissions": "2248473465835073",
+            "managed": false,
+            "mentionable": false,
+            "flags": 0,
+        });
+        let channel = json!({
+            "id": "41771983423143937",
+            "type": 0,
+            "guild_id": "41771983423143937",
+            "name": "general",
+            "position": 6,
+            "permission_overwrites": [],
+            "rate_limit_per_user": 0,
+            "nsfw": false,
+            "topic": "24/7 chat about how to gank Mike #2",
+            "last_message_id": "155117677105512449",
+            "parent_id": "399942396007890945",
+            "flags": 0,
+        });
+        let guild = json!({
+            "id": "41771983423143937",
+            "name": "Discord Developers",
+            "icon": "86e39f7ae3307e811784e2ffd11a7310",
+            "splash": null,
+            "discovery_splash": null,
+            "owner_id": "80351110224678912",
+            "afk_channel_id": null,
+            "afk_timeout": 300,
+            "verification_level": 1,
+            "default_message_notifications": 1,
+            "explicit_content_filter": 2,
+            "roles": [everyone],
+            "emojis": [],
+            "features": ["COMMUNITY", "NEWS"],
+            "mfa_level": 1,
+            "application_id": null,
+            "system_channel_id": null,
+            "rules_channel_id": "441688182833020939",
+            "max_members": 500000,
+            "vanity_url_code": "discord-developers",
+            "description": null,
+            "banner": null,
+            "premium_tier": 3,
+            "premium_subscription_count": 33,
+            "preferred_locale": "en-US",
+            "nsfw_level": 0,
+            "stickers": [],
+            "premium_progress_bar_enabled": false,
+            "joined_at": "2024-05-01T12:00:00.000000+00:00",
+            "large": true,
+            "unavailable": false,
+            "member_count": 196551,
+            "voice_states": [],
+            "members": [],
+            "channels": [channel],
+            "threads": [],
+            "presences": [],
+            "stage_instances": [],
+            "guild_scheduled_events": [],
+            "soundboard_sounds": [],
+        });
+        let event = parse_dispatch(GatewayReceiveEventName::GUILD_CREATE, guild);
+        let Event::GuildCreate(guild) = event else {
+            panic!("expected GUILD_CREATE, got {:?}", event);
+        };
+        assert_eq!(guild.name, "Discord Developers");
+        assert_eq!(guild.roles[0].name, "@everyone");
+        assert_eq!(guild.channels.len(), 1);
+        assert_eq!(guild.member_count, Some(196551));
+    }
+
+    #[test]
+    fn parses_guild_member_add() {
+        let member = json!({
+            "user": user(),
+            "roles": [],
+            "premium_since": null,
+            "pending": false,
+            "nick": null,
+            "mute": false,
+            "joined_at": "2024-05-01T12:00:00.000000+00:00",
+            "flags": 0,
+            "deaf": false,
+            "communication_disabled_until": null,
+            "avatar": null,
+            "guild_id": "41771983423143937",
+        });
+        let event = parse_dispatch(GatewayReceiveEventName::GUILD_MEMBER_ADD, member);
+        let Event::GuildMemberAdd(member) = event else {
+            panic!("expected GUILD_MEMBER_ADD, got {:?}", event);
+        };
+        assert_eq!(member.user.unwrap().id, "80351110224678912");
+        assert_eq!(member.guild_id.as_deref(), Some("41771983423143937"));
+    }
+
+    #[test]
+    fn malformed_known_event_falls_back_to_unknown() {
+        // a member without its required `user.id`
+        let data = json!({ "user": { "username": "nelly" }, "guild_id": "1" });
+        let event = dispatch(GatewayReceiveEventName::GUILD_MEMBER_ADD, data.clone());
+        assert!(Event::from_receive_event(&event).is_err());
+
+        let Some(Event::Unknown { name, data: raw }) = Event::from_receive_event_or_unknown(&event)
+        else {
+            panic!("malformed event was not kept as unknown");
+        };
+        assert_eq!(name, "GUILD_MEMBER_ADD");
+        assert_eq!(raw, Some(data));
+    }
+
+    #[test]
+    fn payloads_without_event_name_are_not_events() {
+        let hello = ReceiveEvent {
+            t: None,
+            s: None,
+            op: DiscordOpCode::Hello,
+            d: Some(json!({ "heartbeat_interval": 41250 })),
+        };
+        assert!(Event::from_receive_event(&hello).unwrap().is_none());
+        assert!(Event::from_receive_event_or_unknown(&hello).is_none());
+    }
+
+    #[test]
+    fn unknown_events_keep_the_gateway_event_name() {
+        let event = Event::parse(&GatewayReceiveEventName::HELLO, Some(json!({}))).unwrap();
+        assert!(matches!(event, Event::Unknown { ref name, .. } if name == "HELLO"));
+
+        let name = GatewayReceiveEventName::Unknown("GUILD_SOUNDBOARD_SOUND_CREATE".to_string());
+        let event = Event::parse(&name, Some(json!({ "sound_id": "1" }))).unwrap();
+        let Event::Unknown { name, data } = event else {
+            panic!("expected an unknown event, got {:?}", event);
+        };
+        assert_eq!(name, "GUILD_SOUNDBOARD_SOUND_CREATE");
+        assert_eq!(data.unwrap()["sound_id"], "1");
+    }
+}
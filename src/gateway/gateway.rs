@@ -1,19 +1,49 @@
-use std::{process::exit, time::Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 
-const GATEWAY_VERSION: i8 = 10;
-const GATEWAY_BASE_URL: &str = "wss://gateway.discord.gg/";
+/// Default gateway url, used when no url was returned by `GET /gateway/bot`
+pub const DEFAULT_GATEWAY_URL: &str = "wss://gateway.discord.gg";
 /// The WebSocket connection to the Discord Gateway.
 /// This will be used to interact with the Discord Gateway.
 use crate::{
     client::Client,
-    gateway::response::{DiscordOpCode, DiscordReceiveEvent, ReceiveEvent},
+    gateway::compression::ZlibStreamInflater,
+    gateway::encoding::{EncodedPayload, GatewayEncoding},
+    gateway::event::{GuildMembersChunk, VoiceServerUpdate},
+    gateway::identify_scheduler::{IdentifyScheduler, IdentifySlot},
+    gateway::members::{PendingGuildMembers, RequestGuildMembers},
+    gateway::messenger::ShardCommand,
+    gateway::ratelimit::GatewayRateLimiter,
+    gateway::recorder::ShardRecorder,
+    gateway::response::{
+        DiscordOpCode, DiscordReceiveEvent, GatewayCloseCode, GatewayReceiveEventName, Hello,
+        ReceiveEvent,
+    },
+    gateway::voice_state::{PendingVoiceConnection, UpdateVoiceState, VoiceConnectionInfo},
+    handlers::dispatch_queue::DispatchQueue,
+    http::DEFAULT_API_VERSION,
+    models::voice::VoiceState,
+    utils::jitter,
 };
 use futures::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use serde_json::from_str;
-use tokio::net::TcpStream;
+use tokio::{
+    net::TcpStream,
+    sync::mpsc::{OwnedPermit, UnboundedReceiver},
+    time::Instant,
+};
 
-use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebSocketReceiveData {}
@@ -56,7 +86,7 @@ pub enum ActivityType {
 /// <https://discord.com/developers/docs/topics/gateway-events#activity-object-activity-structure>
 ///
 /// Note Bot users are only able to set name, state, type, and url.
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Activity {
     /// The activity's name.
     pub name: String,
@@ -91,11 +121,10 @@ pub struct PresenceUpdate {
 /// The activity object that the user is doing.
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
-
 pub enum WebSocketMessageData {
-    /// the heartbeat interval that the client should heartbeat to.
-    /// <https://discord.com/developers/docs/topics/gateway-events#heartbeat/>
-    Heartbeat(u64),
+    /// the last sequence number received by the client, `null` if none was received yet.
+    /// <https://discord.com/developers/docs/topics/gateway-events#heartbeat>
+    Heartbeat(Option<i32>),
 
     /// Used to trigger the initial handshake with the gateway.
     /// <https://discord.com/developers/docs/topics/gateway-events#identify-identify-structure>
@@ -127,6 +156,27 @@ pub enum WebSocketMessageData {
     /// Sent by the client to indicate a presence or status update.
     /// <https://discord.com/developers/docs/topics/gateway-events#update-presence>
     PresenceUpdate(PresenceUpdate),
+
+    /// Used to replay missed events when a disconnected client resumes.
+    /// <https://discord.com/developers/docs/topics/gateway-events#resume>
+    Resume {
+        /// Session token
+        token: String,
+
+        /// Session ID received in READY
+        session_id: String,
+
+        /// Last sequence number received
+        seq: Option<i32>,
+    },
+
+    /// Used to request all members for a guild or a list of guilds.
+    /// <https://discord.com/developers/docs/events/gateway-events#request-guild-members>
+    RequestGuildMembers(RequestGuildMembers),
+
+    /// Sent when a client wants to join, move, or disconnect from a voice channel.
+    /// <https://discord.com/developers/docs/events/gateway-events#update-voice-state>
+    UpdateVoiceState(UpdateVoiceState),
 }
 //  "properties": {"$os": "linux", "$browser": "rustycord", "$device": "rustycord"};
 
@@ -136,15 +186,126 @@ pub struct WebSocketMessage {
     d: WebSocketMessageData,
 }
 
+impl WebSocketMessage {
+    /// Heartbeat payload with the last sequence number received
+    pub fn heartbeat(sequence: Option<i32>) -> Self {
+        Self {
+            op: DiscordWebSocket::HEARTBEAT,
+            d: WebSocketMessageData::Heartbeat(sequence),
+        }
+    }
+
+    /// Identify payload, sent with the properties of rustycord
+    pub fn identify(
+        token: String,
+        intents: i32,
+        compress: bool,
+        large_threshold: i32,
+        shard: Option<Vec<i32>>,
+        presence: Option<PresenceUpdate>,
+    ) -> Self {
+        Self {
+            op: DiscordWebSocket::IDENTIFY,
+            d: WebSocketMessageData::Identify {
+                token,
+                intents,
+                properties: IdentifyProperties {
+                    os: "linux".to_string(),
+                    browser: "rustycord".to_string(),
+                    device: "rustycord".to_string(),
+                },
+                compress,
+                large_threshold,
+                shard,
+                presence,
+            },
+        }
+    }
+
+    /// Resume payload
+    pub fn resume(token: String, session_id: String, seq: Option<i32>) -> Self {
+        Self {
+            op: DiscordWebSocket::RESUME,
+            d: WebSocketMessageData::Resume {
+                token,
+                session_id,
+                seq,
+            },
+        }
+    }
+
+    /// Presence Update payload
+    pub fn presence_update(presence: PresenceUpdate) -> Self {
+        Self {
+            op: DiscordWebSocket::PRESENCE_UPDATE,
+            d: WebSocketMessageData::PresenceUpdate(presence),
+        }
+    }
+
+    /// Request Guild Members payload
+    pub fn request_guild_members(request: RequestGuildMembers) -> Self {
+        Self {
+            op: DiscordWebSocket::REQUEST_GUILD_MEMBERS,
+            d: WebSocketMessageData::RequestGuildMembers(request),
+        }
+    }
+
+    /// Update Voice State payload
+    pub fn update_voice_state(update: UpdateVoiceState) -> Self {
+        Self {
+            op: DiscordWebSocket::VOICE_STATE_UPDATE,
+            d: WebSocketMessageData::UpdateVoiceState(update),
+        }
+    }
+}
+
 pub struct RustycordWebSocketResponse {}
 
-pub struct DiscordWebSocket(WebSocketStream<MaybeTlsStream<TcpStream>>);
+/// Where and how a shard connects to the gateway
+#[derive(Debug, Clone)]
+pub struct GatewayConfig {
+    /// Gateway url without query parameters, resumes use the url from READY
+    pub url: String,
+    /// Gateway version, sent as `v` query parameter
+    pub version: u8,
+    /// Whether `zlib-stream` transport compression is used
+    pub compress: bool,
+    /// Payload encoding of the connection
+    pub encoding: GatewayEncoding,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_GATEWAY_URL.to_string(),
+            version: DEFAULT_API_VERSION,
+            compress: false,
+            encoding: GatewayEncoding::Json,
+        }
+    }
+}
+
+pub struct DiscordWebSocket {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+
+    /// Inflater for `zlib-stream` transport compression, `None` when disabled
+    inflater: Option<ZlibStreamInflater>,
+
+    /// Payload encoding negotiated in the connection url
+    encoding: GatewayEncoding,
+
+    /// Outbound limiter, Discord allows 120 commands per 60 seconds
+    ratelimiter: GatewayRateLimiter,
+
+    /// Records received payloads when the client has a recorder
+    recorder: Option<ShardRecorder>,
+}
 
 /// The WebSocket connection to the Discord Gateway.
 /// This is the main struct that will be used to interact with the Discord Gateway.
 /// It will be used to send and receive messages from the gateway.
 /// It will also be used to handle the connection to the gateway.
-/// The connection will be maintained by sending heartbeats to the gateway every 5 seconds.
+/// The connection will be maintained by sending heartbeats at the interval requested in HELLO.
 /// The connection will be authenticated by sending an identify message to the gateway.
 /// The connection will be reconnected if the gateway disconnects.
 /// The connection will be resumed if the gateway disconnects.
@@ -153,8 +314,8 @@ pub struct DiscordWebSocket(WebSocketStream<MaybeTlsStream<TcpStream>>);
 ///
 /// * `connect` - Connect to the Discord Gateway using the provided client
 /// * `recv` - Receive message from the gateway in json format
-/// * `send_json` - Send message to the gateway in json format
-/// * `send_heartbeat` - Send heartbeat to the gateway every 5 seconds
+/// * `send_json` - Send message to the gateway in the encoding of the connection
+/// * `send_heartbeat` - Send heartbeat carrying the last sequence number to the gateway
 /// * `send_identify` - Send identify message to the gateway to authenticate the client
 /// * `presence_update` - Send presence update to the gateway
 ///
@@ -163,9 +324,8 @@ impl DiscordWebSocket {
     /// All gateway events in Discord are tagged with an opcode that denotes the payload type.
     /// Your connection to our gateway may also sometimes close. When it does, you will
     /// receive a close code that tells you what happened.
-    /// https://discord.com/developers/docs/topics/opcodes-and-status-codes#gateway-gateway-opcodes
+    /// <https://discord.com/developers/docs/topics/opcodes-and-status-codes#gateway-gateway-opcodes>
     ///
-
     /// An event was dispatched.
     /// Client Action: Receive only
     const DISPATCH: u8 = 0;
@@ -210,78 +370,165 @@ impl DiscordWebSocket {
     /// Client Action: Receive only
     const HEARTBEAT_ACK: u8 = 11;
 
-    /// Connect to the Discord Gateway using the provided client
+    /// Connect to the Discord Gateway using the provided configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `shard_id` - the shard this connection belongs to, used for logging
+    /// * `config` - gateway url, version, compression and encoding to use
+    pub(crate) async fn connect(
+        shard_id: usize,
+        config: &GatewayConfig,
+    ) -> Result<Self, GatewayError> {
+        Self::connect_url(&config.url, shard_id, config).await
+    }
+
+    /// Connect to a specific gateway url, such as the `resume_gateway_url` from READY
     ///
     /// # Arguments
     ///
-    /// * `client` - the client to connect to the gateway
-    pub(crate) async fn connect(shard_id: usize) -> Self {
-        let gateway_url = format!("{}?v={}&encoding=json", GATEWAY_BASE_URL, GATEWAY_VERSION);
-        // let (ws_stream, _) = connect_async(gateway_url).await.unwrap();
+    /// * `base_url` - the gateway url without query parameters
+    /// * `shard_id` - the shard this connection belongs to, used for logging
+    /// * `config` - gateway version, compression and encoding to use
+    pub(crate) async fn connect_url(
+        base_url: &str,
+        shard_id: usize,
+        config: &GatewayConfig,
+    ) -> Result<Self, GatewayError> {
+        let GatewayConfig {
+            version,
+            compress,
+            encoding,
+            ..
+        } = *config;
+        let mut gateway_url = format!(
+            "{}/?v={}&encoding={}",
+            base_url.trim_end_matches('/'),
+            version,
+            encoding.as_str()
+        );
+        if compress {
+            gateway_url.push_str("&compress=zlib-stream");
+        }
         match connect_async(gateway_url).await {
             Ok((ws_stream, _)) => {
                 log::info!("🔌 {} -> Connected to The Discord", shard_id);
-                Self(ws_stream)
+                Ok(Self {
+                    stream: ws_stream,
+                    inflater: compress.then(ZlibStreamInflater::new),
+                    encoding,
+                    ratelimiter: GatewayRateLimiter::new(),
+                    recorder: None,
+                })
             }
             Err(err) => {
                 log::error!("Shard {} -> Got Error Message {}", shard_id, err);
-                exit(2)
+                Err(GatewayError::ConnectFailed(err.to_string()))
             }
         }
-        // Self(ws_stream)
+    }
+
+    /// Close the connection with the given close code
+    ///
+    /// Closing with 1000 or 1001 invalidates the session, any other code
+    /// keeps it alive so it can be resumed.
+    pub async fn close(&mut self, code: u16) {
+        let frame = CloseFrame {
+            code: CloseCode::from(code),
+            reason: "".into(),
+        };
+        if let Err(err) = self.stream.close(Some(frame)).await {
+            log::debug!("Error closing gateway connection: {}", err);
+        }
+    }
+
+    /// Record received payloads with `recorder`, `None` stops recording
+    pub(crate) fn set_recorder(&mut self, recorder: Option<ShardRecorder>) {
+        self.recorder = recorder;
     }
 
     /// receive message from the gateway in json format
-    pub async fn recv(&mut self) -> Result<(Option<ReceiveEvent>, bool), String> {
+    ///
+    /// Returns `Ok((None, _))` for frames that carry no gateway payload such as pings.
+    pub async fn recv(&mut self) -> Result<(Option<ReceiveEvent>, bool), GatewayError> {
         log::trace!("🔄 Waiting for message from Discord gateway...");
-        let message = self.0.next().await.unwrap().unwrap();
-        let message = match message {
+        let message = match self.stream.next().await {
+            Some(Ok(message)) => message,
+            Some(Err(err)) => return Err(GatewayError::ConnectionLost(err.to_string())),
+            None => {
+                return Err(GatewayError::ConnectionLost(
+                    "gateway stream ended".to_string(),
+                ))
+            }
+        };
+        // text frames are always json, binary frames use the negotiated encoding
+        let mut encoding = GatewayEncoding::Json;
+        let payload: Vec<u8> = match message {
             Message::Text(text) => {
                 log::trace!(
                     "📥 Received text message from gateway (length: {} chars)",
                     text.len()
                 );
-                text.to_string()
+                text.as_bytes().to_vec()
             }
             Message::Binary(data) => {
-                log::debug!(
+                log::trace!(
                     "📥 Received binary message from gateway (length: {} bytes)",
                     data.len()
                 );
-                String::from_utf8_lossy(&data).to_string()
+                encoding = self.encoding;
+                match self.inflater.as_mut() {
+                    Some(inflater) => match inflater.push(&data) {
+                        Ok(Some(payload)) => payload,
+                        Ok(None) => return Ok((None, true)),
+                        // the zlib context is corrupted, the connection cannot be used anymore
+                        Err(err) => return Err(GatewayError::ConnectionLost(err)),
+                    },
+                    None => data.to_vec(),
+                }
             }
             Message::Close(close_frame) => {
                 log::warn!("🔒 Received close frame from gateway: {:?}", close_frame);
-                return Err("Connection closed by Discord".to_string());
+                return Err(match close_frame {
+                    Some(frame) => GatewayError::Closed {
+                        code: GatewayCloseCode::from(u16::from(frame.code)),
+                        reason: frame.reason.to_string(),
+                    },
+                    None => GatewayError::ConnectionLost(
+                        "connection closed without close code".to_string(),
+                    ),
+                });
             }
             _ => {
-                log::debug!("📥 Received non-text message from gateway");
-                "".to_string()
+                log::trace!("📥 Received non-text message from gateway");
+                return Ok((None, true));
             }
         };
-        if message.is_empty() {
-            return Err("🔴 -> Error receiving message".to_string());
-        }
 
         log::trace!("🔍 Parsing gateway message...");
-        let _opcode = from_str::<ReceiveMessageOpcode>(message.as_str()).unwrap();
-
-        let r_event = from_str::<DiscordReceiveEvent>(message.as_str()).unwrap();
+        let r_event = match encoding.decode::<DiscordReceiveEvent>(&payload) {
+            Ok(r_event) => r_event,
+            Err(err) => {
+                // a single bad payload must not take the whole shard down
+                log::error!("❌ Failed to parse gateway message: {}", err);
+                log::trace!(
+                    "🔍 Unparsable gateway message: {}",
+                    String::from_utf8_lossy(&payload)
+                );
+                return Ok((None, true));
+            }
+        };
 
-        log::debug!("📨 Gateway event: {:?} (op: {})", r_event.t, _opcode.op);
+        log::debug!("📨 Gateway event: {:?} (op: {})", r_event.t, r_event.op);
+        if let Some(recorder) = &self.recorder {
+            recorder.record(&payload, encoding);
+        }
 
         // change discord event to rustycord event
-        let r_event = ReceiveEvent {
-            t: r_event.t,
-            s: r_event.s,
-            op: DiscordOpCode::from(_opcode.op),
-            d: r_event.d,
-        };
-
-        Ok((Some(r_event), true))
+        Ok((Some(ReceiveEvent::from(r_event)), true))
     }
 
-    /// send message to the gateway in json format
+    /// send message to the gateway in the encoding of the connection
     ///
     /// # Arguments
     ///
@@ -293,12 +540,24 @@ impl DiscordWebSocket {
     /// use rustycord::gateway::gateway::WebSocketMessageData;
     ///
     /// // Example of creating message data for heartbeat
-    /// let heartbeat_data = WebSocketMessageData::Heartbeat(251);
+    /// let heartbeat_data = WebSocketMessageData::Heartbeat(Some(251));
     /// println!("Created heartbeat message data: {:?}", heartbeat_data);
     /// ```
+    ///
+    /// The message is sent right away, commands that may be delayed should
+    /// use [`send_command`](Self::send_command) so heartbeats are never starved,
+    /// heartbeats and session starts use [`send_priority`](Self::send_priority).
     pub async fn send_json(&mut self, message: WebSocketMessage) -> bool {
-        let message = Message::Text(serde_json::to_string(&message).unwrap().into());
-        match self.0.send(message).await {
+        let message = match self.encoding.encode(&message) {
+            Ok(EncodedPayload::Text(text)) => Message::Text(text.into()),
+            Ok(EncodedPayload::Binary(data)) => Message::Binary(data.into()),
+            Err(err) => {
+                log::error!("Error encoding message {}", err);
+                return false;
+            }
+        };
+        self.ratelimiter.record();
+        match self.stream.send(message).await {
             Ok(_) => true,
 
             Err(err) => {
@@ -308,14 +567,53 @@ impl DiscordWebSocket {
         }
     }
 
-    /// send heartbeat to the gateway every 5 seconds
-    pub async fn send_heartbeat(&mut self) -> bool {
-        log::debug!("💓 Sending heartbeat to Discord gateway");
-        let heartbeat = WebSocketMessage {
-            op: Self::HEARTBEAT,
-            d: WebSocketMessageData::Heartbeat(251),
-        };
-        let result = self.send_json(heartbeat).await;
+    /// When the next rate limited command can be sent
+    pub fn next_command_slot(&mut self) -> Instant {
+        self.ratelimiter.next_slot(false)
+    }
+
+    /// send a command such as a presence update, waiting for the outbound
+    /// rate limit when needed
+    pub async fn send_command(&mut self, message: WebSocketMessage) -> bool {
+        let slot = self.next_command_slot();
+        if slot > Instant::now() {
+            log::warn!(
+                "⏳ Gateway rate limit reached, delaying command by {:?}",
+                slot - Instant::now()
+            );
+            tokio::time::sleep_until(slot).await;
+        }
+        self.send_json(message).await
+    }
+
+    /// send a heartbeat, IDENTIFY or RESUME, which may use the part of the
+    /// outbound rate limit kept free for them
+    pub async fn send_priority(&mut self, message: WebSocketMessage) -> bool {
+        let slot = self.ratelimiter.next_slot(true);
+        if slot > Instant::now() {
+            log::warn!(
+                "⏳ Gateway rate limit reached, delaying op {} by {:?}",
+                message.op,
+                slot - Instant::now()
+            );
+            tokio::time::sleep_until(slot).await;
+        }
+        self.send_json(message).await
+    }
+
+    /// send heartbeat to the gateway
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - the last sequence number received, `None` if no dispatch was received yet
+    pub async fn send_heartbeat(&mut self, sequence: Option<i32>) -> bool {
+        log::debug!(
+            "💓 Sending heartbeat to Discord gateway (seq: {:?})",
+            sequence
+        );
+        let result = self
+            .send_priority(WebSocketMessage::heartbeat(sequence))
+            .await;
         if result {
             log::trace!("💓 Heartbeat sent successfully");
         } else {
@@ -331,8 +629,8 @@ impl DiscordWebSocket {
     /// * `token` - the token of the bot
     /// * `compress` - whether the connection supports compression of packets
     /// * `large_threshold` - Value between 50 and 250, total number of members
-    ///              where the gateway will stop sending offline members in
-    ///             the guild member list (default 50)
+    ///   where the gateway will stop sending offline members in
+    ///   the guild member list (default 50)
     /// * `shard` - used for Guild Sharding. A list of two integers, [shard_id, num_shards].
     /// * `presence` - presence structure for initial presence information
     pub async fn send_identify(
@@ -368,142 +666,844 @@ impl DiscordWebSocket {
         });
         let shr: Vec<i32> = shard.unwrap_or_else(|| vec![0, 1]);
         log::debug!("🗂️ Using shard configuration: {:?}", shr);
-        let identify = WebSocketMessage {
-            op: DiscordWebSocket::IDENTIFY,
-            d: WebSocketMessageData::Identify {
-                token: token.to_string(),
-                intents: int,
-                properties: IdentifyProperties {
-                    os: "linux".to_string(),
-                    browser: "rustycord".to_string(),
-                    device: "rustycord".to_string(),
-                },
-                compress: compress,
-                large_threshold: large_threshold.unwrap_or(50),
-                shard: Some(shr),
-                presence: Some(pre),
-            },
-        };
+        let identify = WebSocketMessage::identify(
+            token,
+            int,
+            compress,
+            large_threshold.unwrap_or(50),
+            Some(shr),
+            Some(pre),
+        );
         log::info!("🔑 -> Sending Identification Message");
-        self.send_json(identify).await;
+        self.send_priority(identify).await;
     }
 
     /// send presence update to the gateway
     ///
     /// # Arguments
     ///
-    /// * `presence` - presence structure for initial presence information
-    async fn presence_update(&mut self, presence: PresenceUpdate) {
-        let activities: Vec<Activity> = presence.activities.clone();
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis();
-        let presence = WebSocketMessage {
-            op: Self::PRESENCE_UPDATE,
-            d: WebSocketMessageData::PresenceUpdate(PresenceUpdate {
-                since: now,
-                activities: activities,
-                status: "online".to_string(),
-                afk: false,
-            }),
-        };
+    /// * `presence` - new presence of the client, sent as given
+    pub async fn presence_update(&mut self, presence: PresenceUpdate) -> bool {
+        log::debug!(
+            "🎭 -> Sending presence update (status: {})",
+            presence.status
+        );
+        self.send_command(WebSocketMessage::presence_update(presence))
+            .await
+    }
 
-        self.send_json(presence).await;
+    /// send resume message to the gateway to replay missed events
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - the token of the bot
+    /// * `session_id` - the session id received in READY
+    /// * `seq` - the last sequence number received
+    pub async fn resume(&mut self, token: String, session_id: String, seq: Option<i32>) -> bool {
+        log::info!("🔁 -> Sending Resume Message (seq: {:?})", seq);
+        self.send_priority(WebSocketMessage::resume(token, session_id, seq))
+            .await
     }
+}
 
-    async fn resume(&mut self) {
-        let resume = WebSocketMessage {
-            op: Self::RESUME,
-            d: WebSocketMessageData::Heartbeat(251),
-        };
-        self.send_json(resume).await;
+/// The part of READY a shard needs to resume its session.
+///
+/// Parsed separately from [`Ready`](super::response::Ready) so a change in the
+/// user or guild payloads can never prevent a shard from resuming.
+#[derive(Deserialize)]
+struct ReadySession {
+    session_id: String,
+    resume_gateway_url: String,
+    user: ReadyUser,
+}
+
+#[derive(Deserialize)]
+struct ReadyUser {
+    id: String,
+}
+
+/// Wait for the next shard command, forever if the shard has no messenger
+async fn next_command(
+    commands: &mut Option<UnboundedReceiver<ShardCommand>>,
+) -> Option<ShardCommand> {
+    match commands {
+        Some(commands) => commands.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for room in the dispatch queue, forever if the shard has no queue
+async fn reserve_dispatch(queue: &Option<DispatchQueue>) -> Option<OwnedPermit<ReceiveEvent>> {
+    match queue {
+        Some(queue) => queue.reserve().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// What a shard has to do after its gateway connection ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectKind {
+    /// Reconnect to the resume url and send RESUME so missed events are replayed
+    Resume,
+
+    /// Drop the session and start a new one with IDENTIFY
+    Identify,
+}
+
+/// Error that ended a gateway connection
+#[derive(Debug, Clone, PartialEq)]
+pub enum GatewayError {
+    /// The gateway closed the connection with a close code
+    Closed {
+        code: GatewayCloseCode,
+        reason: String,
+    },
+
+    /// The connection dropped without a close frame
+    ConnectionLost(String),
+
+    /// The connection to the gateway could not be opened
+    ConnectFailed(String),
+
+    /// The gateway did not answer a request in time
+    Timeout(String),
+}
+
+impl GatewayError {
+    /// Whether reconnecting can never succeed, e.g. the token or intents are invalid
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Self::Closed { code, .. } => code.is_fatal(),
+            Self::ConnectionLost(_) | Self::ConnectFailed(_) | Self::Timeout(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed { code, reason } => write!(
+                f,
+                "gateway closed the connection with {:?} ({}): {}",
+                code,
+                code.code(),
+                reason
+            ),
+            Self::ConnectionLost(reason) => write!(f, "gateway connection lost: {}", reason),
+            Self::ConnectFailed(reason) => {
+                write!(f, "failed to connect to the gateway: {}", reason)
+            }
+            Self::Timeout(request) => write!(f, "gateway did not answer {} in time", request),
+        }
     }
 }
 
+impl std::error::Error for GatewayError {}
+
 pub struct Manager {
     pub ws: DiscordWebSocket,
     last_heartbeat: Option<Instant>,
     last_heartbeat_ack: Option<Instant>,
-    heartbeat_interval: Option<i8>,
+    /// Interval requested by the gateway in the HELLO payload
+    heartbeat_interval: Option<Duration>,
+    /// When the heartbeat timer fires next, `None` until HELLO is received
+    next_heartbeat: Option<Instant>,
+    /// Whether the last heartbeat we sent has been acknowledged
+    heartbeat_acked: bool,
+    /// Last sequence number received in a dispatch
+    sequence: Option<i32>,
+    /// Session id received in READY, used to resume
+    session_id: Option<String>,
+    /// Gateway url received in READY, used to resume
+    resume_gateway_url: Option<String>,
+    /// Consecutive reconnect attempts since the last healthy session
+    reconnect_attempts: u32,
 
     pub token: String,
     pub intents: i32,
     shard_id: usize,
     pub total_shards: usize,
+    pub large_threshold: Option<i32>,
+    pub presence: Option<PresenceUpdate>,
+    /// Gateway url, version, compression and encoding of the connection
+    pub config: GatewayConfig,
     pub client: Option<Client>,
+    /// Shared scheduler ordering IDENTIFY calls across shards
+    identify_scheduler: Option<Arc<IdentifyScheduler>>,
+    /// Slot taken before opening the current connection, used by its IDENTIFY
+    identify_slot: Option<IdentifySlot>,
+    /// Commands sent through the [`ShardMessenger`](super::messenger::ShardMessenger) of this shard
+    commands: Option<UnboundedReceiver<ShardCommand>>,
+    /// Commands waiting for the outbound rate limit or for the session to be ready
+    outbound: VecDeque<WebSocketMessage>,
+    /// Whether READY or RESUMED was received on the current connection
+    session_ready: bool,
+    /// Request Guild Members waiting for their chunks, by nonce
+    pending_members: HashMap<String, PendingGuildMembers>,
+    /// Voice channel joins waiting for their state and server updates, by guild id
+    pending_voice: HashMap<String, PendingVoiceConnection>,
+    /// Voice connections of the bot, by guild id
+    voice_connections: HashMap<String, VoiceConnectionInfo>,
+    /// ID of the bot user, received in READY
+    user_id: Option<String>,
+    /// Queue handing events to handler tasks, events are handled inline without it
+    dispatch_queue: Option<DispatchQueue>,
+    /// Event waiting for room in the full dispatch queue, no further events
+    /// are read from the gateway until it was queued
+    pending_dispatch: Option<ReceiveEvent>,
 }
 
 impl Manager {
-    pub async fn new(token: String, intents: i32, shard_id: usize, total_shards: usize) -> Self {
-        let ws = DiscordWebSocket::connect(shard_id).await;
-        let last_heartbeat = None;
-        let last_heartbeat_ack = None;
-        let heartbeat_interval = 0;
+    /// Maximum delay between two reconnect attempts
+    const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+    pub async fn new(
+        token: String,
+        intents: i32,
+        shard_id: usize,
+        total_shards: usize,
+        config: GatewayConfig,
+    ) -> Result<Self, GatewayError> {
+        let ws = DiscordWebSocket::connect(shard_id, &config).await?;
+        Ok(Self::with_connection(
+            ws,
+            token,
+            intents,
+            shard_id,
+            total_shards,
+            config,
+        ))
+    }
+
+    /// Open the first connection of a shard, retrying failed attempts with
+    /// the reconnect backoff until one succeeds.
+    ///
+    /// A slot of `identify_scheduler` is taken before every attempt, so the
+    /// shard waits for its turn to identify before it has a connection to
+    /// keep alive, and identifies as soon as the gateway says HELLO.
+    pub async fn connect(
+        token: String,
+        intents: i32,
+        shard_id: usize,
+        total_shards: usize,
+        config: GatewayConfig,
+        identify_scheduler: Option<Arc<IdentifyScheduler>>,
+    ) -> Self {
+        let mut attempts = 0;
+        let (ws, identify_slot) = Self::open_connection(
+            &config.url,
+            shard_id,
+            &config,
+            identify_scheduler.as_ref(),
+            &mut attempts,
+        )
+        .await;
+        let mut manager = Self::with_connection(ws, token, intents, shard_id, total_shards, config);
+        manager.identify_scheduler = identify_scheduler;
+        manager.identify_slot = identify_slot;
+        manager.reconnect_attempts = attempts;
+        manager
+    }
+
+    fn with_connection(
+        ws: DiscordWebSocket,
+        token: String,
+        intents: i32,
+        shard_id: usize,
+        total_shards: usize,
+        config: GatewayConfig,
+    ) -> Self {
         Self {
             ws,
-            last_heartbeat,
-            last_heartbeat_ack,
-            heartbeat_interval: Some(heartbeat_interval),
+            last_heartbeat: None,
+            last_heartbeat_ack: None,
+            heartbeat_interval: None,
+            next_heartbeat: None,
+            heartbeat_acked: true,
+            sequence: None,
+            session_id: None,
+            resume_gateway_url: None,
+            reconnect_attempts: 0,
             token,
             intents,
             shard_id,
             total_shards,
+            large_threshold: None,
+            presence: None,
+            config,
             client: None,
+            identify_scheduler: None,
+            identify_slot: None,
+            commands: None,
+            outbound: VecDeque::new(),
+            session_ready: false,
+            pending_members: HashMap::new(),
+            pending_voice: HashMap::new(),
+            voice_connections: HashMap::new(),
+            user_id: None,
+            dispatch_queue: None,
+            pending_dispatch: None,
         }
     }
 
     pub fn set_client(&mut self, client: Client) {
         self.client = Some(client);
+        self.ws.set_recorder(self.shard_recorder());
+    }
+
+    /// Recorder of this shard's payloads, when the client has one
+    fn shard_recorder(&self) -> Option<ShardRecorder> {
+        let recorder = self.client.as_ref()?.recorder.as_ref()?;
+        Some(recorder.for_shard(self.shard_id, &self.token))
+    }
+
+    /// Receive commands from the [`ShardMessenger`](super::messenger::ShardMessenger) of this shard
+    pub fn set_commands(&mut self, commands: UnboundedReceiver<ShardCommand>) {
+        self.commands = Some(commands);
+    }
+
+    /// Handle events on the tasks of a dispatch queue instead of inline
+    pub fn set_dispatch_queue(&mut self, queue: DispatchQueue) {
+        self.dispatch_queue = Some(queue);
+    }
+
+    /// Set the presence sent with IDENTIFY
+    pub fn set_presence(&mut self, presence: Option<PresenceUpdate>) {
+        self.presence = presence;
+    }
+
+    /// Last sequence number received from the gateway
+    pub fn sequence(&self) -> Option<i32> {
+        self.sequence
+    }
+
+    /// Session id of the current gateway session, if READY was received
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
     }
 
-    /// return false if heartbeat is not required to send to the gateway
+    /// Round trip time between the last heartbeat and its acknowledgement
+    pub fn latency(&self) -> Option<Duration> {
+        match (self.last_heartbeat, self.last_heartbeat_ack) {
+            (Some(sent), Some(acked)) if acked >= sent => Some(acked - sent),
+            _ => None,
+        }
+    }
+
+    /// Drive the shard, reconnecting whenever the connection is lost.
     ///
-    /// True - where heartbeat is required to send to the gateway
-    /// True - where heartbeat interval is not set or last heartbeat ack is more than 15 seconds ago
-    /// True - where last heartbeat is less than 5 seconds ago
+    /// Sessions are resumed when possible so missed events are replayed,
+    /// otherwise a new session is identified. Returns once the shard was
+    /// shut down, or with an error when the gateway closed the connection
+    /// with a fatal close code.
+    pub async fn run(&mut self) -> Result<(), GatewayError> {
+        loop {
+            let Some(kind) = self.run_connection().await? else {
+                log::info!("👋 Shard {} shutting down", self.shard_id);
+                self.ws.close(1000).await;
+                return Ok(());
+            };
+            if kind == ReconnectKind::Identify {
+                self.reset_session();
+            }
+            self.reconnect(kind).await;
+        }
+    }
+
+    /// Drive the current connection until it has to be replaced.
     ///
-    /// False - where last heartbeat is more than 5 seconds ago
-    /// False - where heartbeat interval is set and last heartbeat ack is less than 15 seconds ago
+    /// Heartbeats run on their own timer, so they are sent at the interval
+    /// requested by the gateway no matter how often events arrive.
+    /// Returns `None` when the shard was asked to shut down.
+    async fn run_connection(&mut self) -> Result<Option<ReconnectKind>, GatewayError> {
+        loop {
+            let next_heartbeat = self.next_heartbeat;
+            let next_send = (self.session_ready && !self.outbound.is_empty())
+                .then(|| self.ws.next_command_slot());
+            tokio::select! {
+                _ = tokio::time::sleep_until(next_heartbeat.unwrap_or_else(Instant::now)), if next_heartbeat.is_some() => {
+                    if !self.handle_heartbeat().await {
+                        return Ok(Some(ReconnectKind::Resume));
+                    }
+                }
+                received = self.ws.recv(), if self.pending_dispatch.is_none() => {
+                    if let Some(kind) = self.handle_event(received).await? {
+                        return Ok(Some(kind));
+                    }
+                }
+                _ = tokio::time::sleep_until(next_send.unwrap_or_else(Instant::now)), if next_send.is_some() => {
+                    if let Some(message) = self.outbound.pop_front() {
+                        self.ws.send_json(message).await;
+                    }
+                }
+                permit = reserve_dispatch(&self.dispatch_queue), if self.pending_dispatch.is_some() => {
+                    match (permit, self.pending_dispatch.take()) {
+                        (Some(permit), Some(event)) => {
+                            permit.send(event);
+                        }
+                        (None, Some(event)) => {
+                            log::error!("❌ Dispatch task stopped, dropping {:?}", event.t);
+                        }
+                        _ => {}
+                    }
+                }
+                command = next_command(&mut self.commands) => {
+                    match command {
+                        Some(ShardCommand::Shutdown) => return Ok(None),
+                        Some(command) => {
+                            if let Some(kind) = self.handle_command(command).await {
+                                return Ok(Some(kind));
+                            }
+                        }
+                        // every messenger was dropped, nobody can send commands anymore
+                        None => self.commands = None,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply a command sent through the shard's messenger
+    async fn handle_command(&mut self, command: ShardCommand) -> Option<ReconnectKind> {
+        match command {
+            ShardCommand::UpdatePresence(presence) => {
+                self.presence = Some(presence.clone());
+                self.queue_command(WebSocketMessage::presence_update(presence));
+                None
+            }
+            ShardCommand::UpdateStatus(status) => {
+                let mut presence = self.presence.clone().unwrap_or(PresenceUpdate {
+                    since: 0,
+                    activities: Vec::new(),
+                    status: String::new(),
+                    afk: false,
+                });
+                presence.status = status;
+                self.presence = Some(presence.clone());
+                self.queue_command(WebSocketMessage::presence_update(presence));
+                None
+            }
+            ShardCommand::RequestGuildMembers(mut request, sender) => {
+                let checked = request.ensure_nonce(self.shard_id).and_then(|nonce| {
+                    PendingGuildMembers::check_unused(&self.pending_members, &nonce).map(|_| nonce)
+                });
+                let nonce = match checked {
+                    Ok(nonce) => nonce,
+                    Err(err) => {
+                        // the requester may have stopped waiting
+                        let _ = sender.send(Err(err));
+                        return None;
+                    }
+                };
+                log::debug!(
+                    "👥 Shard {} requesting members of guild {} (nonce: {})",
+                    self.shard_id,
+                    request.guild_id,
+                    nonce
+                );
+                self.pending_members.insert(
+                    nonce.clone(),
+                    PendingGuildMembers::new(request.guild_id.clone(), nonce, sender),
+                );
+                self.queue_command(WebSocketMessage::request_guild_members(request));
+                None
+            }
+            ShardCommand::CancelGuildMembers(nonce) => {
+                if self.pending_members.remove(&nonce).is_some() {
+                    log::warn!(
+                        "Shard {} gave up on member request {}",
+                        self.shard_id,
+                        nonce
+                    );
+                }
+                None
+            }
+            ShardCommand::UpdateVoiceState(update, sender) => {
+                log::debug!(
+                    "🔊 Shard {} updating voice state in guild {} (channel: {:?})",
+                    self.shard_id,
+                    update.guild_id,
+                    update.channel_id
+                );
+                match sender {
+                    Some(sender) if update.channel_id.is_some() => {
+                        let current = self.voice_connections.get(&update.guild_id).cloned();
+                        self.pending_voice.insert(
+                            update.guild_id.clone(),
+                            PendingVoiceConnection::new(sender, current),
+                        );
+                    }
+                    _ => {
+                        self.pending_voice.remove(&update.guild_id);
+                    }
+                }
+                self.queue_command(WebSocketMessage::update_voice_state(update));
+                None
+            }
+            ShardCommand::CancelVoiceConnection(guild_id) => {
+                // a later join of the same guild may have replaced the abandoned one
+                if self
+                    .pending_voice
+                    .get(&guild_id)
+                    .is_some_and(PendingVoiceConnection::is_abandoned)
+                {
+                    self.pending_voice.remove(&guild_id);
+                    log::warn!(
+                        "Shard {} gave up on joining voice in guild {}",
+                        self.shard_id,
+                        guild_id
+                    );
+                }
+                None
+            }
+            ShardCommand::Restart => {
+                log::info!("🔁 Restarting shard {}", self.shard_id);
+                self.reconnect_attempts = 0;
+                Some(ReconnectKind::Identify)
+            }
+            ShardCommand::Shutdown => None,
+        }
+    }
+
+    /// Queue a rate limited command, it is sent once the session is ready
+    /// and the outbound limit allows it
+    fn queue_command(&mut self, message: WebSocketMessage) {
+        self.outbound.push_back(message);
+        if self.outbound.len() > 1 {
+            log::debug!(
+                "📬 Shard {} has {} queued gateway commands",
+                self.shard_id,
+                self.outbound.len()
+            );
+        }
+    }
+
+    /// Forget the current session so the next connection identifies again
+    fn reset_session(&mut self) {
+        if !self.pending_members.is_empty() {
+            log::warn!(
+                "Shard {} dropped {} member request(s) with its session",
+                self.shard_id,
+                self.pending_members.len()
+            );
+            self.pending_members.clear();
+        }
+        self.pending_voice.clear();
+        self.voice_connections.clear();
+        self.session_id = None;
+        self.resume_gateway_url = None;
+        self.sequence = None;
+    }
+
+    /// Whether the stored session can be resumed
+    fn can_resume(&self) -> bool {
+        self.session_id.is_some()
+    }
+
+    /// Delay before the given reconnect attempt: immediate at first,
+    /// then exponential with jitter up to [`Self::MAX_RECONNECT_DELAY`].
+    fn reconnect_delay(attempt: u32) -> Duration {
+        if attempt == 0 {
+            return Duration::ZERO;
+        }
+        let base = Duration::from_secs(1 << (attempt - 1).min(6)).min(Self::MAX_RECONNECT_DELAY);
+        base.mul_f64(1.0 + jitter() / 2.0)
+            .min(Self::MAX_RECONNECT_DELAY)
+    }
+
+    /// Close the current connection and open a new one, retrying with backoff.
+    async fn reconnect(&mut self, kind: ReconnectKind) {
+        // any code other than 1000/1001 keeps the session resumable
+        let close_code = match kind {
+            ReconnectKind::Resume => 4000,
+            ReconnectKind::Identify => 1000,
+        };
+        self.ws.close(close_code).await;
+        self.session_ready = false;
+        self.next_heartbeat = None;
+        self.heartbeat_interval = None;
+        self.heartbeat_acked = true;
+        self.identify_slot = None;
+
+        log::info!("🔄 Shard {} reconnecting ({:?})", self.shard_id, kind);
+        let url = match (&self.resume_gateway_url, self.can_resume()) {
+            (Some(url), true) => url.clone(),
+            _ => self.config.url.clone(),
+        };
+        // resuming does not start a new session, so it needs no slot
+        let scheduler = match self.can_resume() {
+            true => None,
+            false => self.identify_scheduler.as_ref(),
+        };
+        let (ws, identify_slot) = Self::open_connection(
+            &url,
+            self.shard_id,
+            &self.config,
+            scheduler,
+            &mut self.reconnect_attempts,
+        )
+        .await;
+        self.ws = ws;
+        self.ws.set_recorder(self.shard_recorder());
+        self.identify_slot = identify_slot;
+    }
+
+    /// Connect to `url`, retrying with backoff until it succeeds.
     ///
-    pub async fn is_required_heartbeat(&mut self) -> bool {
-        if self.heartbeat_interval.is_none() {
-            log::debug!("Need to send heartbeat");
-            return true;
+    /// `attempts` counts the attempts since the last healthy session and
+    /// picks the delay of the next one. When a scheduler is given, an
+    /// identify slot is taken right before each attempt, a slot of a failed
+    /// attempt gives back its session start when dropped.
+    async fn open_connection(
+        url: &str,
+        shard_id: usize,
+        config: &GatewayConfig,
+        scheduler: Option<&Arc<IdentifyScheduler>>,
+        attempts: &mut u32,
+    ) -> (DiscordWebSocket, Option<IdentifySlot>) {
+        loop {
+            let delay = Self::reconnect_delay(*attempts);
+            *attempts += 1;
+            if !delay.is_zero() {
+                log::info!(
+                    "⏳ Shard {} connecting in {:?} (attempt {})",
+                    shard_id,
+                    delay,
+                    attempts
+                );
+            }
+            tokio::time::sleep(delay).await;
+
+            let slot = match scheduler {
+                Some(scheduler) => Some(scheduler.acquire(shard_id).await),
+                None => None,
+            };
+            match DiscordWebSocket::connect_url(url, shard_id, config).await {
+                Ok(ws) => return (ws, slot),
+                Err(err) => {
+                    log::error!("Shard {} failed to connect: {}", shard_id, err);
+                }
+            }
         }
-        if self.last_heartbeat_ack.is_none() {
-            log::debug!("Need to send heartbeat");
-            return true;
+    }
+
+    /// Store the interval from HELLO, schedule the first heartbeat and
+    /// start the session by resuming or identifying.
+    ///
+    /// The first heartbeat is sent after `heartbeat_interval * jitter` as
+    /// required by Discord, so shards do not all heartbeat at once.
+    async fn handle_hello(&mut self, event: &ReceiveEvent) {
+        let hello = match event.d.clone().map(serde_json::from_value::<Hello>) {
+            Some(Ok(hello)) => hello,
+            Some(Err(err)) => {
+                log::error!("Shard {} failed to parse HELLO: {}", self.shard_id, err);
+                return;
+            }
+            None => {
+                log::error!("Shard {} received HELLO without payload", self.shard_id);
+                return;
+            }
+        };
+        let interval = Duration::from_millis(hello.heartbeat_interval);
+        log::debug!(
+            "👋 Shard {} received HELLO, heartbeat interval: {:?}",
+            self.shard_id,
+            interval
+        );
+        self.heartbeat_interval = Some(interval);
+        self.heartbeat_acked = true;
+        self.next_heartbeat = Some(Instant::now() + interval.mul_f64(jitter()));
+
+        match &self.session_id {
+            Some(session_id) => {
+                let session_id = session_id.clone();
+                self.ws
+                    .resume(self.token.clone(), session_id, self.sequence)
+                    .await;
+            }
+            None => {
+                self.identify().await;
+                // the bucket window starts with the IDENTIFY that was sent
+                if let Some(slot) = self.identify_slot.take() {
+                    slot.identified();
+                }
+            }
         }
-        if self.last_heartbeat.is_none() {
-            log::debug!("Need to send heartbeat");
-            return true;
+    }
+
+    /// Store the session information needed to resume later
+    fn handle_ready(&mut self, event: &ReceiveEvent) {
+        match event.d.clone().map(serde_json::from_value::<ReadySession>) {
+            Some(Ok(ready)) => {
+                log::info!(
+                    "🟢 Shard {} is ready (session: {})",
+                    self.shard_id,
+                    ready.session_id
+                );
+                self.session_id = Some(ready.session_id);
+                self.resume_gateway_url = Some(ready.resume_gateway_url);
+                self.user_id = Some(ready.user.id);
+                self.reconnect_attempts = 0;
+            }
+            Some(Err(err)) => {
+                log::error!("Shard {} failed to parse READY: {}", self.shard_id, err);
+            }
+            None => log::error!("Shard {} received READY without payload", self.shard_id),
         }
-        if self.last_heartbeat.unwrap().elapsed().as_secs() < 5 {
-            log::debug!("NO Need to send heartbeat");
-            return false;
+    }
+
+    /// Collect a GUILD_MEMBERS_CHUNK for the request with the same nonce
+    fn handle_members_chunk(&mut self, event: &ReceiveEvent) {
+        let chunk = match event
+            .d
+            .clone()
+            .map(serde_json::from_value::<GuildMembersChunk>)
+        {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(err)) => {
+                log::error!(
+                    "Shard {} failed to parse GUILD_MEMBERS_CHUNK: {}",
+                    self.shard_id,
+                    err
+                );
+                return;
+            }
+            None => return,
+        };
+        let Some(nonce) = chunk.nonce.clone() else {
+            return;
+        };
+        let Some(pending) = self.pending_members.get_mut(&nonce) else {
+            return;
+        };
+        log::debug!(
+            "👥 Shard {} received member chunk {}/{} (nonce: {})",
+            self.shard_id,
+            chunk.chunk_index + 1,
+            chunk.chunk_count,
+            nonce
+        );
+        if pending.push(chunk) {
+            if let Some(pending) = self.pending_members.remove(&nonce) {
+                pending.finish();
+            }
         }
-        if self.last_heartbeat_ack.unwrap().elapsed().as_secs() > 15 {
-            log::debug!("Need to send heartbeat");
-            return true;
+    }
+
+    /// Collect the voice state of the bot and the voice server for a pending
+    /// voice channel join
+    fn handle_voice_update(&mut self, event: &ReceiveEvent) {
+        let Some(data) = event.d.clone() else {
+            return;
+        };
+        let guild_id = if event.t == Some(GatewayReceiveEventName::VOICE_STATE_UPDATE) {
+            let Ok(state) = serde_json::from_value::<VoiceState>(data) else {
+                return;
+            };
+            // voice states of other users are of no interest here
+            if self.user_id.as_deref() != Some(state.user_id.as_str()) {
+                return;
+            }
+            let Some(guild_id) = state.guild_id.clone() else {
+                return;
+            };
+            if state.channel_id.is_none() {
+                self.voice_connections.remove(&guild_id);
+            }
+            let Some(pending) = self.pending_voice.get_mut(&guild_id) else {
+                return;
+            };
+            pending.set_state(state);
+            guild_id
+        } else {
+            let Ok(server) = serde_json::from_value::<VoiceServerUpdate>(data) else {
+                return;
+            };
+            let guild_id = server.guild_id.clone();
+            let Some(pending) = self.pending_voice.get_mut(&guild_id) else {
+                // the voice server of the current connection changed
+                if let (Some(current), Some(endpoint)) =
+                    (self.voice_connections.get_mut(&guild_id), server.endpoint)
+                {
+                    current.endpoint = endpoint;
+                    current.token = server.token;
+                }
+                return;
+            };
+            pending.set_server(server);
+            guild_id
+        };
+
+        if self
+            .pending_voice
+            .get(&guild_id)
+            .is_some_and(PendingVoiceConnection::is_complete)
+        {
+            log::info!(
+                "🔊 Shard {} received voice server for guild {}",
+                self.shard_id,
+                guild_id
+            );
+            if let Some(info) = self
+                .pending_voice
+                .remove(&guild_id)
+                .and_then(PendingVoiceConnection::finish)
+            {
+                self.voice_connections.insert(guild_id, info);
+            }
         }
-        if self.last_heartbeat.unwrap().elapsed().as_secs() > 5 {
-            log::debug!("Need to send heartbeat");
-            return true;
+    }
+
+    /// Send the scheduled heartbeat.
+    ///
+    /// Returns false when the previous heartbeat was never acknowledged,
+    /// meaning the connection is zombied and has to be dropped.
+    pub async fn handle_heartbeat(&mut self) -> bool {
+        // the ack may be unread while reads wait for room in the dispatch queue
+        if !self.heartbeat_acked && self.pending_dispatch.is_none() {
+            log::warn!(
+                "🧟 Shard {} did not receive HEARTBEAT_ACK, connection is zombied",
+                self.shard_id
+            );
+            self.next_heartbeat = None;
+            return false;
         }
-        log::debug!("Need to send heartbeat");
+        let now = self.send_heartbeat().await;
+        self.next_heartbeat = self.heartbeat_interval.map(|interval| now + interval);
         true
     }
 
-    pub async fn handle_heartbeat(&mut self) {
-        if self.is_required_heartbeat().await {
-            self.ws.send_heartbeat().await;
+    /// Send a scheduled heartbeat and wait for its ack, returns when it was sent
+    async fn send_heartbeat(&mut self) -> Instant {
+        self.ws.send_heartbeat(self.sequence).await;
+        let now = Instant::now();
+        self.last_heartbeat = Some(now);
+        self.heartbeat_acked = false;
+        now
+    }
+
+    /// Send a heartbeat requested by the gateway.
+    ///
+    /// Acks do not say which heartbeat they belong to, so this one neither
+    /// waits for an ack nor moves the schedule, zombie detection only
+    /// follows the scheduled heartbeats. It is only used for the latency
+    /// when no scheduled heartbeat is waiting for its ack.
+    async fn send_requested_heartbeat(&mut self) {
+        self.ws.send_heartbeat(self.sequence).await;
+        if self.heartbeat_acked {
             self.last_heartbeat = Some(Instant::now());
         }
     }
 
-    pub async fn handle_event(&mut self) {
-        match self.ws.recv().await {
+    /// Handle a message received from the gateway.
+    ///
+    /// Returns `Some` when the connection has to be replaced and an error
+    /// when the connection was closed for a reason reconnecting cannot fix.
+    pub async fn handle_event(
+        &mut self,
+        received: Result<(Option<ReceiveEvent>, bool), GatewayError>,
+    ) -> Result<Option<ReconnectKind>, GatewayError> {
+        match received {
             Ok((Some(event), _)) => {
                 log::debug!(
                     "Shard {} received event: {:?} op: {:?}",
@@ -511,24 +1511,112 @@ impl Manager {
                     event.t,
                     event.op
                 );
-                let event_clone = event.clone();
-                if DiscordOpCode::from(event_clone.op) == DiscordOpCode::HeartbeatAck {
-                    self.last_heartbeat_ack = Some(Instant::now())
-                } else {
-                    self.dispatch(event_clone).await
+                if let Some(sequence) = event.s {
+                    self.sequence = Some(sequence);
+                }
+                match event.op {
+                    DiscordOpCode::Hello => self.handle_hello(&event).await,
+                    DiscordOpCode::HeartbeatAck => {
+                        self.heartbeat_acked = true;
+                        self.last_heartbeat_ack = Some(Instant::now());
+                        log::trace!(
+                            "💓 Shard {} heartbeat acknowledged (latency: {:?})",
+                            self.shard_id,
+                            self.latency()
+                        );
+                    }
+                    DiscordOpCode::Heartbeat => {
+                        // the gateway asked for an immediate heartbeat, the
+                        // scheduled ones keep their interval
+                        self.send_requested_heartbeat().await;
+                    }
+                    DiscordOpCode::Reconnect => {
+                        log::info!("Shard {} was asked to reconnect", self.shard_id);
+                        return Ok(Some(ReconnectKind::Resume));
+                    }
+                    DiscordOpCode::InvalidSession => {
+                        let resumable = event.d.as_ref().and_then(|d| d.as_bool()).unwrap_or(false);
+                        log::warn!(
+                            "Shard {} session invalidated (resumable: {})",
+                            self.shard_id,
+                            resumable
+                        );
+                        if resumable && self.can_resume() {
+                            return Ok(Some(ReconnectKind::Resume));
+                        }
+                        // Discord asks to wait a random 1-5 seconds before identifying again
+                        tokio::time::sleep(Duration::from_secs_f64(1.0 + 4.0 * jitter())).await;
+                        return Ok(Some(ReconnectKind::Identify));
+                    }
+                    _ => {
+                        if event.t == Some(GatewayReceiveEventName::READY) {
+                            self.handle_ready(&event);
+                            self.session_ready = true;
+                        } else if event.t == Some(GatewayReceiveEventName::RESUMED) {
+                            log::info!("🔁 Shard {} resumed its session", self.shard_id);
+                            self.reconnect_attempts = 0;
+                            self.session_ready = true;
+                        } else if event.t == Some(GatewayReceiveEventName::GUILD_MEMBERS_CHUNK) {
+                            self.handle_members_chunk(&event);
+                        } else if event.t == Some(GatewayReceiveEventName::VOICE_STATE_UPDATE)
+                            || event.t == Some(GatewayReceiveEventName::VOICE_SERVER_UPDATE)
+                        {
+                            self.handle_voice_update(&event);
+                        }
+                        self.dispatch(event).await
+                    }
                 }
             }
             Ok((None, _)) => {
-                log::warn!("Shard {} received None event", self.shard_id);
+                log::trace!("Shard {} received frame without payload", self.shard_id);
+            }
+            Err(err) if err.is_fatal() => {
+                log::error!("❌ Shard {} cannot reconnect: {}", self.shard_id, err);
+                return Err(err);
             }
             Err(err) => {
-                log::error!("Shard {} error receiving event: {:?}", self.shard_id, err);
+                log::warn!("Shard {} connection ended: {}", self.shard_id, err);
+                let resumable = match &err {
+                    GatewayError::Closed { code, .. } => code.can_resume(),
+                    GatewayError::ConnectionLost(_)
+                    | GatewayError::ConnectFailed(_)
+                    | GatewayError::Timeout(_) => true,
+                };
+                return Ok(Some(if resumable && self.can_resume() {
+                    ReconnectKind::Resume
+                } else {
+                    ReconnectKind::Identify
+                }));
             }
         }
+        Ok(None)
+    }
+
+    /// Send IDENTIFY using the shard configuration of this manager
+    async fn identify(&mut self) {
+        self.send_identify(
+            self.token.clone(),
+            self.intents,
+            // payload compression must not be combined with transport compression
+            false,
+            self.large_threshold,
+            Some(vec![self.shard_id as i32, self.total_shards as i32]),
+            self.presence.clone(),
+        )
+        .await;
     }
+
     /// Dispatch events to handlers
-    async fn dispatch(&self, event: ReceiveEvent) {
-        if let Some(client) = &self.client {
+    async fn dispatch(&mut self, event: ReceiveEvent) {
+        if let Some(queue) = &self.dispatch_queue {
+            if let Err(event) = queue.try_push(event) {
+                log::debug!(
+                    "⏳ Shard {} dispatch queue is full, pausing reads",
+                    self.shard_id
+                );
+                self.pending_dispatch = Some(event);
+            }
+        } else if let Some(client) = &self.client {
             if let Err(e) = client.event_dispatcher.dispatch_event(&event, client).await {
                 log::error!("Error dispatching event: {:?}", e);
             }
@@ -575,26 +1663,18 @@ impl Manager {
         shard: Option<Vec<i32>>,
         presence: Option<PresenceUpdate>,
     ) {
-        let identify = WebSocketMessage {
-            op: DiscordWebSocket::IDENTIFY,
-            d: WebSocketMessageData::Identify {
-                token: token.to_string(),
-                intents,
-                properties: IdentifyProperties {
-                    os: "linux".to_string(),
-                    browser: "rustycord".to_string(),
-                    device: "rustycord".to_string(),
-                },
-                compress,
-                large_threshold: large_threshold.unwrap_or(50),
-                shard,
-                presence,
-            },
-        };
+        let identify = WebSocketMessage::identify(
+            token,
+            intents,
+            compress,
+            large_threshold.unwrap_or(50),
+            shard,
+            presence,
+        );
         log::info!(
             "🔑 Shard {} - Sending Identification Message",
             self.shard_id
         );
-        self.ws.send_json(identify).await;
+        self.ws.send_priority(identify).await;
     }
 }
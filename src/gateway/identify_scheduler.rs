@@ -0,0 +1,383 @@
+// gateway/identify_scheduler.rs
+
+use std::{
+    sync::{Arc, Mutex as SyncMutex},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Mutex, OwnedMutexGuard},
+    time::Instant,
+};
+
+use crate::http::SessionStartLimit;
+
+/// Window in which `max_concurrency` shards may identify
+const IDENTIFY_WINDOW: Duration = Duration::from_secs(5);
+
+/// Session start limits reset at most once a day
+const SESSION_LIMIT_RESET: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Remaining session starts of the bot
+struct SessionBudget {
+    total: u32,
+    remaining: u32,
+    reset_at: Instant,
+}
+
+/// Rate limit bucket shared by the shards with the same `shard_id % max_concurrency`
+struct IdentifyBucket {
+    /// Shards of the bucket wait for their turn in order, the turn is held
+    /// by the [`IdentifySlot`] until it identified or was dropped
+    queue: Arc<Mutex<()>>,
+    /// Earliest time the next shard of the bucket may identify
+    next_identify: SyncMutex<Option<Instant>>,
+}
+
+/// Orders IDENTIFY calls of all shards so they respect Discord's limits.
+///
+/// Shards are put in rate limit buckets by `shard_id % max_concurrency`,
+/// each bucket may identify once every 5 seconds. Every identify also takes
+/// a session start from the daily budget, once it is used up shards wait
+/// until the budget resets.
+///
+/// Shards take their [`IdentifySlot`] before opening the connection they
+/// identify on, so a shard waiting for its turn has no connection that
+/// would need heartbeats.
+///
+/// More: <https://discord.com/developers/docs/events/gateway#sharding-max-concurrency>
+pub struct IdentifyScheduler {
+    max_concurrency: usize,
+    buckets: Vec<IdentifyBucket>,
+    /// Shards waiting for a session start line up here
+    budget_queue: Mutex<()>,
+    budget: Option<SyncMutex<SessionBudget>>,
+}
+
+impl IdentifyScheduler {
+    /// Scheduler allowing `max_concurrency` identifies every 5 seconds,
+    /// without tracking the session start budget
+    pub fn new(max_concurrency: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+        Self {
+            max_concurrency,
+            buckets: (0..max_concurrency)
+                .map(|_| IdentifyBucket {
+                    queue: Arc::new(Mutex::new(())),
+                    next_identify: SyncMutex::new(None),
+                })
+                .collect(),
+            budget_queue: Mutex::new(()),
+            budget: None,
+        }
+    }
+
+    /// Scheduler following the limits returned by `GET /gateway/bot`
+    ///
+    /// A `total` of 0 can never be refilled, the budget is not tracked then
+    /// and Discord has the last word on session starts.
+    pub fn from_session_start_limit(limit: &SessionStartLimit) -> Self {
+        let mut scheduler = Self::new(limit.max_concurrency);
+        if limit.total == 0 {
+            log::warn!("⚠️ Session start limit without total, not tracking session starts");
+            return scheduler;
+        }
+        scheduler.budget = Some(SyncMutex::new(SessionBudget {
+            total: limit.total,
+            remaining: limit.remaining,
+            reset_at: Instant::now() + Duration::from_millis(limit.reset_after),
+        }));
+        scheduler
+    }
+
+    /// Number of shards that may identify at the same time
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Wait until `shard_id` is allowed to identify.
+    ///
+    /// Call this right before opening the connection a new session is
+    /// identified on, resuming sessions do not need a slot. Other shards of
+    /// the bucket wait until the slot is used or dropped, however long
+    /// connecting takes. The session start is given back when the slot is
+    /// dropped without [`IdentifySlot::identified`], e.g. because the
+    /// connection failed.
+    pub async fn acquire(self: &Arc<Self>, shard_id: usize) -> IdentifySlot {
+        if let Some(budget) = &self.budget {
+            let _turn = self.budget_queue.lock().await;
+            loop {
+                let reset_at = {
+                    let mut budget = budget.lock().unwrap_or_else(|err| err.into_inner());
+                    if budget.remaining > 0 {
+                        budget.remaining -= 1;
+                        log::debug!(
+                            "🎫 Shard {} took a session start ({} remaining)",
+                            shard_id,
+                            budget.remaining
+                        );
+                        break;
+                    }
+                    budget.reset_at
+                };
+                log::warn!(
+                    "⏳ Session start limit reached, shard {} waits {:?} for the reset",
+                    shard_id,
+                    reset_at.saturating_duration_since(Instant::now())
+                );
+                tokio::time::sleep_until(reset_at).await;
+                let mut budget = budget.lock().unwrap_or_else(|err| err.into_inner());
+                if budget.remaining == 0 && budget.reset_at <= Instant::now() {
+                    budget.remaining = budget.total;
+                    budget.reset_at = Instant::now() + SESSION_LIMIT_RESET;
+                }
+            }
+        }
+
+        let bucket = &self.buckets[shard_id % self.max_concurrency];
+        // the turn is held by the slot, the window starts once it identified
+        let turn = bucket.queue.clone().lock_owned().await;
+        loop {
+            let at = {
+                let next_identify = bucket
+                    .next_identify
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner());
+                match *next_identify {
+                    Some(at) if at > Instant::now() => at,
+                    _ => break,
+                }
+            };
+            log::debug!(
+                "⏳ Shard {} waits {:?} to identify",
+                shard_id,
+                at.saturating_duration_since(Instant::now())
+            );
+            tokio::time::sleep_until(at).await;
+        }
+
+        IdentifySlot {
+            scheduler: self.clone(),
+            shard_id,
+            session_start: self.budget.is_some(),
+            _turn: turn,
+        }
+    }
+
+    /// Start the bucket window of `shard_id` now, an IDENTIFY was just sent
+    fn identified(&self, shard_id: usize) {
+        let bucket = &self.buckets[shard_id % self.max_concurrency];
+        let mut next_identify = bucket
+            .next_identify
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        *next_identify = Some(Instant::now() + IDENTIFY_WINDOW);
+    }
+
+    /// Give back a session start that was not used
+    fn release_session_start(&self, shard_id: usize) {
+        if let Some(budget) = &self.budget {
+            let mut budget = budget.lock().unwrap_or_else(|err| err.into_inner());
+            budget.remaining = (budget.remaining + 1).min(budget.total);
+            log::debug!(
+                "🎫 Shard {} gave back its session start ({} remaining)",
+                shard_id,
+                budget.remaining
+            );
+        }
+    }
+}
+
+impl Default for IdentifyScheduler {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// Permission of a shard to identify, granted by [`IdentifyScheduler::acquire`]
+pub struct IdentifySlot {
+    scheduler: Arc<IdentifyScheduler>,
+    shard_id: usize,
+    /// Whether a session start of the budget is held by this slot
+    session_start: bool,
+    /// Turn of the shard in its bucket, released when the slot is dropped
+    _turn: OwnedMutexGuard<()>,
+}
+
+impl IdentifySlot {
+    /// Mark the slot as used once IDENTIFY was sent, the 5 second window of
+    /// the shard's bucket starts from here and the next shard of the bucket
+    /// waits for it
+    pub fn identified(mut self) {
+        self.session_start = false;
+        self.scheduler.identified(self.shard_id);
+    }
+}
+
+impl Drop for IdentifySlot {
+    fn drop(&mut self) {
+        if self.session_start {
+            self.scheduler.release_session_start(self.shard_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(total: u32, remaining: u32, max_concurrency: usize) -> SessionStartLimit {
+        SessionStartLimit {
+            total,
+            remaining,
+            reset_after: 60_000,
+            max_concurrency,
+        }
+    }
+
+    /// Take a slot and identify right away
+    async fn identify(scheduler: &Arc<IdentifyScheduler>, shard_id: usize) {
+        scheduler.acquire(shard_id).await.identified();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn buckets_identify_once_per_window() {
+        let scheduler = Arc::new(IdentifyScheduler::new(2));
+        let start = Instant::now();
+
+        // shards 0 and 1 are in different buckets
+        identify(&scheduler, 0).await;
+        identify(&scheduler, 1).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // shard 2 shares the bucket of shard 0
+        identify(&scheduler, 2).await;
+        assert_eq!(start.elapsed(), IDENTIFY_WINDOW);
+
+        // the bucket of shard 1 was free since the start
+        identify(&scheduler, 3).await;
+        assert_eq!(start.elapsed(), IDENTIFY_WINDOW);
+
+        identify(&scheduler, 4).await;
+        assert_eq!(start.elapsed(), IDENTIFY_WINDOW * 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn more_shards_than_buckets_identify_in_windows() {
+        let scheduler = Arc::new(IdentifyScheduler::new(1));
+        let start = Instant::now();
+
+        let shards: Vec<_> = (0..16)
+            .map(|shard_id| {
+                let scheduler = scheduler.clone();
+                tokio::spawn(async move {
+                    identify(&scheduler, shard_id).await;
+                    start.elapsed()
+                })
+            })
+            .collect();
+        let mut identified_at = Vec::new();
+        for shard in shards {
+            identified_at.push(shard.await.unwrap());
+        }
+        identified_at.sort();
+
+        for (window, at) in identified_at.iter().enumerate() {
+            assert_eq!(*at, IDENTIFY_WINDOW * window as u32);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn window_starts_when_identify_is_sent() {
+        let scheduler = Arc::new(IdentifyScheduler::new(1));
+        let start = Instant::now();
+
+        // connecting took 2 seconds before IDENTIFY went out
+        let slot = scheduler.acquire(0).await;
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        slot.identified();
+
+        identify(&scheduler, 1).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2) + IDENTIFY_WINDOW);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bucket_waits_for_a_slot_held_longer_than_the_window() {
+        let scheduler = Arc::new(IdentifyScheduler::new(1));
+        let start = Instant::now();
+
+        let slot = scheduler.acquire(0).await;
+        let next = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move {
+                identify(&scheduler, 1).await;
+                start.elapsed()
+            }
+        });
+        // connecting took longer than a whole window
+        tokio::time::sleep(IDENTIFY_WINDOW + Duration::from_secs(3)).await;
+        slot.identified();
+
+        let identified_at = next.await.unwrap();
+        assert_eq!(identified_at, IDENTIFY_WINDOW * 2 + Duration::from_secs(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn dropped_slot_frees_the_bucket_without_a_window() {
+        let scheduler = Arc::new(IdentifyScheduler::new(1));
+        let start = Instant::now();
+
+        // the connection failed before IDENTIFY was sent
+        drop(scheduler.acquire(0).await);
+        identify(&scheduler, 1).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_the_reset_once_session_starts_are_used_up() {
+        let scheduler = Arc::new(IdentifyScheduler::from_session_start_limit(&limit(
+            3, 1, 16,
+        )));
+        let start = Instant::now();
+
+        identify(&scheduler, 0).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        identify(&scheduler, 1).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+
+        // the budget was refilled to the total
+        identify(&scheduler, 2).await;
+        identify(&scheduler, 3).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+        identify(&scheduler, 4).await;
+        assert_eq!(
+            start.elapsed(),
+            Duration::from_secs(60) + SESSION_LIMIT_RESET
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unused_slots_give_back_their_session_start() {
+        let scheduler = Arc::new(IdentifyScheduler::from_session_start_limit(&limit(
+            3, 1, 16,
+        )));
+        let start = Instant::now();
+
+        // the connection failed before IDENTIFY was sent
+        drop(scheduler.acquire(0).await);
+
+        identify(&scheduler, 1).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_total_does_not_track_session_starts() {
+        let scheduler = Arc::new(IdentifyScheduler::from_session_start_limit(&limit(0, 0, 1)));
+        let start = Instant::now();
+
+        identify(&scheduler, 0).await;
+        identify(&scheduler, 0).await;
+        assert_eq!(start.elapsed(), IDENTIFY_WINDOW);
+    }
+}
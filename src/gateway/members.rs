@@ -0,0 +1,349 @@
+// gateway/members.rs
+
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::{
+    error::{Error, Result},
+    gateway::event::GuildMembersChunk,
+    models::{guild::GuildMember, user::Presence},
+};
+
+/// Maximum length of a nonce in bytes
+pub const MAX_NONCE_LENGTH: usize = 32;
+
+/// Maximum number of user ids of a single request
+pub const MAX_USER_IDS: usize = 100;
+
+/// Counter making nonces unique within the process
+static NEXT_NONCE: AtomicU64 = AtomicU64::new(0);
+
+/// Used to request all members for a guild or a list of guilds.
+///
+/// Requesting all members or members by query requires the `GUILD_MEMBERS`
+/// intent, presences require the `GUILD_PRESENCES` intent.
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#request-guild-members>
+#[derive(Debug, Serialize, Clone)]
+pub struct RequestGuildMembers {
+    /// ID of the guild to get members for
+    pub guild_id: String,
+
+    /// String that username starts with, or an empty string to return all members
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Maximum number of members to send matching the query, 0 with an empty query returns all members
+    pub limit: u32,
+
+    /// Used to specify if we want the presences of the matched members
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presences: Option<bool>,
+
+    /// Used to specify which users you wish to fetch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_ids: Option<Vec<String>>,
+
+    /// Nonce to identify the Guild Members Chunk response, generated when not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+impl RequestGuildMembers {
+    /// Request every member of the guild
+    pub fn all(guild_id: impl Into<String>) -> Self {
+        Self::query(guild_id, "", 0)
+    }
+
+    /// Request up to `limit` members whose username starts with `query`
+    pub fn query(guild_id: impl Into<String>, query: impl Into<String>, limit: u32) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            query: Some(query.into()),
+            limit,
+            presences: None,
+            user_ids: None,
+            nonce: None,
+        }
+    }
+
+    /// Request the members with the given user ids, at most 100
+    pub fn user_ids(guild_id: impl Into<String>, user_ids: Vec<String>) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            query: None,
+            limit: 0,
+            presences: None,
+            user_ids: Some(user_ids),
+            nonce: None,
+        }
+    }
+
+    /// Also request the presences of the members
+    pub fn with_presences(mut self, presences: bool) -> Self {
+        self.presences = Some(presences);
+        self
+    }
+
+    /// Set the nonce of the request, at most 32 bytes and not used by
+    /// another pending request of the shard
+    pub fn with_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Check the request against the limits of Discord, so an invalid request
+    /// fails without being sent
+    pub fn validate(&self) -> Result<()> {
+        if let Some(nonce) = &self.nonce {
+            if nonce.len() > MAX_NONCE_LENGTH {
+                return Err(Error::Validation(format!(
+                    "nonce is longer than {} bytes",
+                    MAX_NONCE_LENGTH
+                )));
+            }
+        }
+        let user_ids = self.user_ids.as_deref().unwrap_or_default();
+        if user_ids.len() > MAX_USER_IDS {
+            return Err(Error::Validation(format!(
+                "more than {} user ids",
+                MAX_USER_IDS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Nonce of the request, generating one for `shard_id` when none is set.
+    ///
+    /// Fails when the request is invalid.
+    pub(crate) fn ensure_nonce(&mut self, shard_id: usize) -> Result<String> {
+        let nonce = self
+            .nonce
+            .get_or_insert_with(|| {
+                format!(
+                    "{}-{}",
+                    shard_id,
+                    NEXT_NONCE.fetch_add(1, Ordering::Relaxed)
+                )
+            })
+            .clone();
+        self.validate()?;
+        Ok(nonce)
+    }
+}
+
+/// Members received for a Request Guild Members, collected from all chunks
+#[derive(Debug, Clone, Default)]
+pub struct GuildMembers {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// Members of all chunks
+    pub members: Vec<GuildMember>,
+
+    /// Presences of the members, when requested
+    pub presences: Vec<Presence>,
+
+    /// Requested user ids that are not members of the guild
+    pub not_found: Vec<serde_json::Value>,
+
+    /// Nonce of the request
+    pub nonce: Option<String>,
+}
+
+/// A request waiting for its chunks
+pub(crate) struct PendingGuildMembers {
+    members: GuildMembers,
+    /// Number of chunks received so far
+    received: u32,
+    sender: oneshot::Sender<Result<GuildMembers>>,
+}
+
+impl PendingGuildMembers {
+    pub(crate) fn new(
+        guild_id: String,
+        nonce: String,
+        sender: oneshot::Sender<Result<GuildMembers>>,
+    ) -> Self {
+        Self {
+            members: GuildMembers {
+                guild_id,
+                nonce: Some(nonce),
+                ..Default::default()
+            },
+            received: 0,
+            sender,
+        }
+    }
+
+    /// Add a chunk, returns true once all chunks were received.
+    ///
+    /// Chunks may arrive in any order, the request is complete once as many
+    /// chunks as announced by `chunk_count` were received.
+    pub(crate) fn push(&mut self, chunk: GuildMembersChunk) -> bool {
+        self.members.members.extend(chunk.members);
+        self.members.presences.extend(chunk.presences);
+        self.members.not_found.extend(chunk.not_found);
+        self.received += 1;
+        self.received >= chunk.chunk_count
+    }
+
+    /// Hand the collected members to the requester
+    pub(crate) fn finish(self) {
+        // the requester may have stopped waiting
+        let _ = self.sender.send(Ok(self.members));
+    }
+
+    /// Fail when `nonce` is already used by one of the `pending` requests,
+    /// their chunks could not be told apart
+    pub(crate) fn check_unused(pending: &HashMap<String, Self>, nonce: &str) -> Result<()> {
+        if pending.contains_key(nonce) {
+            return Err(Error::Validation(format!(
+                "nonce {} is already used by a pending member request",
+                nonce
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn chunk(
+        chunk_index: u32,
+        chunk_count: u32,
+        user_ids: &[&str],
+        not_found: Value,
+    ) -> GuildMembersChunk {
+        let members: Vec<Value> = user_ids
+            .iter()
+            .map(|id| {
+                json!({
+                    "user": {
+                        "id": id,
+                        "username": format!("user-{}", id),
+                        "discriminator": "0",
+                        "global_name": null,
+                        "avatar": null,
+                    },
+                    "nick": null,
+                    "roles": [],
+                    "joined_at": "2024-01-01T00:00:00.000000+00:00",
+                    "deaf": false,
+                    "mute": false,
+                    "flags": 0,
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "guild_id": "1000",
+            "members": members,
+            "chunk_index": chunk_index,
+            "chunk_count": chunk_count,
+            "not_found": not_found,
+            "nonce": "0-1",
+        }))
+        .unwrap()
+    }
+
+    fn pending() -> (PendingGuildMembers, oneshot::Receiver<Result<GuildMembers>>) {
+        let (sender, receiver) = oneshot::channel();
+        (
+            PendingGuildMembers::new("1000".to_string(), "0-1".to_string(), sender),
+            receiver,
+        )
+    }
+
+    fn user_ids(members: &GuildMembers) -> Vec<String> {
+        members
+            .members
+            .iter()
+            .map(|member| member.user.as_ref().unwrap().id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn single_chunk_completes_the_request() {
+        let (mut pending, mut receiver) = pending();
+        assert!(pending.push(chunk(0, 1, &["1", "2"], json!([]))));
+        pending.finish();
+
+        let members = receiver.try_recv().unwrap().unwrap();
+        assert_eq!(members.guild_id, "1000");
+        assert_eq!(members.nonce.as_deref(), Some("0-1"));
+        assert_eq!(user_ids(&members), ["1", "2"]);
+    }
+
+    #[test]
+    fn chunks_out_of_order_wait_for_every_chunk() {
+        let (mut pending, mut receiver) = pending();
+        assert!(!pending.push(chunk(2, 3, &["5"], json!([]))));
+        assert!(!pending.push(chunk(0, 3, &["1", "2"], json!([]))));
+        assert!(pending.push(chunk(1, 3, &["3", "4"], json!([]))));
+        pending.finish();
+
+        let mut ids = user_ids(&receiver.try_recv().unwrap().unwrap());
+        ids.sort();
+        assert_eq!(ids, ["1", "2", "3", "4", "5"]);
+    }
+
+    #[test]
+    fn not_found_ids_are_collected() {
+        let (mut pending, mut receiver) = pending();
+        assert!(!pending.push(chunk(0, 2, &["1"], json!(["404"]))));
+        assert!(pending.push(chunk(1, 2, &[], json!(["405", 406]))));
+        pending.finish();
+
+        let members = receiver.try_recv().unwrap().unwrap();
+        assert_eq!(user_ids(&members), ["1"]);
+        assert_eq!(members.not_found, [json!("404"), json!("405"), json!(406)]);
+    }
+
+    #[test]
+    fn nonce_longer_than_32_bytes_is_rejected() {
+        let mut request = RequestGuildMembers::all("1000").with_nonce("n".repeat(32));
+        assert_eq!(request.ensure_nonce(0).unwrap(), "n".repeat(32));
+
+        // 11 characters of 3 bytes each
+        let mut request = RequestGuildMembers::all("1000").with_nonce("€".repeat(11));
+        assert!(matches!(request.ensure_nonce(0), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn generated_nonce_is_valid() {
+        let mut request = RequestGuildMembers::all("1000");
+        let nonce = request.ensure_nonce(usize::MAX).unwrap();
+        assert!(nonce.len() <= MAX_NONCE_LENGTH);
+        assert_eq!(request.nonce, Some(nonce));
+    }
+
+    #[test]
+    fn nonce_of_a_pending_request_is_rejected() {
+        let (pending, _receiver) = pending();
+        let pending = HashMap::from([("0-1".to_string(), pending)]);
+        assert!(PendingGuildMembers::check_unused(&pending, "0-2").is_ok());
+        assert!(matches!(
+            PendingGuildMembers::check_unused(&pending, "0-1"),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn more_than_100_user_ids_are_rejected() {
+        let ids = |count: usize| (0..count).map(|id| id.to_string()).collect();
+        let mut request = RequestGuildMembers::user_ids("1000", ids(100));
+        assert!(request.ensure_nonce(0).is_ok());
+
+        let mut request = RequestGuildMembers::user_ids("1000", ids(101));
+        assert!(matches!(request.ensure_nonce(0), Err(Error::Validation(_))));
+    }
+}
@@ -0,0 +1,283 @@
+// gateway/messenger.rs
+
+use std::time::Duration;
+
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    time::timeout,
+};
+
+use crate::{
+    error::{Error, Result},
+    gateway::{
+        gateway::{GatewayError, PresenceUpdate},
+        members::{GuildMembers, RequestGuildMembers},
+        voice_state::{UpdateVoiceState, VoiceConnectionInfo},
+    },
+};
+
+/// Command sent to a running shard
+#[derive(Debug)]
+pub enum ShardCommand {
+    /// Send a new presence to the gateway, it is also used for later IDENTIFYs
+    UpdatePresence(PresenceUpdate),
+
+    /// Change only the status (`online`, `dnd`, `idle`, `invisible`) of the presence
+    UpdateStatus(String),
+
+    /// Send Request Guild Members and answer with the members of all chunks
+    RequestGuildMembers(RequestGuildMembers, oneshot::Sender<Result<GuildMembers>>),
+
+    /// Forget the Request Guild Members with this nonce, its requester stopped waiting
+    CancelGuildMembers(String),
+
+    /// Send Update Voice State, answering with the voice server of a joined channel
+    UpdateVoiceState(
+        UpdateVoiceState,
+        Option<oneshot::Sender<VoiceConnectionInfo>>,
+    ),
+
+    /// Forget the voice channel join of this guild if its requester stopped waiting
+    CancelVoiceConnection(String),
+
+    /// Close the connection and start a new session
+    Restart,
+
+    /// Close the connection with a normal close frame and stop the shard
+    Shutdown,
+}
+
+/// Handle to control a running shard from application code.
+///
+/// Messengers are cheap to clone, every clone talks to the same shard.
+/// Commands are queued and handled by the shard between gateway events.
+#[derive(Debug, Clone)]
+pub struct ShardMessenger {
+    shard_id: usize,
+    sender: UnboundedSender<ShardCommand>,
+}
+
+impl ShardMessenger {
+    /// How long [`request_guild_members`](Self::request_guild_members) waits for all chunks
+    pub const MEMBERS_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// How long [`join_voice`](Self::join_voice) waits for the voice state and server updates
+    pub const VOICE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create a messenger and the receiving end that is handed to the shard
+    pub fn channel(shard_id: usize) -> (Self, UnboundedReceiver<ShardCommand>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { shard_id, sender }, receiver)
+    }
+
+    /// Id of the shard this messenger talks to
+    pub fn shard_id(&self) -> usize {
+        self.shard_id
+    }
+
+    /// Send a command to the shard.
+    ///
+    /// Returns false when the shard has already stopped.
+    pub fn send(&self, command: ShardCommand) -> bool {
+        log::debug!("📨 Sending {:?} to shard {}", command, self.shard_id);
+        self.sender.send(command).is_ok()
+    }
+
+    /// Update the presence of the shard
+    pub fn set_presence(&self, presence: PresenceUpdate) -> bool {
+        self.send(ShardCommand::UpdatePresence(presence))
+    }
+
+    /// Update the status of the shard, keeping its activities
+    pub fn set_status(&self, status: impl Into<String>) -> bool {
+        self.send(ShardCommand::UpdateStatus(status.into()))
+    }
+
+    /// Request members of a guild handled by this shard.
+    ///
+    /// Resolves once every GUILD_MEMBERS_CHUNK with the request's nonce was
+    /// received. Fails with [`Error::Validation`] when the request is invalid
+    /// or its nonce is used by another pending request, when the shard stopped
+    /// or lost its session first, or when the chunks did not arrive within
+    /// [`MEMBERS_TIMEOUT`](Self::MEMBERS_TIMEOUT).
+    ///
+    /// ```rust,no_run
+    /// # async fn example(messenger: rustycord::gateway::messenger::ShardMessenger) {
+    /// use rustycord::gateway::members::RequestGuildMembers;
+    ///
+    /// let request = RequestGuildMembers::query("1234", "dha", 10).with_presences(true);
+    /// if let Ok(members) = messenger.request_guild_members(request).await {
+    ///     println!("Found {} members", members.members.len());
+    /// }
+    /// # }
+    /// ```
+    pub async fn request_guild_members(
+        &self,
+        mut request: RequestGuildMembers,
+    ) -> Result<GuildMembers> {
+        let nonce = request.ensure_nonce(self.shard_id)?;
+        let (sender, receiver) = oneshot::channel();
+        if !self.send(ShardCommand::RequestGuildMembers(request, sender)) {
+            return Err(self.stopped());
+        }
+        match timeout(Self::MEMBERS_TIMEOUT, receiver).await {
+            Ok(Ok(members)) => members,
+            Ok(Err(_)) => Err(self.stopped()),
+            Err(_) => {
+                self.send(ShardCommand::CancelGuildMembers(nonce.clone()));
+                Err(Error::Gateway(GatewayError::Timeout(format!(
+                    "member request {}",
+                    nonce
+                ))))
+            }
+        }
+    }
+
+    /// Join or move to a voice channel of a guild handled by this shard.
+    ///
+    /// Resolves once both VOICE_STATE_UPDATE and VOICE_SERVER_UPDATE were
+    /// received, with what a voice client needs to connect. A move within the
+    /// guild resolves on the VOICE_STATE_UPDATE alone when Discord keeps the
+    /// voice server, reusing its endpoint and token. Fails when the
+    /// shard stopped or lost its session first, or when the updates did not
+    /// arrive within [`VOICE_TIMEOUT`](Self::VOICE_TIMEOUT).
+    pub async fn join_voice(
+        &self,
+        guild_id: impl Into<String>,
+        channel_id: impl Into<String>,
+        self_mute: bool,
+        self_deaf: bool,
+    ) -> Result<VoiceConnectionInfo> {
+        let update = UpdateVoiceState::join(guild_id, channel_id, self_mute, self_deaf);
+        let guild_id = update.guild_id.clone();
+        let (sender, receiver) = oneshot::channel();
+        if !self.send(ShardCommand::UpdateVoiceState(update, Some(sender))) {
+            return Err(self.stopped());
+        }
+        match timeout(Self::VOICE_TIMEOUT, receiver).await {
+            Ok(Ok(info)) => Ok(info),
+            Ok(Err(_)) => Err(self.stopped()),
+            Err(_) => {
+                // the receiver was dropped with the timeout, marking the join as abandoned
+                self.send(ShardCommand::CancelVoiceConnection(guild_id.clone()));
+                Err(Error::Gateway(GatewayError::Timeout(format!(
+                    "voice channel join in guild {}",
+                    guild_id
+                ))))
+            }
+        }
+    }
+
+    /// Leave the voice channel of a guild
+    pub fn leave_voice(&self, guild_id: impl Into<String>) -> bool {
+        self.send(ShardCommand::UpdateVoiceState(
+            UpdateVoiceState::leave(guild_id),
+            None,
+        ))
+    }
+
+    /// Reconnect the shard with a new session
+    pub fn restart(&self) -> bool {
+        self.send(ShardCommand::Restart)
+    }
+
+    /// Gracefully close the gateway connection and stop the shard
+    pub fn shutdown(&self) -> bool {
+        self.send(ShardCommand::Shutdown)
+    }
+
+    /// Error of a request the shard dropped without answering
+    fn stopped(&self) -> Error {
+        Error::Gateway(GatewayError::ConnectionLost(format!(
+            "shard {} stopped or lost its session",
+            self.shard_id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn member_request_times_out_and_is_cancelled() {
+        let (messenger, mut commands) = ShardMessenger::channel(3);
+        let request = tokio::spawn(async move {
+            messenger
+                .request_guild_members(RequestGuildMembers::all("1000"))
+                .await
+        });
+
+        // keep the sender alive, the shard never answers
+        let Some(ShardCommand::RequestGuildMembers(sent, _sender)) = commands.recv().await else {
+            panic!("expected a member request");
+        };
+        let nonce = sent.nonce.expect("nonce set by the messenger");
+        assert!(nonce.starts_with("3-"));
+
+        let result = request.await.unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Gateway(GatewayError::Timeout(_)))
+        ));
+        match commands.recv().await {
+            Some(ShardCommand::CancelGuildMembers(cancelled)) => assert_eq!(cancelled, nonce),
+            other => panic!("expected the request to be cancelled, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn voice_join_times_out_and_is_cancelled() {
+        let (messenger, mut commands) = ShardMessenger::channel(0);
+        let join =
+            tokio::spawn(async move { messenger.join_voice("1000", "3000", false, true).await });
+
+        let Some(ShardCommand::UpdateVoiceState(update, Some(sender))) = commands.recv().await
+        else {
+            panic!("expected a voice state update waiting for an answer");
+        };
+        assert_eq!(update.channel_id.as_deref(), Some("3000"));
+
+        let result = join.await.unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Gateway(GatewayError::Timeout(_)))
+        ));
+        assert!(sender.is_closed());
+        match commands.recv().await {
+            Some(ShardCommand::CancelVoiceConnection(guild_id)) => assert_eq!(guild_id, "1000"),
+            other => panic!("expected the join to be cancelled, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn member_request_fails_when_the_shard_drops_it() {
+        let (messenger, mut commands) = ShardMessenger::channel(0);
+        let request = tokio::spawn(async move {
+            messenger
+                .request_guild_members(RequestGuildMembers::all("1000"))
+                .await
+        });
+        drop(commands.recv().await);
+
+        let result = request.await.unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Gateway(GatewayError::ConnectionLost(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn invalid_member_request_is_not_sent() {
+        let (messenger, mut commands) = ShardMessenger::channel(0);
+        let request = RequestGuildMembers::all("1000").with_nonce("n".repeat(33));
+        let result = messenger.request_guild_members(request).await;
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        drop(messenger);
+        assert!(commands.recv().await.is_none());
+    }
+}
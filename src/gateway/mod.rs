@@ -1,4 +1,15 @@
+pub mod compression;
+pub mod encoding;
+pub mod etf;
+pub mod event;
+#[allow(clippy::module_inception)]
 pub mod gateway;
+pub mod identify_scheduler;
 pub mod intents;
+pub mod members;
+pub mod messenger;
+pub mod ratelimit;
+pub mod recorder;
 pub mod response;
-pub mod shard_manager;
\ No newline at end of file
+pub mod shard_manager;
+pub mod voice_state;
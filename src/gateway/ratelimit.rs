@@ -0,0 +1,121 @@
+// gateway/ratelimit.rs
+
+use std::{collections::VecDeque, time::Duration};
+
+use tokio::time::Instant;
+
+/// Gateway commands allowed per connection and period
+const COMMANDS_PER_PERIOD: usize = 120;
+
+/// Period of the gateway command limit
+const PERIOD: Duration = Duration::from_secs(60);
+
+/// Commands kept free for heartbeats, IDENTIFY and RESUME.
+///
+/// Heartbeat intervals are around 41 seconds, this leaves room for heartbeats
+/// down to a 15 second interval plus a session start in every period.
+const RESERVED_FOR_HEARTBEATS: usize = 5;
+
+/// Outbound limiter of a single gateway connection.
+///
+/// Discord closes connections sending more than 120 commands per 60 seconds.
+/// Regular commands such as presence updates, member requests and voice
+/// state updates may only use part of the budget, so heartbeats are never
+/// starved by a busy bot.
+///
+/// More: <https://discord.com/developers/docs/events/gateway#rate-limiting>
+#[derive(Debug)]
+pub struct GatewayRateLimiter {
+    /// When the commands of the current period were sent, oldest first
+    sent: VecDeque<Instant>,
+}
+
+impl GatewayRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            sent: VecDeque::with_capacity(COMMANDS_PER_PERIOD),
+        }
+    }
+
+    /// Forget commands sent before the current period
+    fn prune(&mut self, now: Instant) {
+        while let Some(sent) = self.sent.front() {
+            if now.duration_since(*sent) < PERIOD {
+                break;
+            }
+            self.sent.pop_front();
+        }
+    }
+
+    fn limit(priority: bool) -> usize {
+        if priority {
+            COMMANDS_PER_PERIOD
+        } else {
+            COMMANDS_PER_PERIOD - RESERVED_FOR_HEARTBEATS
+        }
+    }
+
+    /// When the next command can be sent, `priority` commands may use the
+    /// budget reserved for heartbeats
+    pub fn next_slot(&mut self, priority: bool) -> Instant {
+        let now = Instant::now();
+        self.prune(now);
+
+        let limit = Self::limit(priority);
+        if self.sent.len() < limit {
+            now
+        } else {
+            // wait until enough commands left the period to get below the limit
+            self.sent[self.sent.len() - limit] + PERIOD
+        }
+    }
+
+    /// Record a command that was sent
+    pub fn record(&mut self) {
+        self.sent.push_back(Instant::now());
+    }
+
+    /// Number of regular commands that can be sent right now
+    pub fn remaining(&mut self) -> usize {
+        self.prune(Instant::now());
+        Self::limit(false).saturating_sub(self.sent.len())
+    }
+}
+
+impl Default for GatewayRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn regular_commands_leave_room_for_priority_ones() {
+        let mut limiter = GatewayRateLimiter::new();
+        let start = Instant::now();
+        for _ in 0..COMMANDS_PER_PERIOD - RESERVED_FOR_HEARTBEATS {
+            assert_eq!(limiter.next_slot(false), Instant::now());
+            limiter.record();
+            tokio::time::advance(Duration::from_millis(100)).await;
+        }
+
+        // 115 commands sent within the window, regular ones wait for the first to expire
+        assert_eq!(limiter.remaining(), 0);
+        assert_eq!(limiter.next_slot(false), start + PERIOD);
+        assert_eq!(limiter.next_slot(true), Instant::now());
+
+        // heartbeats may use the reserved budget, but not more
+        for _ in 0..RESERVED_FOR_HEARTBEATS {
+            assert_eq!(limiter.next_slot(true), Instant::now());
+            limiter.record();
+        }
+        assert_eq!(limiter.next_slot(true), start + PERIOD);
+
+        tokio::time::advance(start + PERIOD - Instant::now()).await;
+        assert_eq!(limiter.next_slot(true), Instant::now());
+        assert!(limiter.next_slot(false) > Instant::now());
+    }
+}
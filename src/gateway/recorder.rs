@@ -0,0 +1,366 @@
+// gateway/recorder.rs
+
+//! Record gateway payloads and replay them offline.
+//!
+//! An [`EventRecorder`] set on the [`Client`] writes every payload received
+//! by its shards to a JSON-lines file, one [`RecordedPayload`] per line. The
+//! payload is written as the gateway sent it, ETF payloads as the json they
+//! decode to, and the bot token is replaced by `[REDACTED]` wherever it
+//! appears. Lines are written by a background task, so shards never wait
+//! for the disk.
+//!
+//! [`replay`] feeds a recording through
+//! [`EventDispatcher::dispatch_event`](crate::handlers::event_dispatcher::EventDispatcher::dispatch_event)
+//! in order, so a handler bug can be reproduced without a gateway connection:
+//!
+//! ```rust,no_run
+//! use rustycord::{client::Client, gateway::recorder};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> rustycord::Result<()> {
+//! // a client that is never logged in, register the handlers under test on it
+//! let client = Client::new();
+//! let dispatched = recorder::replay("incident.jsonl", &client).await?;
+//! println!("Replayed {} events", dispatched);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter},
+    sync::{mpsc, oneshot},
+};
+
+use crate::{
+    client::Client,
+    error::{Error, Result},
+    gateway::{
+        encoding::GatewayEncoding,
+        etf,
+        response::{DiscordOpCode, DiscordReceiveEvent, ReceiveEvent},
+    },
+};
+
+/// Placeholder written instead of the bot token
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A line of a recording
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordedPayload {
+    /// Shard that received the payload
+    pub shard_id: usize,
+
+    /// When the payload was received, in milliseconds since the unix epoch
+    pub received_at: u64,
+
+    /// The payload as sent by the gateway, parsed the same way a shard does
+    pub payload: DiscordReceiveEvent,
+}
+
+/// Work for the task writing a recording
+enum WriterCommand {
+    Line(String),
+    /// Answered once every line sent before was written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes the gateway payloads received by all shards to a JSON-lines file
+pub struct EventRecorder {
+    path: PathBuf,
+    writer: mpsc::UnboundedSender<WriterCommand>,
+}
+
+impl EventRecorder {
+    /// Create the recording file, an existing file is truncated
+    pub async fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path).await.inspect_err(|err| {
+            log::error!("❌ Failed to create recording {}: {}", path.display(), err)
+        })?;
+        log::info!("🎙️ Recording gateway payloads to {}", path.display());
+        let (writer, commands) = mpsc::unbounded_channel();
+        tokio::spawn(write_lines(BufWriter::new(file), path.clone(), commands));
+        Ok(Self { path, writer })
+    }
+
+    /// Path of the recording file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Recorder for the payloads of one shard
+    pub(crate) fn for_shard(self: &Arc<Self>, shard_id: usize, token: &str) -> ShardRecorder {
+        ShardRecorder {
+            recorder: self.clone(),
+            shard_id,
+            token: token.to_string(),
+        }
+    }
+
+    /// Queue a received json payload for writing, `token` is redacted
+    pub(crate) fn record(&self, shard_id: usize, payload: &str, token: &str) {
+        let received_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        // json strings escape line breaks, the ones left are whitespace
+        // between tokens and would split the line
+        let payload = payload.trim().replace(['\r', '\n'], " ");
+        let mut line = format!(
+            "{{\"shard_id\":{},\"received_at\":{},\"payload\":{}}}\n",
+            shard_id, received_at, payload
+        );
+        if !token.is_empty() {
+            line = line.replace(token, REDACTED);
+        }
+        if self.writer.send(WriterCommand::Line(line)).is_err() {
+            log::error!(
+                "❌ Recording {} stopped, dropping payload",
+                self.path.display()
+            );
+        }
+    }
+
+    /// Wait until the payloads recorded so far were written to the file
+    pub async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        if self.writer.send(WriterCommand::Flush(done)).is_ok() {
+            let _ = written.await;
+        }
+    }
+}
+
+/// Records the payloads received by one shard
+#[derive(Clone)]
+pub(crate) struct ShardRecorder {
+    recorder: Arc<EventRecorder>,
+    shard_id: usize,
+    token: String,
+}
+
+impl ShardRecorder {
+    /// Record a payload as it was received, before it is parsed
+    pub(crate) fn record(&self, payload: &[u8], encoding: GatewayEncoding) {
+        let payload = match encoding {
+            GatewayEncoding::Json => String::from_utf8_lossy(payload).into_owned(),
+            // ETF has no text form, record the json it decodes to
+            GatewayEncoding::Etf => match etf::decode(payload) {
+                Ok(value) => value.to_string(),
+                Err(err) => {
+                    log::error!("❌ Failed to record ETF payload: {}", err);
+                    return;
+                }
+            },
+        };
+        self.recorder.record(self.shard_id, &payload, &self.token);
+    }
+}
+
+/// Write queued lines until the recorder was dropped
+async fn write_lines(
+    mut file: BufWriter<File>,
+    path: PathBuf,
+    mut commands: mpsc::UnboundedReceiver<WriterCommand>,
+) {
+    while let Some(command) = commands.recv().await {
+        let result = match command {
+            WriterCommand::Line(line) => file.write_all(line.as_bytes()).await,
+            WriterCommand::Flush(done) => {
+                let result = file.flush().await;
+                let _ = done.send(());
+                result
+            }
+        };
+        // flush whenever the queue ran empty so a recording survives a crash of the bot
+        let result = match result {
+            Ok(()) if commands.is_empty() => file.flush().await,
+            result => result,
+        };
+        if let Err(err) = result {
+            log::error!("❌ Failed to write recording {}: {}", path.display(), err);
+        }
+    }
+    let _ = file.flush().await;
+}
+
+/// Read all payloads of a recording
+pub async fn read_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedPayload>> {
+    let path = path.as_ref();
+    let file = File::open(path).await.inspect_err(|err| {
+        log::error!("❌ Failed to open recording {}: {}", path.display(), err)
+    })?;
+    let mut lines = BufReader::new(file).lines();
+    let mut payloads = Vec::new();
+    let mut line_number = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let payload = serde_json::from_str::<RecordedPayload>(&line)
+            .map_err(|err| Error::Decode(format!("line {}: {}", line_number, err)))?;
+        payloads.push(payload);
+    }
+    Ok(payloads)
+}
+
+/// Dispatch the events of a recording to the handlers of `client`, one
+/// after another in recorded order.
+///
+/// Only dispatch payloads reach handlers, the same way they do when received
+/// by a shard. Returns the number of dispatched events.
+pub async fn replay(path: impl AsRef<Path>, client: &Client) -> Result<usize> {
+    let payloads = read_recording(path).await?;
+    Ok(replay_payloads(payloads, client).await)
+}
+
+/// Dispatch already read payloads to the handlers of `client`
+pub async fn replay_payloads<I>(payloads: I, client: &Client) -> usize
+where
+    I: IntoIterator<Item = RecordedPayload>,
+{
+    let mut dispatched = 0;
+    for recorded in payloads {
+        let event = ReceiveEvent::from(recorded.payload);
+        if event.op != DiscordOpCode::Dispatch {
+            continue;
+        }
+        log::debug!("⏪ Replaying {:?} of shard {}", event.t, recorded.shard_id);
+        if let Err(err) = client.event_dispatcher.dispatch_event(&event, client).await {
+            log::error!("Error dispatching event: {:?}", err);
+        }
+        dispatched += 1;
+    }
+    dispatched
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as SyncMutex;
+
+    use async_trait::async_trait;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::handlers::{
+        message_handler::MessageHandlerResult, raw_event_handler::RawEventHandler,
+    };
+
+    const TOKEN: &str = "MTAw.secret-token";
+
+    /// Recorder writing to a fresh file in the temp directory
+    async fn recorder(name: &str) -> Arc<EventRecorder> {
+        let path = std::env::temp_dir().join(format!(
+            "rustycord-recorder-{}-{}.jsonl",
+            std::process::id(),
+            name
+        ));
+        Arc::new(EventRecorder::create(path).await.unwrap())
+    }
+
+    async fn read_lines(recorder: &EventRecorder) -> Vec<String> {
+        recorder.flush().await;
+        let text = tokio::fs::read_to_string(recorder.path()).await.unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+
+    /// Collects the names of the events it receives
+    struct EventNames(Arc<SyncMutex<Vec<String>>>);
+
+    #[async_trait]
+    impl RawEventHandler for EventNames {
+        async fn on_raw_event(
+            &self,
+            event: &ReceiveEvent,
+            _client: &Client,
+        ) -> MessageHandlerResult {
+            let name = event.t.as_ref().map(|name| name.as_wire_name());
+            self.0.lock().unwrap().push(name.unwrap_or_default());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn token_is_redacted_from_recorded_lines() {
+        let recorder = recorder("redact").await;
+        let shard = recorder.for_shard(0, TOKEN);
+        let payload = format!(
+            r#"{{"op":0,"s":1,"t":"MESSAGE_CREATE","d":{{"content":"token is {}"}}}}"#,
+            TOKEN
+        );
+        shard.record(payload.as_bytes(), GatewayEncoding::Json);
+
+        let lines = read_lines(&recorder).await;
+        assert_eq!(lines.len(), 1);
+        assert!(!lines[0].contains(TOKEN), "{}", lines[0]);
+        let line: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(
+            line["payload"]["d"]["content"],
+            format!("token is {}", REDACTED)
+        );
+        let _ = tokio::fs::remove_file(recorder.path()).await;
+    }
+
+    #[tokio::test]
+    async fn payloads_are_recorded_as_received() {
+        let recorder = recorder("raw").await;
+        let shard = recorder.for_shard(3, TOKEN);
+        // a field no model knows and a line break between tokens
+        let payload = "{\"op\":0,\"s\":7,\"t\":\"NEW_EVENT\",\n\"d\":{\"brand_new\":[1,2.5,null]}}";
+        shard.record(payload.as_bytes(), GatewayEncoding::Json);
+
+        let lines = read_lines(&recorder).await;
+        assert_eq!(lines.len(), 1);
+        let line: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(line["shard_id"], 3);
+        let sent: Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(line["payload"], sent);
+        let _ = tokio::fs::remove_file(recorder.path()).await;
+    }
+
+    #[tokio::test]
+    async fn replay_dispatches_recorded_events_in_order() {
+        let recorder = recorder("replay").await;
+        let shard = recorder.for_shard(1, TOKEN);
+        for payload in [
+            r#"{"op":10,"d":{"heartbeat_interval":41250}}"#,
+            r#"{"op":0,"s":1,"t":"READY","d":{"v":10}}"#,
+            r#"{"op":11}"#,
+            r#"{"op":0,"s":2,"t":"TYPING_START","d":{"channel_id":"1"}}"#,
+            r#"{"op":0,"s":3,"t":"SOMETHING_NEW","d":{}}"#,
+        ] {
+            shard.record(payload.as_bytes(), GatewayEncoding::Json);
+        }
+        recorder.flush().await;
+
+        let payloads = read_recording(recorder.path()).await.unwrap();
+        assert_eq!(payloads.len(), 5);
+        assert!(payloads.iter().all(|recorded| recorded.shard_id == 1));
+        assert_eq!(payloads[3].payload.s, Some(2));
+
+        let names = Arc::new(SyncMutex::new(Vec::new()));
+        let client = Client::new();
+        client
+            .event_dispatcher
+            .add_raw_handler(EventNames(names.clone()))
+            .await;
+        // only dispatches reach the handlers
+        assert_eq!(replay(recorder.path(), &client).await.unwrap(), 3);
+        assert_eq!(
+            *names.lock().unwrap(),
+            ["READY", "TYPING_START", "SOMETHING_NEW"]
+        );
+
+        assert_eq!(replay_payloads(payloads, &client).await, 3);
+        assert_eq!(names.lock().unwrap().len(), 6);
+        let _ = tokio::fs::remove_file(recorder.path()).await;
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::{
+    application::PartialApplication,
+    models::{guild::UnavailableGuild, user::User},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum GatewayReceiveEventName {
     /// Defines the Heartbeat interval
@@ -212,96 +217,26 @@ pub enum GatewayReceiveEventName {
 
     /// Unknown event
     /// This is a catch-all for any event that is not yet implemented
-    /// in the library.
+    /// in the library, it holds the event name sent by Discord.
     #[serde(untagged)]
-    Unknown,
+    Unknown(String),
 }
 
-impl PartialEq for GatewayReceiveEventName {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::HELLO, Self::HELLO) => true,
-            (Self::READY, Self::READY) => true,
-            (Self::RESUMED, Self::RESUMED) => true,
-            (Self::RECONNECT, Self::RECONNECT) => true,
-            (Self::INVALID_SESSION, Self::INVALID_SESSION) => true,
-            (
-                Self::APPLICATION_COMMAND_PERMISSIONS_UPDATE,
-                Self::APPLICATION_COMMAND_PERMISSIONS_UPDATE,
-            ) => true,
-            (Self::AUTO_MODERATION_RULE_CREATE, Self::AUTO_MODERATION_RULE_CREATE) => true,
-            (Self::AUTO_MODERATION_RULE_UPDATE, Self::AUTO_MODERATION_RULE_UPDATE) => true,
-            (Self::AUTO_MODERATION_RULE_DELETE, Self::AUTO_MODERATION_RULE_DELETE) => true,
-            (Self::AUTO_MODERATION_ACTION_EXECUTION, Self::AUTO_MODERATION_ACTION_EXECUTION) => {
-                true
-            }
-            (Self::CHANNEL_CREATE, Self::CHANNEL_CREATE) => true,
-            (Self::CHANNEL_UPDATE, Self::CHANNEL_UPDATE) => true,
-            (Self::CHANNEL_DELETE, Self::CHANNEL_DELETE) => true,
-            (Self::CHANNEL_PINS_UPDATE, Self::CHANNEL_PINS_UPDATE) => true,
-            (Self::THREAD_CREATE, Self::THREAD_CREATE) => true,
-            (Self::THREAD_UPDATE, Self::THREAD_UPDATE) => true,
-            (Self::THREAD_DELETE, Self::THREAD_DELETE) => true,
-            (Self::THREAD_LIST_SYNC, Self::THREAD_LIST_SYNC) => true,
-            (Self::THREAD_MEMBER_UPDATE, Self::THREAD_MEMBER_UPDATE) => true,
-            (Self::THREAD_MEMBERS_UPDATE, Self::THREAD_MEMBERS_UPDATE) => true,
-            (Self::ENTITLEMENT_CREATE, Self::ENTITLEMENT_CREATE) => true,
-            (Self::ENTITLEMENT_UPDATE, Self::ENTITLEMENT_UPDATE) => true,
-            (Self::ENTITLEMENT_DELETE, Self::ENTITLEMENT_DELETE) => true,
-            (Self::GUILD_CREATE, Self::GUILD_CREATE) => true,
-            (Self::GUILD_UPDATE, Self::GUILD_UPDATE) => true,
-            (Self::GUILD_DELETE, Self::GUILD_DELETE) => true,
-            (Self::GUILD_AUDIT_LOG_ENTRY_CREATE, Self::GUILD_AUDIT_LOG_ENTRY_CREATE) => true,
-            (Self::GUILD_BAN_ADD, Self::GUILD_BAN_ADD) => true,
-            (Self::GUILD_BAN_REMOVE, Self::GUILD_BAN_REMOVE) => true,
-            (Self::GUILD_EMOJIS_UPDATE, Self::GUILD_EMOJIS_UPDATE) => true,
-            (Self::GUILD_STICKERS_UPDATE, Self::GUILD_STICKERS_UPDATE) => true,
-            (Self::GUILD_INTEGRATIONS_UPDATE, Self::GUILD_INTEGRATIONS_UPDATE) => true,
-            (Self::GUILD_MEMBER_ADD, Self::GUILD_MEMBER_ADD) => true,
-            (Self::GUILD_MEMBER_REMOVE, Self::GUILD_MEMBER_REMOVE) => true,
-            (Self::GUILD_MEMBER_UPDATE, Self::GUILD_MEMBER_UPDATE) => true,
-            (Self::GUILD_MEMBERS_CHUNK, Self::GUILD_MEMBERS_CHUNK) => true,
-            (Self::GUILD_ROLE_CREATE, Self::GUILD_ROLE_CREATE) => true,
-            (Self::GUILD_ROLE_UPDATE, Self::GUILD_ROLE_UPDATE) => true,
-            (Self::GUILD_ROLE_DELETE, Self::GUILD_ROLE_DELETE) => true,
-            (Self::GUILD_SCHEDULED_EVENT_CREATE, Self::GUILD_SCHEDULED_EVENT_CREATE) => true,
-            (Self::GUILD_SCHEDULED_EVENT_UPDATE, Self::GUILD_SCHEDULED_EVENT_UPDATE) => true,
-            (Self::GUILD_SCHEDULED_EVENT_DELETE, Self::GUILD_SCHEDULED_EVENT_DELETE) => true,
-            (Self::GUILD_SCHEDULED_EVENT_USER_ADD, Self::GUILD_SCHEDULED_EVENT_USER_ADD) => true,
-            (Self::GUILD_SCHEDULED_EVENT_USER_REMOVE, Self::GUILD_SCHEDULED_EVENT_USER_REMOVE) => {
-                true
-            }
-            (Self::INTEGRATION_CREATE, Self::INTEGRATION_CREATE) => true,
-            (Self::INTEGRATION_UPDATE, Self::INTEGRATION_UPDATE) => true,
-            (Self::INTEGRATION_DELETE, Self::INTEGRATION_DELETE) => true,
-            (Self::INTERACTION_CREATE, Self::INTERACTION_CREATE) => true,
-            (Self::INVITE_CREATE, Self::INVITE_CREATE) => true,
-            (Self::INVITE_DELETE, Self::INVITE_DELETE) => true,
-            (Self::MESSAGE_CREATE, Self::MESSAGE_CREATE) => true,
-            (Self::MESSAGE_UPDATE, Self::MESSAGE_UPDATE) => true,
-            (Self::MESSAGE_DELETE, Self::MESSAGE_DELETE) => true,
-            (Self::MESSAGE_DELETE_BULK, Self::MESSAGE_DELETE_BULK) => true,
-            (Self::MESSAGE_REACTION_ADD, Self::MESSAGE_REACTION_ADD) => true,
-            (Self::MESSAGE_REACTION_REMOVE, Self::MESSAGE_REACTION_REMOVE) => true,
-            (Self::MESSAGE_REACTION_REMOVE_ALL, Self::MESSAGE_REACTION_REMOVE_ALL) => true,
-            (Self::MESSAGE_REACTION_REMOVE_EMOJI, Self::MESSAGE_REACTION_REMOVE_EMOJI) => true,
-            (Self::PRESENCE_UPDATE, Self::PRESENCE_UPDATE) => true,
-            (Self::STAGE_INSTANCE_CREATE, Self::STAGE_INSTANCE_CREATE) => true,
-            (Self::STAGE_INSTANCE_UPDATE, Self::STAGE_INSTANCE_UPDATE) => true,
-            (Self::STAGE_INSTANCE_DELETE, Self::STAGE_INSTANCE_DELETE) => true,
-            (Self::TYPING_START, Self::TYPING_START) => true,
-            (Self::USER_UPDATE, Self::USER_UPDATE) => true,
-            (Self::VOICE_STATE_UPDATE, Self::VOICE_STATE_UPDATE) => true,
-            (Self::VOICE_SERVER_UPDATE, Self::VOICE_SERVER_UPDATE) => true,
-            (Self::WEBHOOKS_UPDATE, Self::WEBHOOKS_UPDATE) => true,
-            (Self::MESSAGE_POLL_VOTE_ADD, Self::MESSAGE_POLL_VOTE_ADD) => true,
-            (Self::MESSAGE_POLL_VOTE_REMOVE, Self::MESSAGE_POLL_VOTE_REMOVE) => true,
-            _ => false,
+impl GatewayReceiveEventName {
+    /// Name of the event as sent by the gateway, e.g. `MESSAGE_CREATE`
+    pub fn as_wire_name(&self) -> String {
+        match self {
+            GatewayReceiveEventName::Unknown(name) => name.clone(),
+            known => match serde_json::to_value(known) {
+                Ok(serde_json::Value::String(name)) => name,
+                _ => format!("{:?}", known),
+            },
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[repr(i8)]
 pub enum DiscordOpCode {
     Dispatch = 0,
     Heartbeat = 1,
@@ -314,32 +249,10 @@ pub enum DiscordOpCode {
     InvalidSession = 9,
     Hello = 10,
     HeartbeatAck = 11,
+    /// Opcode not known by the library
+    Unknown(i8),
 }
 
-impl PartialEq for DiscordOpCode {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Self::Dispatch, Self::Dispatch) => true,
-            (Self::Heartbeat, Self::Heartbeat) => true,
-            (Self::Identify, Self::Identify) => true,
-            (Self::PresenceUpdate, Self::PresenceUpdate) => true,
-            (Self::VoiceStateUpdate, Self::VoiceStateUpdate) => true,
-            (Self::Resume, Self::Resume) => true,
-            (Self::Reconnect, Self::Reconnect) => true,
-            (Self::RequestGuildMembers, Self::RequestGuildMembers) => true,
-            (Self::InvalidSession, Self::InvalidSession) => true,
-            (Self::Hello, Self::Hello) => true,
-            (Self::HeartbeatAck, Self::HeartbeatAck) => true,
-            _ => false,
-        }
-    }
-}
-
-impl Clone for DiscordOpCode {
-    fn clone(&self) -> Self {
-        *self
-    }
-}
 // impl for change i8 to DiscordOpCode
 impl From<i8> for DiscordOpCode {
     fn from(value: i8) -> Self {
@@ -355,15 +268,145 @@ impl From<i8> for DiscordOpCode {
             9 => DiscordOpCode::InvalidSession,
             10 => DiscordOpCode::Hello,
             11 => DiscordOpCode::HeartbeatAck,
-            _ => panic!("Invalid OpCode"),
+            other => DiscordOpCode::Unknown(other),
+        }
+    }
+}
+
+impl From<DiscordOpCode> for i8 {
+    fn from(value: DiscordOpCode) -> Self {
+        match value {
+            DiscordOpCode::Dispatch => 0,
+            DiscordOpCode::Heartbeat => 1,
+            DiscordOpCode::Identify => 2,
+            DiscordOpCode::PresenceUpdate => 3,
+            DiscordOpCode::VoiceStateUpdate => 4,
+            DiscordOpCode::Resume => 6,
+            DiscordOpCode::Reconnect => 7,
+            DiscordOpCode::RequestGuildMembers => 8,
+            DiscordOpCode::InvalidSession => 9,
+            DiscordOpCode::Hello => 10,
+            DiscordOpCode::HeartbeatAck => 11,
+            DiscordOpCode::Unknown(other) => other,
         }
     }
 }
 
+/// Close codes the gateway may send when it closes the connection.
+/// <https://discord.com/developers/docs/topics/opcodes-and-status-codes#gateway-gateway-close-event-codes>
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GatewayCloseCode {
+    /// Normal closure, the session is invalidated
+    Normal,
+    /// The endpoint is going away, the session is invalidated
+    GoingAway,
+    /// We're not sure what went wrong. Try reconnecting?
+    UnknownError,
+    /// You sent an invalid Gateway opcode or an invalid payload for an opcode
+    UnknownOpcode,
+    /// You sent an invalid payload
+    DecodeError,
+    /// You sent us a payload prior to identifying
+    NotAuthenticated,
+    /// The account token sent with your identify payload is incorrect
+    AuthenticationFailed,
+    /// You sent more than one identify payload
+    AlreadyAuthenticated,
+    /// The sequence sent when resuming the session was invalid
+    InvalidSeq,
+    /// You're sending payloads to us too quickly
+    RateLimited,
+    /// Your session timed out
+    SessionTimedOut,
+    /// You sent us an invalid shard when identifying
+    InvalidShard,
+    /// The session would have handled too many guilds, you are required to shard your connection
+    ShardingRequired,
+    /// You sent an invalid version for the gateway
+    InvalidApiVersion,
+    /// You sent an invalid intent for a Gateway Intent
+    InvalidIntents,
+    /// You sent a disallowed intent, one you have not enabled or are not approved for
+    DisallowedIntents,
+    /// Any other close code
+    Other(u16),
+}
+
+impl GatewayCloseCode {
+    /// Whether reconnecting can never succeed with the same configuration
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Self::AuthenticationFailed
+                | Self::InvalidShard
+                | Self::ShardingRequired
+                | Self::InvalidApiVersion
+                | Self::InvalidIntents
+                | Self::DisallowedIntents
+        )
+    }
+
+    /// Whether the session is still valid and can be resumed after reconnecting
+    pub fn can_resume(&self) -> bool {
+        !self.is_fatal()
+            && !matches!(
+                self,
+                Self::Normal | Self::GoingAway | Self::InvalidSeq | Self::SessionTimedOut
+            )
+    }
+
+    /// The numeric close code
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::Normal => 1000,
+            Self::GoingAway => 1001,
+            Self::UnknownError => 4000,
+            Self::UnknownOpcode => 4001,
+            Self::DecodeError => 4002,
+            Self::NotAuthenticated => 4003,
+            Self::AuthenticationFailed => 4004,
+            Self::AlreadyAuthenticated => 4005,
+            Self::InvalidSeq => 4007,
+            Self::RateLimited => 4008,
+            Self::SessionTimedOut => 4009,
+            Self::InvalidShard => 4010,
+            Self::ShardingRequired => 4011,
+            Self::InvalidApiVersion => 4012,
+            Self::InvalidIntents => 4013,
+            Self::DisallowedIntents => 4014,
+            Self::Other(code) => *code,
+        }
+    }
+}
+
+impl From<u16> for GatewayCloseCode {
+    fn from(value: u16) -> Self {
+        match value {
+            1000 => Self::Normal,
+            1001 => Self::GoingAway,
+            4000 => Self::UnknownError,
+            4001 => Self::UnknownOpcode,
+            4002 => Self::DecodeError,
+            4003 => Self::NotAuthenticated,
+            4004 => Self::AuthenticationFailed,
+            4005 => Self::AlreadyAuthenticated,
+            4007 => Self::InvalidSeq,
+            4008 => Self::RateLimited,
+            4009 => Self::SessionTimedOut,
+            4010 => Self::InvalidShard,
+            4011 => Self::ShardingRequired,
+            4012 => Self::InvalidApiVersion,
+            4013 => Self::InvalidIntents,
+            4014 => Self::DisallowedIntents,
+            other => Self::Other(other),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DiscordReceiveEvent {
-    pub t: GatewayReceiveEventName,
+    /// Event name, only set for dispatch (op 0) payloads
+    pub t: Option<GatewayReceiveEventName>,
     pub s: Option<i32>,
     pub op: i8,
     pub d: Option<serde_json::Value>,
@@ -371,8 +414,68 @@ pub struct DiscordReceiveEvent {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReceiveEvent {
-    pub t: GatewayReceiveEventName,
+    /// Event name, only set for dispatch (op 0) payloads
+    pub t: Option<GatewayReceiveEventName>,
     pub s: Option<i32>,
     pub op: DiscordOpCode,
     pub d: Option<serde_json::Value>,
 }
+
+// change discord event to rustycord event
+impl From<DiscordReceiveEvent> for ReceiveEvent {
+    fn from(event: DiscordReceiveEvent) -> Self {
+        Self {
+            t: event.t,
+            s: event.s,
+            op: DiscordOpCode::from(event.op),
+            d: event.d,
+        }
+    }
+}
+
+// change rustycord event back to the payload sent by discord
+impl From<ReceiveEvent> for DiscordReceiveEvent {
+    fn from(event: ReceiveEvent) -> Self {
+        Self {
+            t: event.t,
+            s: event.s,
+            op: i8::from(event.op),
+            d: event.d,
+        }
+    }
+}
+
+/// Sent on connection to the websocket. Defines the heartbeat interval that an app should heartbeat to.
+/// <https://discord.com/developers/docs/topics/gateway-events#hello>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Hello {
+    /// Interval (in milliseconds) an app should heartbeat with
+    pub heartbeat_interval: u64,
+}
+
+/// The ready event is dispatched when a client has completed the initial handshake with the gateway.
+/// <https://discord.com/developers/docs/topics/gateway-events#ready>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ready {
+    /// API version
+    pub v: u8,
+
+    /// Information about the user including email
+    pub user: User,
+
+    /// Guilds the user is in, they are sent as GUILD_CREATE once available
+    #[serde(default)]
+    pub guilds: Vec<UnavailableGuild>,
+
+    /// Used for resuming connections
+    pub session_id: String,
+
+    /// Gateway URL for resuming connections
+    pub resume_gateway_url: String,
+
+    /// Shard information associated with this session, if sent when identifying
+    pub shard: Option<Vec<i32>>,
+
+    /// Contains id and flags
+    pub application: Option<PartialApplication>,
+}
@@ -1,31 +1,158 @@
 // gateway/shard_manager.rs
 
-use futures::future::join_all;
+use std::{sync::Arc, time::Duration};
+
+use tokio::{sync::Semaphore, task::JoinSet};
 
 use crate::client::Client;
-use crate::gateway::gateway::Manager;
+use crate::error::Error;
+use crate::gateway::gateway::{GatewayConfig, Manager, DEFAULT_GATEWAY_URL};
+use crate::gateway::identify_scheduler::IdentifyScheduler;
+use crate::gateway::messenger::ShardMessenger;
+use crate::handlers::dispatch_queue::DispatchQueue;
+use crate::http::{GatewayResponse, SessionStartLimit};
 
 use super::gateway::PresenceUpdate;
 
+/// How long the other shards get to close their connections after a shard
+/// stopped with a fatal error
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
 pub struct ShardManager {
+    /// Total number of shards of the bot, across all clusters
     pub total_shards: usize,
+    /// Shards run by this process, `0..total_shards` unless a cluster was configured
+    pub shard_ids: Vec<usize>,
     pub token: String,
     pub client: Client,
+    /// Gateway url the shards connect to
+    pub gateway_url: String,
+    /// Orders IDENTIFY calls of all shards
+    pub identify_scheduler: Arc<IdentifyScheduler>,
 }
 
 impl ShardManager {
     pub fn new(token: String, total_shards: usize, client: Client) -> Self {
+        let gateway_url = client
+            .http
+            .endpoints()
+            .gateway_url
+            .clone()
+            .unwrap_or_else(|| DEFAULT_GATEWAY_URL.to_string());
         Self {
             total_shards,
+            shard_ids: (0..total_shards).collect(),
             token,
             client,
+            gateway_url,
+            identify_scheduler: Arc::new(IdentifyScheduler::default()),
+        }
+    }
+
+    /// Shard manager using the shard count and session start limit
+    /// recommended by `GET /gateway/bot`
+    pub fn from_gateway(token: String, gateway: &GatewayResponse, client: Client) -> Self {
+        let mut manager = Self::new(token, gateway.shards.max(1), client);
+        manager.set_gateway(gateway);
+        manager
+    }
+
+    /// Use the gateway url and session start limit returned by `GET /gateway/bot`.
+    ///
+    /// A gateway url configured in the client's [`Endpoints`](crate::http::Endpoints)
+    /// takes precedence over the returned one.
+    pub fn set_gateway(&mut self, gateway: &GatewayResponse) {
+        if self.client.http.endpoints().gateway_url.is_none() {
+            self.gateway_url = gateway.url.clone();
+        }
+        if let Some(limit) = &gateway.session_start_limit {
+            self.set_session_start_limit(limit);
+        }
+    }
+
+    /// Only run the given shards in this process.
+    ///
+    /// Used to split a bot into clusters: every process uses the same
+    /// `total_shards` and runs its own part of the shard ids, e.g. process A
+    /// runs `0..8` and process B runs `8..16`.
+    pub fn set_shard_ids<I>(&mut self, shard_ids: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut shard_ids: Vec<usize> = shard_ids.into_iter().collect();
+        shard_ids.sort_unstable();
+        shard_ids.dedup();
+        if let Some(invalid) = shard_ids.iter().find(|id| **id >= self.total_shards) {
+            return Err(Error::Config(format!(
+                "Shard id {} is out of range for {} shards",
+                invalid, self.total_shards
+            )));
+        }
+        if shard_ids.is_empty() {
+            return Err(Error::Config(
+                "A cluster needs at least one shard".to_string(),
+            ));
+        }
+        log::info!("🧩 Running shards {:?} of {}", shard_ids, self.total_shards);
+        self.shard_ids = shard_ids;
+        Ok(())
+    }
+
+    /// Identify the shards of this manager according to the session start
+    /// limit returned by `GET /gateway/bot`.
+    ///
+    /// Buckets are picked by `shard_id % max_concurrency`, so the shards of
+    /// different clusters share them. A scheduler only coordinates the shards
+    /// it is given to: clusters with their own scheduler can exceed a
+    /// bucket's limit whenever they identify within 5 seconds of each other,
+    /// and each tracks the session start budget on its own. Clusters running
+    /// in one process share a scheduler through
+    /// [`set_identify_scheduler`](Self::set_identify_scheduler), clusters in
+    /// separate processes have to coordinate their identifies themselves,
+    /// e.g. by starting one after the other has identified all its shards.
+    pub fn set_session_start_limit(&mut self, limit: &SessionStartLimit) {
+        if (limit.remaining as usize) < self.shard_ids.len() {
+            log::warn!(
+                "⚠️ Only {} session starts left for {} shards, some shards will wait for the reset",
+                limit.remaining,
+                self.shard_ids.len()
+            );
         }
+        self.identify_scheduler = Arc::new(IdentifyScheduler::from_session_start_limit(limit));
     }
 
-    pub async fn start(&self, intents: Option<i32>, presense: Option<PresenceUpdate>) {
-        let mut shard_tasks = Vec::new();
+    /// Identify the shards through `scheduler` instead of an own one, e.g.
+    /// the scheduler of another cluster of the bot running in this process.
+    ///
+    /// The clusters then share the rate limit buckets and session start
+    /// budget of the scheduler. Call this after
+    /// [`set_gateway`](Self::set_gateway), which replaces the scheduler.
+    pub fn set_identify_scheduler(&mut self, scheduler: Arc<IdentifyScheduler>) {
+        self.identify_scheduler = scheduler;
+    }
+
+    /// Run all shards until they stop.
+    ///
+    /// Every shard runs on its own task. Shards connect and reconnect on
+    /// their own, retrying network errors with backoff, so this only returns
+    /// once every shard was shut down through its [`ShardMessenger`], or as
+    /// soon as one shard was closed with a close code reconnecting cannot
+    /// fix, such as an invalid token, invalid intents or required sharding.
+    /// The other shards are shut down in that case and the error of the
+    /// failed shard is returned.
+    pub async fn start(
+        &self,
+        intents: Option<i32>,
+        presense: Option<PresenceUpdate>,
+    ) -> Result<(), Error> {
+        let mut shard_tasks = JoinSet::new();
+        let mut messengers = self.client.shard_messengers.write().await;
+        // handler tasks of all shards share the concurrency limit
+        let dispatch_limit = Arc::new(Semaphore::new(
+            self.client.dispatch_config.max_concurrency.max(1),
+        ));
 
-        for shard_id in 0..self.total_shards {
+        for &shard_id in &self.shard_ids {
             let token = self.token.clone();
             let intents: i32 = match intents {
                 Some(int) => int,
@@ -34,42 +161,84 @@ impl ShardManager {
                     1
                 }
             };
-            let intents = intents;
-            let compress = false;
-            let large_threshold = Some(50);
-            let shard = Some(vec![shard_id as i32, self.total_shards as i32]);
             let presence = presense.clone(); // Initialize with your desired presence if any
             let client = self.client.clone();
+            let config = GatewayConfig {
+                url: self.gateway_url.clone(),
+                version: self.client.http.endpoints().api_version,
+                compress: self.client.compress,
+                encoding: self.client.encoding,
+            };
+            let scheduler = self.identify_scheduler.clone();
+            let dispatch_queue = DispatchQueue::start(
+                self.client.clone(),
+                &self.client.dispatch_config,
+                dispatch_limit.clone(),
+            );
+            let (messenger, commands) = ShardMessenger::channel(shard_id);
+            messengers.insert(shard_id, messenger);
 
-            // Spawn each shard as a separate task
-            let shard_task = async move {
-                let mut shard_manager = Manager::new(token.clone(), intents, shard_id, self.total_shards).await;
-                shard_manager.set_client(client);
-                shard_manager.send_identify(
+            let total_shards = self.total_shards;
+            shard_tasks.spawn(async move {
+                // the shard waits for its identify slot before connecting
+                let mut shard_manager = Manager::connect(
                     token,
                     intents,
-                    compress,
-                    large_threshold,
-                    shard,
-                    presence,
-                ).await;
-
-                loop {
-                    // Handle heartbeats
-                    shard_manager.handle_heartbeat().await;
-
-                    // Handle incoming events
-                    shard_manager.handle_event().await;
-
-                    // Sleep for a short duration to prevent tight looping
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                    shard_id,
+                    total_shards,
+                    config,
+                    Some(scheduler),
+                )
+                .await;
+                shard_manager.set_client(client);
+                shard_manager.set_commands(commands);
+                shard_manager.set_dispatch_queue(dispatch_queue);
+                shard_manager.large_threshold = Some(50);
+                // IDENTIFY is sent once the gateway says HELLO
+                shard_manager.set_presence(presence);
+                let result = shard_manager.run().await;
+                if let Err(err) = &result {
+                    log::error!("❌ Shard {} stopped: {}", shard_id, err);
                 }
-            };
+                result.map_err(Error::from)
+            });
+        }
 
-            shard_tasks.push(shard_task);
+        drop(messengers);
+
+        while let Some(joined) = shard_tasks.join_next().await {
+            let result = joined.unwrap_or_else(|err| {
+                // a panicking shard is gone, the others keep running
+                log::error!("❌ Shard task ended unexpectedly: {}", err);
+                Ok(())
+            });
+            if let Err(err) = result {
+                self.shutdown_after_error(&mut shard_tasks).await;
+                return Err(err);
+            }
         }
+        Ok(())
+    }
 
-        // Await all shard tasks
-        join_all(shard_tasks).await;
+    /// Shut down the shards still running after one of them failed, giving
+    /// them [`SHUTDOWN_GRACE_PERIOD`] to close their connections
+    async fn shutdown_after_error(&self, shard_tasks: &mut JoinSet<Result<(), Error>>) {
+        if shard_tasks.is_empty() {
+            return;
+        }
+        log::warn!("🛑 Shutting down {} remaining shard(s)", shard_tasks.len());
+        for &shard_id in &self.shard_ids {
+            if let Some(messenger) = self.client.shard_messenger(shard_id).await {
+                messenger.shutdown();
+            }
+        }
+        let drain = async { while shard_tasks.join_next().await.is_some() {} };
+        if tokio::time::timeout(SHUTDOWN_GRACE_PERIOD, drain)
+            .await
+            .is_err()
+        {
+            log::warn!("⚠️ Shards did not stop in time, dropping their connections");
+            shard_tasks.shutdown().await;
+        }
     }
 }
@@ -0,0 +1,312 @@
+// gateway/voice_state.rs
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::{gateway::event::VoiceServerUpdate, models::voice::VoiceState};
+
+/// Sent when a client wants to join, move, or disconnect from a voice channel.
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#update-voice-state>
+#[derive(Debug, Serialize, Clone)]
+pub struct UpdateVoiceState {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// ID of the voice channel client wants to join, `None` if disconnecting
+    pub channel_id: Option<String>,
+
+    /// Whether the client is muted
+    pub self_mute: bool,
+
+    /// Whether the client deafened
+    pub self_deaf: bool,
+}
+
+impl UpdateVoiceState {
+    /// Join or move to a voice channel
+    pub fn join(
+        guild_id: impl Into<String>,
+        channel_id: impl Into<String>,
+        self_mute: bool,
+        self_deaf: bool,
+    ) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            channel_id: Some(channel_id.into()),
+            self_mute,
+            self_deaf,
+        }
+    }
+
+    /// Leave the voice channel of the guild
+    pub fn leave(guild_id: impl Into<String>) -> Self {
+        Self {
+            guild_id: guild_id.into(),
+            channel_id: None,
+            self_mute: false,
+            self_deaf: false,
+        }
+    }
+}
+
+/// Everything a voice client needs to connect to the voice server of a guild.
+///
+/// Collected from the VOICE_STATE_UPDATE of the bot and the
+/// VOICE_SERVER_UPDATE sent after joining a voice channel.
+///
+/// More: <https://discord.com/developers/docs/topics/voice-connections#retrieving-voice-server-information>
+#[derive(Debug, Clone)]
+pub struct VoiceConnectionInfo {
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// ID of the voice channel that was joined
+    pub channel_id: String,
+
+    /// ID of the bot user
+    pub user_id: String,
+
+    /// Session id from the VOICE_STATE_UPDATE
+    pub session_id: String,
+
+    /// Voice server host from the VOICE_SERVER_UPDATE
+    pub endpoint: String,
+
+    /// Voice connection token from the VOICE_SERVER_UPDATE
+    pub token: String,
+}
+
+/// A voice channel join waiting for its state and server updates
+pub(crate) struct PendingVoiceConnection {
+    state: Option<VoiceState>,
+    server: Option<VoiceServerUpdate>,
+    /// Connection the bot already has in the guild, Discord may not send a
+    /// VOICE_SERVER_UPDATE when moving to another channel of the guild
+    current: Option<VoiceConnectionInfo>,
+    sender: oneshot::Sender<VoiceConnectionInfo>,
+}
+
+impl PendingVoiceConnection {
+    pub(crate) fn new(
+        sender: oneshot::Sender<VoiceConnectionInfo>,
+        current: Option<VoiceConnectionInfo>,
+    ) -> Self {
+        Self {
+            state: None,
+            server: None,
+            current,
+            sender,
+        }
+    }
+
+    /// Store the voice state of the bot
+    pub(crate) fn set_state(&mut self, state: VoiceState) {
+        self.state = Some(state);
+    }
+
+    /// Store the voice server, updates without endpoint are ignored as the
+    /// server was deallocated and a new update follows
+    pub(crate) fn set_server(&mut self, server: VoiceServerUpdate) {
+        if server.endpoint.is_some() {
+            self.server = Some(server);
+        }
+    }
+
+    /// Whether both updates were received, or the state of a move within the
+    /// session of the current connection
+    pub(crate) fn is_complete(&self) -> bool {
+        let Some(state) = self
+            .state
+            .as_ref()
+            .filter(|state| state.channel_id.is_some())
+        else {
+            return false;
+        };
+        self.server.is_some()
+            || self
+                .current
+                .as_ref()
+                .is_some_and(|current| current.session_id == state.session_id)
+    }
+
+    /// Whether the requester stopped waiting for the connection info
+    pub(crate) fn is_abandoned(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Hand the connection info to the requester once it is complete, a move
+    /// without server update reuses the endpoint of the current connection.
+    ///
+    /// Returns the info of the new connection.
+    pub(crate) fn finish(self) -> Option<VoiceConnectionInfo> {
+        if !self.is_complete() {
+            return None;
+        }
+        let state = self.state?;
+        let (guild_id, endpoint, token) = match self.server {
+            Some(server) => (server.guild_id, server.endpoint?, server.token),
+            None => {
+                let current = self.current?;
+                (current.guild_id, current.endpoint, current.token)
+            }
+        };
+        let info = VoiceConnectionInfo {
+            guild_id,
+            channel_id: state.channel_id?,
+            user_id: state.user_id,
+            session_id: state.session_id,
+            endpoint,
+            token,
+        };
+        // the requester may have stopped waiting
+        let _ = self.sender.send(info.clone());
+        Some(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn state(channel_id: Option<&str>) -> VoiceState {
+        serde_json::from_value(json!({
+            "guild_id": "1000",
+            "channel_id": channel_id,
+            "user_id": "2000",
+            "session_id": "voice-session",
+            "deaf": false,
+            "mute": false,
+            "self_deaf": true,
+            "self_mute": false,
+            "self_video": false,
+            "suppress": false,
+            "request_to_speak_timestamp": null,
+        }))
+        .unwrap()
+    }
+
+    fn server(endpoint: Option<&str>) -> VoiceServerUpdate {
+        serde_json::from_value(json!({
+            "token": "voice-token",
+            "guild_id": "1000",
+            "endpoint": endpoint,
+        }))
+        .unwrap()
+    }
+
+    fn pending() -> (
+        PendingVoiceConnection,
+        oneshot::Receiver<VoiceConnectionInfo>,
+    ) {
+        let (sender, receiver) = oneshot::channel();
+        (PendingVoiceConnection::new(sender, None), receiver)
+    }
+
+    /// Connection of the bot to channel 2999 of the guild
+    fn current(session_id: &str) -> VoiceConnectionInfo {
+        VoiceConnectionInfo {
+            guild_id: "1000".to_string(),
+            channel_id: "2999".to_string(),
+            user_id: "2000".to_string(),
+            session_id: session_id.to_string(),
+            endpoint: "us-east1234.discord.media:443".to_string(),
+            token: "voice-token".to_string(),
+        }
+    }
+
+    fn assert_info(info: VoiceConnectionInfo) {
+        assert_eq!(info.guild_id, "1000");
+        assert_eq!(info.channel_id, "3000");
+        assert_eq!(info.user_id, "2000");
+        assert_eq!(info.session_id, "voice-session");
+        assert_eq!(info.endpoint, "us-east1234.discord.media:443");
+        assert_eq!(info.token, "voice-token");
+    }
+
+    #[test]
+    fn completes_with_state_first() {
+        let (mut pending, mut receiver) = pending();
+        pending.set_state(state(Some("3000")));
+        assert!(!pending.is_complete());
+        pending.set_server(server(Some("us-east1234.discord.media:443")));
+        assert!(pending.is_complete());
+        assert!(pending.finish().is_some());
+        assert_info(receiver.try_recv().unwrap());
+    }
+
+    #[test]
+    fn completes_with_server_first() {
+        let (mut pending, mut receiver) = pending();
+        pending.set_server(server(Some("us-east1234.discord.media:443")));
+        assert!(!pending.is_complete());
+        pending.set_state(state(Some("3000")));
+        assert!(pending.is_complete());
+        assert!(pending.finish().is_some());
+        assert_info(receiver.try_recv().unwrap());
+    }
+
+    #[test]
+    fn server_without_endpoint_waits_for_the_next_update() {
+        let (mut pending, mut receiver) = pending();
+        pending.set_state(state(Some("3000")));
+        pending.set_server(server(None));
+        assert!(!pending.is_complete());
+
+        pending.set_server(server(Some("us-east1234.discord.media:443")));
+        assert!(pending.is_complete());
+        assert!(pending.finish().is_some());
+        assert_info(receiver.try_recv().unwrap());
+    }
+
+    #[test]
+    fn state_without_channel_is_not_complete() {
+        let (mut pending, _receiver) = pending();
+        pending.set_server(server(Some("us-east1234.discord.media:443")));
+        pending.set_state(state(None));
+        assert!(!pending.is_complete());
+    }
+
+    #[test]
+    fn abandoned_once_the_requester_stopped_waiting() {
+        let (pending, receiver) = pending();
+        assert!(!pending.is_abandoned());
+        drop(receiver);
+        assert!(pending.is_abandoned());
+    }
+
+    #[test]
+    fn move_within_the_guild_completes_without_server() {
+        let (sender, mut receiver) = oneshot::channel();
+        let mut pending = PendingVoiceConnection::new(sender, Some(current("voice-session")));
+        pending.set_state(state(Some("3000")));
+        assert!(pending.is_complete());
+
+        let info = pending.finish().unwrap();
+        assert_eq!(info.channel_id, "3000");
+        assert_info(receiver.try_recv().unwrap());
+    }
+
+    #[test]
+    fn move_uses_a_new_server_when_sent() {
+        let (sender, mut receiver) = oneshot::channel();
+        let mut current = current("voice-session");
+        current.endpoint = "old.discord.media:443".to_string();
+        let mut pending = PendingVoiceConnection::new(sender, Some(current));
+        pending.set_server(server(Some("us-east1234.discord.media:443")));
+        pending.set_state(state(Some("3000")));
+        assert!(pending.finish().is_some());
+        assert_info(receiver.try_recv().unwrap());
+    }
+
+    #[test]
+    fn new_session_waits_for_the_server() {
+        let (sender, _receiver) = oneshot::channel();
+        let mut pending = PendingVoiceConnection::new(sender, Some(current("old-session")));
+        pending.set_state(state(Some("3000")));
+        assert!(!pending.is_complete());
+        assert!(pending.finish().is_none());
+    }
+}
@@ -0,0 +1,431 @@
+use futures::FutureExt;
+use std::collections::{HashMap, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use tokio::sync::{
+    mpsc::{self, error::TrySendError, OwnedPermit},
+    Mutex, OwnedSemaphorePermit, Semaphore,
+};
+
+use crate::client::Client;
+use crate::gateway::response::{GatewayReceiveEventName, ReceiveEvent};
+
+/// Limits of the event dispatch of a client
+#[derive(Debug, Clone)]
+pub struct DispatchConfig {
+    /// Events a shard buffers for its handlers before it stops reading from
+    /// the gateway. As many events may wait behind the running event of
+    /// their channel, counted across all channels of the shard.
+    pub queue_capacity: usize,
+
+    /// Events handled at the same time across all shards of the client,
+    /// `1` handles every event after the previous one
+    pub max_concurrency: usize,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 256,
+            max_concurrency: 16,
+        }
+    }
+}
+
+/// Events of a channel waiting for the previous event of the channel, by
+/// channel id, each holding a slot of the shard's backlog
+type ChannelQueues = Arc<Mutex<HashMap<String, VecDeque<(ReceiveEvent, OwnedSemaphorePermit)>>>>;
+
+/// Bounded queue between a shard and the event handlers.
+///
+/// Events are handled on spawned tasks, so a slow handler neither delays
+/// heartbeats nor events of other channels. Events of the same channel are
+/// handled one after another in the order they were received, events
+/// without channel have no ordering guarantee.
+pub struct DispatchQueue {
+    sender: mpsc::Sender<ReceiveEvent>,
+}
+
+impl DispatchQueue {
+    /// Start the dispatch task of a shard
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - the client whose handlers receive the events
+    /// * `config` - queue capacity of the shard
+    /// * `limit` - permits shared by all shards, one per event being handled
+    pub fn start(client: Client, config: &DispatchConfig, limit: Arc<Semaphore>) -> Self {
+        let capacity = config.queue_capacity.max(1);
+        let (sender, receiver) = mpsc::channel(capacity);
+        tokio::spawn(run(client, receiver, capacity, limit));
+        Self { sender }
+    }
+
+    /// Queue an event, the event is handed back when the queue is full
+    pub fn try_push(&self, event: ReceiveEvent) -> Result<(), ReceiveEvent> {
+        match self.sender.try_send(event) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(event)) => Err(event),
+            Err(TrySendError::Closed(event)) => {
+                log::error!("❌ Dispatch task stopped, dropping {:?}", event.t);
+                Ok(())
+            }
+        }
+    }
+
+    /// Wait until the queue has room for an event
+    pub async fn reserve(&self) -> Option<OwnedPermit<ReceiveEvent>> {
+        self.sender.clone().reserve_owned().await.ok()
+    }
+}
+
+/// Hand queued events to handler tasks until the shard dropped its queue
+async fn run(
+    client: Client,
+    mut receiver: mpsc::Receiver<ReceiveEvent>,
+    capacity: usize,
+    limit: Arc<Semaphore>,
+) {
+    let channels: ChannelQueues = Arc::new(Mutex::new(HashMap::new()));
+    // events waiting behind their channel, bounded for the whole shard so a
+    // busy channel cannot stall the others before the shard is out of room
+    let backlog = Arc::new(Semaphore::new(capacity));
+
+    while let Some(event) = receiver.recv().await {
+        let channel_id = channel_id(&event);
+        let event = match &channel_id {
+            Some(channel_id) => {
+                match queue_behind_channel(&channels, &backlog, channel_id, event).await {
+                    Some(event) => event,
+                    None => continue,
+                }
+            }
+            None => event,
+        };
+        spawn_handler(&client, &limit, &channels, channel_id, event).await;
+    }
+}
+
+/// Queue an event behind the running event of its channel.
+///
+/// Returns the event when no event of the channel is running, the channel
+/// is then marked as running. Waits while the backlog of the shard is full.
+async fn queue_behind_channel(
+    channels: &ChannelQueues,
+    backlog: &Arc<Semaphore>,
+    channel_id: &str,
+    event: ReceiveEvent,
+) -> Option<ReceiveEvent> {
+    loop {
+        {
+            let mut channels = channels.lock().await;
+            let Some(queue) = channels.get_mut(channel_id) else {
+                channels.insert(channel_id.to_string(), VecDeque::new());
+                return Some(event);
+            };
+            if let Ok(slot) = backlog.clone().try_acquire_owned() {
+                queue.push_back((event, slot));
+                return None;
+            }
+        }
+        // only this task takes slots, wait until a channel task freed one
+        let Ok(slot) = backlog.acquire().await else {
+            return None;
+        };
+        drop(slot);
+    }
+}
+
+/// Handle an event on a new task once a permit is free.
+///
+/// A task of a channel keeps handling the events queued behind it until the
+/// channel queue is empty, taking a new permit for every event.
+async fn spawn_handler(
+    client: &Client,
+    limit: &Arc<Semaphore>,
+    channels: &ChannelQueues,
+    channel_id: Option<String>,
+    event: ReceiveEvent,
+) {
+    let Ok(permit) = limit.clone().acquire_owned().await else {
+        return;
+    };
+    let client = client.clone();
+    let channels = channels.clone();
+    let limit = limit.clone();
+    tokio::spawn(async move {
+        let mut event = event;
+        let mut permit = permit;
+        loop {
+            dispatch(&client, &event).await;
+            let Some(channel_id) = &channel_id else {
+                break;
+            };
+            let mut queues = channels.lock().await;
+            let next = queues.get_mut(channel_id).and_then(VecDeque::pop_front);
+            match next {
+                // the event leaves the backlog once its handling starts
+                Some((next, _slot)) => event = next,
+                None => {
+                    queues.remove(channel_id);
+                    break;
+                }
+            }
+            drop(queues);
+            // queue for a new permit so a busy channel does not starve the others
+            drop(permit);
+            let Ok(next_permit) = limit.clone().acquire_owned().await else {
+                return;
+            };
+            permit = next_permit;
+        }
+        drop(permit);
+    });
+}
+
+async fn dispatch(client: &Client, event: &ReceiveEvent) {
+    // a panicking handler must not leave its channel marked as running
+    match AssertUnwindSafe(client.event_dispatcher.dispatch_event(event, client))
+        .catch_unwind()
+        .await
+    {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::error!("Error dispatching event: {:?}", e),
+        Err(_) => log::error!("❌ Handler panicked while handling {:?}", event.t),
+    }
+}
+
+/// Channel an event belongs to, used to keep the events of a channel in order
+///
+/// Channel and thread events carry the channel in `id`, the other events of
+/// a channel in `channel_id`.
+fn channel_id(event: &ReceiveEvent) -> Option<String> {
+    let field = match event.t.as_ref()? {
+        GatewayReceiveEventName::CHANNEL_CREATE
+        | GatewayReceiveEventName::CHANNEL_UPDATE
+        | GatewayReceiveEventName::CHANNEL_DELETE
+        | GatewayReceiveEventName::THREAD_CREATE
+        | GatewayReceiveEventName::THREAD_UPDATE
+        | GatewayReceiveEventName::THREAD_DELETE => "id",
+        _ => "channel_id",
+    };
+    event.d.as_ref()?.get(field)?.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex as SyncMutex,
+        },
+        time::Duration,
+    };
+
+    use async_trait::async_trait;
+    use serde_json::json;
+    use tokio::time::Instant;
+
+    use super::*;
+    use crate::gateway::response::DiscordOpCode;
+    use crate::handlers::{
+        message_handler::MessageHandlerResult, raw_event_handler::RawEventHandler,
+    };
+
+    /// Handles events named by their `name` for `delay_ms`, panics on `panic`
+    #[derive(Default)]
+    struct Recorder {
+        started: SyncMutex<Vec<String>>,
+        finished: SyncMutex<Vec<String>>,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    struct RecordingHandler(Arc<Recorder>);
+
+    #[async_trait]
+    impl RawEventHandler for RecordingHandler {
+        async fn on_raw_event(
+            &self,
+            event: &ReceiveEvent,
+            _client: &Client,
+        ) -> MessageHandlerResult {
+            let data = event.d.as_ref().unwrap();
+            let name = data["name"].as_str().unwrap().to_string();
+            if data["panic"] == true {
+                panic!("handler of {} panicked", name);
+            }
+            let recorder = &self.0;
+            recorder.started.lock().unwrap().push(name.clone());
+            let running = recorder.running.fetch_add(1, Ordering::SeqCst) + 1;
+            recorder.max_running.fetch_max(running, Ordering::SeqCst);
+            let delay = data["delay_ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            recorder.running.fetch_sub(1, Ordering::SeqCst);
+            recorder.finished.lock().unwrap().push(name);
+            Ok(())
+        }
+    }
+
+    /// Queue handing events to a [`RecordingHandler`]
+    async fn queue(
+        queue_capacity: usize,
+        max_concurrency: usize,
+    ) -> (DispatchQueue, Arc<Recorder>) {
+        let recorder = Arc::new(Recorder::default());
+        let client = Client::new();
+        client
+            .event_dispatcher
+            .add_raw_handler(RecordingHandler(recorder.clone()))
+            .await;
+        let config = DispatchConfig {
+            queue_capacity,
+            max_concurrency,
+        };
+        let limit = Arc::new(Semaphore::new(max_concurrency));
+        (DispatchQueue::start(client, &config, limit), recorder)
+    }
+
+    fn event(event_name: GatewayReceiveEventName, data: serde_json::Value) -> ReceiveEvent {
+        ReceiveEvent {
+            t: Some(event_name),
+            s: None,
+            op: DiscordOpCode::Dispatch,
+            d: Some(data),
+        }
+    }
+
+    /// Event of `channel_id` taking `delay_ms` to handle
+    fn channel_event(channel_id: &str, name: &str, delay_ms: u64) -> ReceiveEvent {
+        event(
+            GatewayReceiveEventName::Unknown("TEST_EVENT".to_string()),
+            json!({ "channel_id": channel_id, "name": name, "delay_ms": delay_ms }),
+        )
+    }
+
+    /// Wait until `count` events were handled
+    async fn wait_for_finished(recorder: &Recorder, count: usize) -> Vec<String> {
+        tokio::time::timeout(Duration::from_secs(60), async {
+            loop {
+                let finished = recorder.finished.lock().unwrap().clone();
+                if finished.len() >= count {
+                    return finished;
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("events were not handled")
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn events_of_a_channel_are_handled_in_order() {
+        let (queue, recorder) = queue(16, 4).await;
+        // earlier events take longer, running them in parallel would reorder them
+        for (name, delay) in [("a", 30), ("b", 20), ("c", 10), ("d", 0)] {
+            queue.try_push(channel_event("1", name, delay)).unwrap();
+        }
+
+        assert_eq!(wait_for_finished(&recorder, 4).await, ["a", "b", "c", "d"]);
+        assert_eq!(recorder.max_running.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn channels_are_handled_in_parallel_up_to_the_limit() {
+        let (queue, recorder) = queue(16, 2).await;
+        let start = Instant::now();
+        for channel_id in ["1", "2", "3", "4"] {
+            queue
+                .try_push(channel_event(channel_id, channel_id, 100))
+                .unwrap();
+        }
+
+        wait_for_finished(&recorder, 4).await;
+        assert_eq!(recorder.max_running.load(Ordering::SeqCst), 2);
+        // two rounds of two events
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(300), "{:?}", elapsed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn channel_events_are_ordered_with_the_channel_messages() {
+        let (queue, recorder) = queue(16, 4).await;
+        // CHANNEL_UPDATE names its channel in `id`
+        queue
+            .try_push(event(
+                GatewayReceiveEventName::CHANNEL_UPDATE,
+                json!({ "id": "1", "name": "update", "delay_ms": 30 }),
+            ))
+            .unwrap();
+        queue.try_push(channel_event("1", "message", 0)).unwrap();
+
+        assert_eq!(wait_for_finished(&recorder, 2).await, ["update", "message"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn busy_channel_does_not_hold_back_other_channels() {
+        let (queue, recorder) = queue(4, 4).await;
+        let start = Instant::now();
+        queue.try_push(channel_event("1", "slow", 10_000)).unwrap();
+        queue.try_push(channel_event("1", "queued", 0)).unwrap();
+        queue.try_push(channel_event("2", "other", 0)).unwrap();
+
+        assert_eq!(wait_for_finished(&recorder, 1).await, ["other"]);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(
+            wait_for_finished(&recorder, 3).await,
+            ["other", "slow", "queued"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn full_backlog_pushes_back_on_the_shard() {
+        let (queue, recorder) = queue(2, 1).await;
+        queue.try_push(channel_event("1", "slow", 10_000)).unwrap();
+        // two events wait behind the slow one, one waits for room in the
+        // backlog and two fill the queue of the shard
+        for name in ["b", "c", "d", "e", "f"] {
+            let pushed = queue.try_push(channel_event("1", name, 0));
+            assert!(
+                pushed.is_ok(),
+                "event {} was refused before the queue was full",
+                name
+            );
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let refused = queue.try_push(channel_event("2", "g", 0));
+        assert!(
+            refused.is_err(),
+            "queue took more events than it has room for"
+        );
+
+        // the queue takes events again once the backlog drains
+        let permit = tokio::time::timeout(Duration::from_secs(60), queue.reserve())
+            .await
+            .expect("queue did not make room")
+            .unwrap();
+        permit.send(channel_event("2", "g", 0));
+        let finished = wait_for_finished(&recorder, 7).await;
+        assert_eq!(finished[0], "slow");
+        let channel_1: Vec<&String> = finished.iter().filter(|name| *name != "g").collect();
+        assert_eq!(channel_1, ["slow", "b", "c", "d", "e", "f"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn panicking_handler_does_not_stop_the_queue() {
+        let (queue, recorder) = queue(16, 1).await;
+        queue
+            .try_push(event(
+                GatewayReceiveEventName::Unknown("TEST_EVENT".to_string()),
+                json!({ "channel_id": "1", "name": "boom", "panic": true }),
+            ))
+            .unwrap();
+        queue.try_push(channel_event("1", "after", 0)).unwrap();
+        queue.try_push(channel_event("2", "other", 0)).unwrap();
+
+        let mut finished = wait_for_finished(&recorder, 2).await;
+        finished.sort();
+        assert_eq!(finished, ["after", "other"]);
+    }
+}
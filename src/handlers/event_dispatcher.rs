@@ -1,75 +1,99 @@
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
-use crate::message::ChannelMessage;
 use crate::client::Client;
-use crate::gateway::response::{GatewayReceiveEventName, ReceiveEvent};
+use crate::event_handler::{self, EventHandler};
+use crate::gateway::event::Event;
+use crate::gateway::response::ReceiveEvent;
 use crate::handlers::message_handler::MessageHandlerRegistry;
+use crate::handlers::raw_event_handler::RawEventHandler;
 
 /// Event dispatcher that routes gateway events to appropriate handlers
 pub struct EventDispatcher {
     message_handlers: Arc<MessageHandlerRegistry>,
+    raw_handlers: Arc<RwLock<Vec<Box<dyn RawEventHandler>>>>,
+    event_handlers: Arc<RwLock<Vec<Box<dyn EventHandler>>>>,
 }
 
 impl EventDispatcher {
     pub fn new() -> Self {
         Self {
             message_handlers: Arc::new(MessageHandlerRegistry::new()),
+            raw_handlers: Arc::new(RwLock::new(Vec::new())),
+            event_handlers: Arc::new(RwLock::new(Vec::new())),
         }
     }
     
     pub fn get_message_handlers(&self) -> Arc<MessageHandlerRegistry> {
         self.message_handlers.clone()
     }
+
+    /// Register a handler that receives every raw gateway event
+    pub async fn add_raw_handler<H>(&self, handler: H)
+    where
+        H: RawEventHandler + 'static,
+    {
+        let mut handlers = self.raw_handlers.write().await;
+        let handler_name = std::any::type_name::<H>();
+        log::debug!("📝 Adding raw event handler: {}", handler_name);
+        handlers.push(Box::new(handler));
+        log::info!(
+            "✅ Raw event handler registered: {} (Total: {})",
+            handler_name,
+            handlers.len()
+        );
+    }
+
+    /// Register a handler that receives typed gateway events
+    pub async fn add_event_handler<H>(&self, handler: H)
+    where
+        H: EventHandler + 'static,
+    {
+        let mut handlers = self.event_handlers.write().await;
+        let handler_name = std::any::type_name::<H>();
+        log::debug!("📝 Adding event handler: {}", handler_name);
+        handlers.push(Box::new(handler));
+        log::info!(
+            "✅ Event handler registered: {} (Total: {})",
+            handler_name,
+            handlers.len()
+        );
+    }
     
     /// Dispatch a gateway event to the appropriate handler
     pub async fn dispatch_event(&self, event: &ReceiveEvent, client: &Client) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match event.t {
-            GatewayReceiveEventName::MESSAGE_CREATE => {
-                if let Some(data) = &event.d {
-                    match serde_json::from_value::<ChannelMessage>(data.clone()) {
-                        Ok(message) => {
-                            log::info!("📨 Message received: {} from {}", message.content, message.author.name);
-                            self.message_handlers.handle_message_create(&message, client).await?;
-                        }
-                        Err(e) => {
-                            log::error!("Failed to parse MESSAGE_CREATE event: {:?}", e);
-                        }
-                    }
-                }
-            }
-            GatewayReceiveEventName::MESSAGE_UPDATE => {
-                if let Some(data) = &event.d {
-                    match serde_json::from_value::<ChannelMessage>(data.clone()) {
-                        Ok(message) => {
-                            log::info!("📝 Message updated: {} from {}", message.content, message.author.name);
-                            self.message_handlers.handle_message_update(&message, client).await?;
-                        }
-                        Err(e) => {
-                            log::error!("Failed to parse MESSAGE_UPDATE event: {:?}", e);
-                        }
-                    }
-                }
+        for (index, handler) in self.raw_handlers.read().await.iter().enumerate() {
+            if let Err(e) = handler.on_raw_event(event, client).await {
+                log::error!("❌ Error in raw event handler {}: {:?}", index + 1, e);
             }
-            GatewayReceiveEventName::MESSAGE_DELETE => {
-                if let Some(data) = &event.d {
-                    if let (Some(message_id), Some(channel_id)) = (
-                        data.get("id").and_then(|v| v.as_str()),
-                        data.get("channel_id").and_then(|v| v.as_str()),
-                    ) {
-                        log::info!("🗑️ Message deleted: {} in channel {}", message_id, channel_id);
-                        self.message_handlers.handle_message_delete(message_id, channel_id, client).await?;
-                    }
-                }
+        }
+
+        // handlers still get the raw payload, a model mismatch must not hide the event
+        let Some(typed) = Event::from_receive_event_or_unknown(event) else {
+            log::debug!("Unhandled payload without event name (op: {:?})", event.op);
+            return Ok(());
+        };
+
+        for (index, handler) in self.event_handlers.read().await.iter().enumerate() {
+            if let Err(e) = event_handler::handle_event(handler.as_ref(), &typed, client).await {
+                log::error!("❌ Error in event handler {}: {:?}", index + 1, e);
             }
-            GatewayReceiveEventName::READY => {
+        }
+
+        self.dispatch_message_event(&typed, client).await?;
+
+        match &typed {
+            Event::Ready(_) => {
                 log::info!("🚀 Bot is ready!");
             }
-            GatewayReceiveEventName::GUILD_CREATE => {
-                if let Some(data) = &event.d {
-                    if let Some(guild_name) = data.get("name").and_then(|v| v.as_str()) {
-                        log::info!("🏰 Joined guild: {}", guild_name);
-                    }
-                }
+            Event::GuildCreate(guild) => {
+                log::info!("🏰 Joined guild: {}", guild.name);
+            }
+            Event::GuildMembersChunk(chunk) => {
+                log::debug!("👥 Member chunk {}/{} for guild {}", chunk.chunk_index + 1, chunk.chunk_count, chunk.guild_id);
+            }
+            Event::Unknown { name, .. } => {
+                log::debug!("Unknown event: {}", name);
             }
             _ => {
                 log::debug!("Unhandled event: {:?}", event.t);
@@ -78,6 +102,30 @@ impl EventDispatcher {
         
         Ok(())
     }
+
+    /// Feed message events to the message handlers.
+    ///
+    /// Fields of a message that are not needed to handle it fall back to their
+    /// default when they do not parse, so a model mismatch in one of them does
+    /// not stop message handlers and prefix commands.
+    async fn dispatch_message_event(&self, typed: &Event, client: &Client) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match typed {
+            Event::MessageCreate(message) => {
+                log::info!("📨 Message received: {} from {}", message.content, message.author.name);
+                self.message_handlers.handle_message_create(message, client).await?;
+            }
+            Event::MessageUpdate(message) => {
+                log::info!("📝 Message updated: {} from {}", message.content, message.author.name);
+                self.message_handlers.handle_message_update(message, client).await?;
+            }
+            Event::MessageDelete(deleted) => {
+                log::info!("🗑️ Message deleted: {} in channel {}", deleted.id, deleted.channel_id);
+                self.message_handlers.handle_message_delete(&deleted.id, &deleted.channel_id, client).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 }
 
 impl Default for EventDispatcher {
@@ -102,13 +102,13 @@ impl MessageHandler for PingPongHandler {
     }
 }
 
+/// Boxed callback executed for a single prefix command
+type CommandFn = Box<dyn Fn(&ChannelMessage, &Client) -> MessageHandlerResult + Send + Sync>;
+
 /// Command-based message handler with prefix support
 pub struct CommandHandler {
     prefix: String,
-    commands: HashMap<
-        String,
-        Box<dyn Fn(&ChannelMessage, &Client) -> MessageHandlerResult + Send + Sync>,
-    >,
+    commands: HashMap<String, CommandFn>,
 }
 
 impl CommandHandler {
@@ -253,6 +253,12 @@ impl PrefixListenerRegistry {
     }
 }
 
+impl Default for PrefixListenerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl MessageHandler for CommandHandler {
     async fn on_message_create(
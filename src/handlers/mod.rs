@@ -1,2 +1,4 @@
-pub mod message_handler;
+pub mod dispatch_queue;
 pub mod event_dispatcher;
+pub mod message_handler;
+pub mod raw_event_handler;
@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::client::Client;
+use crate::gateway::response::ReceiveEvent;
+use crate::handlers::message_handler::MessageHandlerResult;
+
+/// Trait for handling raw gateway events before they are parsed.
+///
+/// Raw handlers receive every event the shard dispatches, including events
+/// and opcodes the library does not know yet, so bots can react to new
+/// Discord features without waiting for a release.
+#[async_trait]
+pub trait RawEventHandler: Send + Sync {
+    /// Handle a raw gateway event
+    async fn on_raw_event(&self, event: &ReceiveEvent, client: &Client) -> MessageHandlerResult;
+}
@@ -0,0 +1,146 @@
+// http/attachment.rs
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::{Error, Result};
+
+/// Files allowed per message
+pub const MAX_ATTACHMENTS: usize = 10;
+
+/// Characters allowed in the description (alt text) of an attachment
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+
+/// Prefix of the filename that hides an attachment behind a spoiler
+const SPOILER_PREFIX: &str = "SPOILER_";
+
+/// A file uploaded with a message
+///
+/// The file is read into memory, so a request can be sent again after a
+/// rate limit.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use rustycord::{
+///     client::Client,
+///     embeds::Embed,
+///     http::{AttachmentFile, CreateMessage},
+/// };
+///
+/// # async fn report(client: &Client) -> rustycord::Result<()> {
+/// let chart = AttachmentFile::from_path("chart.png")
+///     .await?
+///     .description("Requests per minute over the last hour");
+/// let embed = Embed::new().title("Traffic").image(&chart.url());
+/// let message = CreateMessage::new().embed(embed).attachment(chart);
+/// client.create_message("1000", message).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AttachmentFile {
+    /// Name of the file, shown to users
+    pub filename: String,
+
+    /// Content of the file
+    pub data: Vec<u8>,
+
+    /// Description (alt text) of the file, up to 1024 characters
+    pub description: Option<String>,
+
+    /// Hide the file behind a spoiler
+    pub spoiler: bool,
+}
+
+impl AttachmentFile {
+    /// File with the content `data`
+    pub fn from_bytes(filename: &str, data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            filename: filename.to_string(),
+            data: data.into(),
+            description: None,
+            spoiler: false,
+        }
+    }
+
+    /// Read a file from disk, it is uploaded with its file name
+    pub async fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let filename = path
+            .file_name()
+            .and_then(|filename| filename.to_str())
+            .ok_or_else(|| Error::Config(format!("{} is not a file", path.display())))?;
+        let data = tokio::fs::read(path).await.inspect_err(|err| {
+            log::error!("❌ Failed to read attachment {}: {}", path.display(), err)
+        })?;
+        Ok(Self::from_bytes(filename, data))
+    }
+
+    /// Read a file from `reader` until it ends
+    pub async fn from_reader<R>(filename: &str, mut reader: R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        Ok(Self::from_bytes(filename, data))
+    }
+
+    /// Set the description (alt text) of the file
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Hide the file behind a spoiler
+    pub fn spoiler(mut self, spoiler: bool) -> Self {
+        self.spoiler = spoiler;
+        self
+    }
+
+    /// Name the file is uploaded with, prefixed with `SPOILER_` for spoilers
+    pub fn upload_filename(&self) -> String {
+        if self.spoiler && !self.filename.starts_with(SPOILER_PREFIX) {
+            format!("{}{}", SPOILER_PREFIX, self.filename)
+        } else {
+            self.filename.clone()
+        }
+    }
+
+    /// Url to use the file in an embed of the same message, e.g. as image
+    ///
+    /// More: <https://discord.com/developers/docs/reference#uploading-files>
+    pub fn url(&self) -> String {
+        format!("attachment://{}", self.upload_filename())
+    }
+
+    /// Metadata of the file sent in `payload_json`, `id` is the index of the
+    /// `files[n]` part
+    pub(crate) fn metadata(&self, id: usize) -> PartialAttachment {
+        PartialAttachment {
+            id: id.to_string(),
+            filename: Some(self.upload_filename()),
+            description: self.description.clone(),
+        }
+    }
+}
+
+/// Attachment of a message being sent or edited
+///
+/// More: <https://discord.com/developers/docs/resources/message#attachment-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PartialAttachment {
+    /// index of the `files[n]` part of a new file, or the id of an existing attachment
+    pub id: String,
+
+    /// name of the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+
+    /// description (alt text) of the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
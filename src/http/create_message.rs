@@ -0,0 +1,716 @@
+// http/create_message.rs
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    attachment::{AttachmentFile, MAX_ATTACHMENTS, MAX_DESCRIPTION_LENGTH},
+    AllowedMentions, MessagePayload, MessageReference,
+};
+use crate::{
+    embeds::Embed,
+    error::{Error, Result},
+};
+
+/// Do not include any embeds when serializing this message
+///
+/// SUPPRESS_EMBEDS (1 << 2)
+pub const SUPPRESS_EMBEDS: u64 = 1 << 2;
+
+/// This message will not trigger push and desktop notifications
+///
+/// SUPPRESS_NOTIFICATIONS (1 << 12)
+pub const SUPPRESS_NOTIFICATIONS: u64 = 1 << 12;
+
+/// Characters allowed in the content of a message
+pub const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Embeds allowed per message
+pub const MAX_EMBEDS: usize = 10;
+
+/// Characters allowed in all embeds of a message together
+pub const MAX_EMBED_TOTAL_LENGTH: usize = 6000;
+
+/// Stickers allowed per message
+pub const MAX_STICKERS: usize = 3;
+
+/// Action rows allowed per message
+pub const MAX_ACTION_ROWS: usize = 5;
+
+/// Characters allowed in a nonce
+pub const MAX_NONCE_LENGTH: usize = 25;
+
+/// Answers allowed per poll
+pub const MAX_POLL_ANSWERS: usize = 10;
+
+/// Longest poll duration in hours (32 days)
+pub const MAX_POLL_DURATION: u32 = 768;
+
+/// Text of a poll question or answer
+///
+/// More: <https://discord.com/developers/docs/resources/poll#poll-media-object>
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PollMedia {
+    /// the text of the field, up to 300 characters for questions and 55 for answers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    /// the emoji of an answer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<PollEmoji>,
+}
+
+/// Emoji of a poll answer, either a custom emoji by id or a unicode emoji by name
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PollEmoji {
+    /// id of a custom emoji
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// a unicode emoji
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// An answer of a poll
+///
+/// More: <https://discord.com/developers/docs/resources/poll#poll-answer-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PollAnswer {
+    /// the data of the answer
+    pub poll_media: PollMedia,
+}
+
+/// A poll sent with a message
+///
+/// More: <https://discord.com/developers/docs/resources/poll#poll-create-request-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PollCreate {
+    /// the question of the poll, only `text` is supported
+    pub question: PollMedia,
+
+    /// each of the answers available in the poll, up to 10
+    pub answers: Vec<PollAnswer>,
+
+    /// number of hours the poll should be open for, up to 32 days, default 24
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+
+    /// whether a user can select multiple answers, default false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_multiselect: Option<bool>,
+}
+
+impl PollCreate {
+    /// Create a poll asking `question`
+    pub fn new(question: &str) -> Self {
+        Self {
+            question: PollMedia {
+                text: Some(question.to_string()),
+                emoji: None,
+            },
+            answers: Vec::new(),
+            duration: None,
+            allow_multiselect: None,
+        }
+    }
+
+    /// Add an answer
+    pub fn answer(mut self, text: &str) -> Self {
+        self.answers.push(PollAnswer {
+            poll_media: PollMedia {
+                text: Some(text.to_string()),
+                emoji: None,
+            },
+        });
+        self
+    }
+
+    /// Add an answer shown with a unicode emoji
+    pub fn answer_with_emoji(mut self, text: &str, emoji: &str) -> Self {
+        self.answers.push(PollAnswer {
+            poll_media: PollMedia {
+                text: Some(text.to_string()),
+                emoji: Some(PollEmoji {
+                    id: None,
+                    name: Some(emoji.to_string()),
+                }),
+            },
+        });
+        self
+    }
+
+    /// Set how many hours the poll is open
+    pub fn duration(mut self, hours: u32) -> Self {
+        self.duration = Some(hours);
+        self
+    }
+
+    /// Let users select more than one answer
+    pub fn allow_multiselect(mut self, allow_multiselect: bool) -> Self {
+        self.allow_multiselect = Some(allow_multiselect);
+        self
+    }
+}
+
+/// Builder of a new message, checked against the limits of Discord before
+/// it is sent
+///
+/// # Examples
+///
+/// ```rust
+/// use rustycord::{
+///     embeds::Embed,
+///     http::{AllowedMentions, CreateMessage},
+/// };
+///
+/// let payload = CreateMessage::new()
+///     .content("Deploy finished <@123>")
+///     .embed(Embed::new().title("v1.2.0").color(0x00ff00))
+///     .allowed_mentions(AllowedMentions::none())
+///     .reply_to("1000", "2000")
+///     .silent(true)
+///     .build()
+///     .unwrap();
+/// assert_eq!(payload.flags, Some(1 << 12));
+///
+/// assert!(CreateMessage::new().content(&"a".repeat(2001)).build().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CreateMessage {
+    payload: MessagePayload,
+    files: Vec<AttachmentFile>,
+}
+
+impl CreateMessage {
+    /// Create an empty message
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the content of the message
+    pub fn content(mut self, content: &str) -> Self {
+        self.payload.content = Some(content.to_string());
+        self
+    }
+
+    /// Add an embed to the message
+    pub fn embed(mut self, embed: Embed) -> Self {
+        self.payload.embeds.get_or_insert_with(Vec::new).push(embed);
+        self
+    }
+
+    /// Set the embeds of the message
+    pub fn embeds(mut self, embeds: Vec<Embed>) -> Self {
+        self.payload.embeds = Some(embeds);
+        self
+    }
+
+    /// Read the message aloud using text-to-speech
+    pub fn tts(mut self, tts: bool) -> Self {
+        self.payload.tts = Some(tts);
+        self
+    }
+
+    /// Set a nonce to recognize the message in `MESSAGE_CREATE`
+    pub fn nonce(mut self, nonce: &str) -> Self {
+        self.payload.nonce = Some(nonce.to_string());
+        self
+    }
+
+    /// Do not send the message again if a message with the same nonce was
+    /// sent in the past few minutes, needs a [`nonce`](Self::nonce)
+    pub fn enforce_nonce(mut self, enforce_nonce: bool) -> Self {
+        self.payload.enforce_nonce = Some(enforce_nonce);
+        self
+    }
+
+    /// Set which mentions of the message notify
+    pub fn allowed_mentions(mut self, allowed_mentions: AllowedMentions) -> Self {
+        self.payload.allowed_mentions = Some(allowed_mentions);
+        self
+    }
+
+    /// Set the message this message replies to
+    pub fn reference(mut self, reference: MessageReference) -> Self {
+        self.payload.message_reference = Some(reference);
+        self
+    }
+
+    /// Reply to `message_id` in `channel_id`, failing if the message was deleted
+    pub fn reply_to(self, channel_id: &str, message_id: &str) -> Self {
+        self.reference(MessageReference::new(channel_id, message_id).set_fail_if_not_exists(true))
+    }
+
+    /// Add a sticker of the server to the message
+    pub fn sticker(mut self, sticker_id: &str) -> Self {
+        self.payload
+            .sticker_ids
+            .get_or_insert_with(Vec::new)
+            .push(sticker_id.to_string());
+        self
+    }
+
+    /// Add a component, such as an action row with buttons
+    ///
+    /// More: <https://discord.com/developers/docs/components/reference>
+    pub fn component(mut self, component: Value) -> Self {
+        self.payload
+            .components
+            .get_or_insert_with(Vec::new)
+            .push(component);
+        self
+    }
+
+    /// Set the components of the message
+    pub fn components(mut self, components: Vec<Value>) -> Self {
+        self.payload.components = Some(components);
+        self
+    }
+
+    /// Attach a poll to the message
+    pub fn poll(mut self, poll: PollCreate) -> Self {
+        self.payload.poll = Some(poll);
+        self
+    }
+
+    /// Set the message flags, see [`SUPPRESS_EMBEDS`] and [`SUPPRESS_NOTIFICATIONS`]
+    pub fn flags(mut self, flags: u64) -> Self {
+        self.payload.flags = Some(flags);
+        self
+    }
+
+    /// Do not show embeds of links in the content
+    pub fn suppress_embeds(self, suppress: bool) -> Self {
+        self.set_flag(SUPPRESS_EMBEDS, suppress)
+    }
+
+    /// Send the message without push and desktop notifications
+    pub fn silent(self, silent: bool) -> Self {
+        self.set_flag(SUPPRESS_NOTIFICATIONS, silent)
+    }
+
+    /// Upload a file with the message
+    pub fn attachment(mut self, file: AttachmentFile) -> Self {
+        self.files.push(file);
+        self
+    }
+
+    /// Set the files uploaded with the message
+    pub fn attachments(mut self, files: Vec<AttachmentFile>) -> Self {
+        self.files = files;
+        self
+    }
+
+    fn set_flag(mut self, flag: u64, enabled: bool) -> Self {
+        let flags = self.payload.flags.unwrap_or_default();
+        self.payload.flags = Some(if enabled { flags | flag } else { flags & !flag });
+        self
+    }
+
+    /// Check the message and turn it into the payload sent to Discord
+    ///
+    /// Uploaded files are described in `attachments`, their content is sent
+    /// by [`HTTPClient::create_message`](super::HTTPClient::create_message).
+    pub fn build(self) -> Result<MessagePayload> {
+        self.into_parts().map(|(payload, _)| payload)
+    }
+
+    /// Check the message and turn it into the payload of an edit, fields
+    /// left out keep their value
+    pub(crate) fn build_edit(self) -> Result<MessagePayload> {
+        self.payload.validate_edit()?;
+        Ok(self.payload)
+    }
+
+    /// Check the message and split it into the payload and the files to upload
+    pub(crate) fn into_parts(mut self) -> Result<(MessagePayload, Vec<AttachmentFile>)> {
+        if !self.files.is_empty() {
+            self.payload.attachments = Some(
+                self.files
+                    .iter()
+                    .enumerate()
+                    .map(|(id, file)| file.metadata(id))
+                    .collect(),
+            );
+        }
+        self.payload.validate()?;
+        Ok((self.payload, self.files))
+    }
+}
+
+impl MessagePayload {
+    /// Check the payload against the limits of Discord, so an invalid message
+    /// fails without a request
+    ///
+    /// More: <https://discord.com/developers/docs/resources/message#create-message>
+    pub fn validate(&self) -> Result<()> {
+        self.validate_edit()?;
+        if self.content.as_deref().unwrap_or_default().is_empty()
+            && self.embeds.as_deref().unwrap_or_default().is_empty()
+            && self.sticker_ids.as_deref().unwrap_or_default().is_empty()
+            && self.components.as_deref().unwrap_or_default().is_empty()
+            && self.poll.is_none()
+            && self.attachments.as_deref().unwrap_or_default().is_empty()
+        {
+            return invalid(
+                "a message needs content, embeds, stickers, components, a poll or files"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Check the payload of an edit against the limits of Discord
+    ///
+    /// Unlike [`validate`](Self::validate) an edit may leave out every field,
+    /// those keep their current value.
+    ///
+    /// More: <https://discord.com/developers/docs/resources/message#edit-message>
+    pub fn validate_edit(&self) -> Result<()> {
+        let content = self.content.as_deref().unwrap_or_default();
+        if content.chars().count() > MAX_CONTENT_LENGTH {
+            return invalid(format!(
+                "content is longer than {} characters",
+                MAX_CONTENT_LENGTH
+            ));
+        }
+
+        let embeds = self.embeds.as_deref().unwrap_or_default();
+        if embeds.len() > MAX_EMBEDS {
+            return invalid(format!("more than {} embeds", MAX_EMBEDS));
+        }
+        let mut embed_length = 0;
+        for (index, embed) in embeds.iter().enumerate() {
+            embed_length += validate_embed(index, embed)?;
+        }
+        if embed_length > MAX_EMBED_TOTAL_LENGTH {
+            return invalid(format!(
+                "embeds are longer than {} characters in total",
+                MAX_EMBED_TOTAL_LENGTH
+            ));
+        }
+
+        let stickers = self.sticker_ids.as_deref().unwrap_or_default();
+        if stickers.len() > MAX_STICKERS {
+            return invalid(format!("more than {} stickers", MAX_STICKERS));
+        }
+
+        let components = self.components.as_deref().unwrap_or_default();
+        if components.len() > MAX_ACTION_ROWS {
+            return invalid(format!("more than {} action rows", MAX_ACTION_ROWS));
+        }
+
+        match &self.nonce {
+            Some(nonce) if nonce.chars().count() > MAX_NONCE_LENGTH => {
+                return invalid(format!(
+                    "nonce is longer than {} characters",
+                    MAX_NONCE_LENGTH
+                ));
+            }
+            None if self.enforce_nonce == Some(true) => {
+                return invalid("enforce_nonce needs a nonce".to_string());
+            }
+            _ => {}
+        }
+
+        let flags = self.flags.unwrap_or_default();
+        if flags & !(SUPPRESS_EMBEDS | SUPPRESS_NOTIFICATIONS) != 0 {
+            return invalid(format!(
+                "flags {:#x} can not be set when sending a message",
+                flags
+            ));
+        }
+
+        if let Some(poll) = &self.poll {
+            validate_poll(poll)?;
+        }
+
+        let attachments = self.attachments.as_deref().unwrap_or_default();
+        if attachments.len() > MAX_ATTACHMENTS {
+            return invalid(format!("more than {} attachments", MAX_ATTACHMENTS));
+        }
+        for attachment in attachments {
+            checked_length(
+                attachment.description.as_deref(),
+                MAX_DESCRIPTION_LENGTH,
+                &format!("description of attachment {}", attachment.id),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: String) -> Result<()> {
+    Err(Error::Validation(reason))
+}
+
+/// Check `text` is at most `limit` characters and return its length
+fn checked_length(text: Option<&str>, limit: usize, what: &str) -> Result<usize> {
+    let length = text.map(|text| text.chars().count()).unwrap_or_default();
+    if length > limit {
+        return Err(Error::Validation(format!(
+            "{} is longer than {} characters",
+            what, limit
+        )));
+    }
+    Ok(length)
+}
+
+/// Check the limits of an embed and return the characters it counts towards
+/// the total of the message
+///
+/// More: <https://discord.com/developers/docs/resources/message#embed-object-embed-limits>
+fn validate_embed(index: usize, embed: &Embed) -> Result<usize> {
+    let what = |field: &str| format!("{} of embed {}", field, index + 1);
+    let mut length = checked_length(embed.title.as_deref(), 256, &what("title"))?;
+    length += checked_length(embed.description.as_deref(), 4096, &what("description"))?;
+    if let Some(footer) = &embed.footer {
+        length += checked_length(Some(&footer.text), 2048, &what("footer text"))?;
+    }
+    if let Some(author) = &embed.author {
+        length += checked_length(author.name.as_deref(), 256, &what("author name"))?;
+    }
+    let fields = embed.fields.as_deref().unwrap_or_default();
+    if fields.len() > 25 {
+        return Err(Error::Validation(format!(
+            "embed {} has more than 25 fields",
+            index + 1
+        )));
+    }
+    for field in fields {
+        length += checked_length(Some(&field.name), 256, &what("field name"))?;
+        length += checked_length(Some(&field.value), 1024, &what("field value"))?;
+    }
+    Ok(length)
+}
+
+fn validate_poll(poll: &PollCreate) -> Result<()> {
+    checked_length(poll.question.text.as_deref(), 300, "poll question")?;
+    if poll.answers.is_empty() || poll.answers.len() > MAX_POLL_ANSWERS {
+        return invalid(format!("a poll needs 1 to {} answers", MAX_POLL_ANSWERS));
+    }
+    for answer in &poll.answers {
+        checked_length(answer.poll_media.text.as_deref(), 55, "poll answer")?;
+    }
+    if poll
+        .duration
+        .is_some_and(|duration| duration > MAX_POLL_DURATION)
+    {
+        return invalid(format!(
+            "polls can be open for up to {} hours",
+            MAX_POLL_DURATION
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn text(length: usize) -> String {
+        "a".repeat(length)
+    }
+
+    fn assert_valid(message: CreateMessage) {
+        if let Err(err) = message.build() {
+            panic!("expected a valid message, got {}", err);
+        }
+    }
+
+    fn assert_invalid(message: CreateMessage) {
+        match message.build() {
+            Err(Error::Validation(_)) => {}
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    fn with_content() -> CreateMessage {
+        CreateMessage::new().content("hello")
+    }
+
+    #[test]
+    fn content_length() {
+        assert_valid(CreateMessage::new().content(&text(MAX_CONTENT_LENGTH)));
+        assert_invalid(CreateMessage::new().content(&text(MAX_CONTENT_LENGTH + 1)));
+        // the limit counts characters, not bytes
+        assert_valid(CreateMessage::new().content(&"é".repeat(MAX_CONTENT_LENGTH)));
+    }
+
+    #[test]
+    fn empty_message_is_invalid() {
+        assert_invalid(CreateMessage::new());
+        assert_invalid(CreateMessage::new().content(""));
+    }
+
+    #[test]
+    fn embed_count() {
+        let embeds = |count| vec![Embed::new().title("title"); count];
+        assert_valid(CreateMessage::new().embeds(embeds(MAX_EMBEDS)));
+        assert_invalid(CreateMessage::new().embeds(embeds(MAX_EMBEDS + 1)));
+    }
+
+    #[test]
+    fn embed_field_lengths() {
+        let embed = |embed: Embed| CreateMessage::new().embed(embed);
+        assert_valid(embed(Embed::new().title(&text(256))));
+        assert_invalid(embed(Embed::new().title(&text(257))));
+        assert_valid(embed(Embed::new().description(&text(4096))));
+        assert_invalid(embed(Embed::new().description(&text(4097))));
+        assert_valid(embed(Embed::new().footer(&text(2048), None)));
+        assert_invalid(embed(Embed::new().footer(&text(2049), None)));
+        assert_valid(embed(Embed::new().author(&text(256), None, None)));
+        assert_invalid(embed(Embed::new().author(&text(257), None, None)));
+        assert_valid(embed(Embed::new().field(&text(256), &text(1024), false)));
+        assert_invalid(embed(Embed::new().field(&text(257), "value", false)));
+        assert_invalid(embed(Embed::new().field("name", &text(1025), false)));
+    }
+
+    #[test]
+    fn embed_field_count() {
+        let fields =
+            |count| (0..count).fold(Embed::new(), |embed, _| embed.field("name", "value", true));
+        assert_valid(CreateMessage::new().embed(fields(25)));
+        assert_invalid(CreateMessage::new().embed(fields(26)));
+    }
+
+    #[test]
+    fn embed_total_length() {
+        let half = MAX_EMBED_TOTAL_LENGTH / 2;
+        assert_valid(
+            CreateMessage::new()
+                .embed(Embed::new().description(&text(half)))
+                .embed(Embed::new().description(&text(half))),
+        );
+        assert_invalid(
+            CreateMessage::new()
+                .embed(Embed::new().description(&text(half)))
+                .embed(Embed::new().description(&text(half + 1))),
+        );
+    }
+
+    #[test]
+    fn sticker_and_action_row_count() {
+        let stickers =
+            |count| (0..count).fold(CreateMessage::new(), |message, _| message.sticker("1"));
+        assert_valid(stickers(MAX_STICKERS));
+        assert_invalid(stickers(MAX_STICKERS + 1));
+
+        let row = json!({ "type": 1, "components": [] });
+        assert_valid(CreateMessage::new().components(vec![row.clone(); MAX_ACTION_ROWS]));
+        assert_invalid(CreateMessage::new().components(vec![row; MAX_ACTION_ROWS + 1]));
+    }
+
+    #[test]
+    fn nonce() {
+        assert_valid(with_content().nonce(&text(MAX_NONCE_LENGTH)));
+        assert_invalid(with_content().nonce(&text(MAX_NONCE_LENGTH + 1)));
+        assert_valid(with_content().nonce("1").enforce_nonce(true));
+        assert_invalid(with_content().enforce_nonce(true));
+    }
+
+    #[test]
+    fn only_sendable_flags() {
+        assert_valid(with_content().flags(SUPPRESS_EMBEDS | SUPPRESS_NOTIFICATIONS));
+        // EPHEMERAL can only be set on interaction responses
+        assert_invalid(with_content().flags(1 << 6));
+    }
+
+    #[test]
+    fn poll_limits() {
+        let answers =
+            |count| (0..count).fold(PollCreate::new("question"), |poll, _| poll.answer("answer"));
+        let poll = |poll| CreateMessage::new().poll(poll);
+        assert_valid(poll(answers(MAX_POLL_ANSWERS)));
+        assert_invalid(poll(answers(MAX_POLL_ANSWERS + 1)));
+        assert_invalid(poll(answers(0)));
+
+        assert_valid(poll(answers(1).duration(MAX_POLL_DURATION)));
+        assert_invalid(poll(answers(1).duration(MAX_POLL_DURATION + 1)));
+        assert_valid(poll(PollCreate::new(&text(300)).answer(&text(55))));
+        assert_invalid(poll(PollCreate::new(&text(301)).answer("answer")));
+        assert_invalid(poll(PollCreate::new("question").answer(&text(56))));
+    }
+
+    #[test]
+    fn unset_fields_are_skipped() {
+        let payload = with_content().build().unwrap();
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({ "content": "hello" })
+        );
+    }
+
+    #[test]
+    fn serializes_every_set_field() {
+        let payload = with_content()
+            .embed(Embed::new().title("title"))
+            .allowed_mentions(AllowedMentions::none())
+            .reply_to("1000", "2000")
+            .sticker("3000")
+            .nonce("4000")
+            .enforce_nonce(true)
+            .tts(true)
+            .silent(true)
+            .poll(PollCreate::new("question").answer_with_emoji("yes", "👍"))
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&payload).unwrap();
+
+        assert_eq!(json["content"], "hello");
+        assert_eq!(json["embeds"][0]["title"], "title");
+        assert_eq!(
+            json["allowed_mentions"],
+            json!({ "parse": [], "replied_user": false })
+        );
+        assert_eq!(
+            json["message_reference"],
+            json!({ "message_id": "2000", "channel_id": "1000", "fail_if_not_exists": true })
+        );
+        assert_eq!(json["sticker_ids"], json!(["3000"]));
+        assert_eq!(json["nonce"], "4000");
+        assert_eq!(json["enforce_nonce"], true);
+        assert_eq!(json["tts"], true);
+        assert_eq!(json["flags"], SUPPRESS_NOTIFICATIONS);
+        assert_eq!(
+            json["poll"],
+            json!({
+                "question": { "text": "question" },
+                "answers": [{ "poll_media": { "text": "yes", "emoji": { "name": "👍" } } }],
+            })
+        );
+        assert!(json.get("attachments").is_none());
+        assert!(json.get("components").is_none());
+    }
+
+    #[test]
+    fn flag_helpers_set_and_clear_their_flag() {
+        let payload = with_content()
+            .suppress_embeds(true)
+            .silent(true)
+            .silent(false)
+            .build()
+            .unwrap();
+        assert_eq!(payload.flags, Some(SUPPRESS_EMBEDS));
+    }
+
+    #[test]
+    fn edits_check_limits_but_may_be_empty() {
+        assert!(CreateMessage::new().build_edit().is_ok());
+        assert!(CreateMessage::new().embeds(Vec::new()).build_edit().is_ok());
+        assert!(matches!(
+            CreateMessage::new()
+                .content(&text(MAX_CONTENT_LENGTH + 1))
+                .build_edit(),
+            Err(Error::Validation(_))
+        ));
+        let embeds = vec![Embed::new().title("title"); MAX_EMBEDS + 1];
+        assert!(matches!(
+            CreateMessage::new().embeds(embeds).build_edit(),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,947 @@
+pub mod attachment;
+pub mod create_message;
+pub mod ratelimit;
+pub mod routing;
+
+pub use attachment::{AttachmentFile, PartialAttachment};
+pub use create_message::{CreateMessage, PollAnswer, PollCreate, PollEmoji, PollMedia};
+pub use routing::Route;
+
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    multipart::{Form, Part},
+    Client, Response, StatusCode,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::embeds::Embed;
+use crate::error::{Error, Result};
+use crate::response::UserResponse;
+use ratelimit::{RateLimiter, RouteKey, MAX_RATE_LIMIT_RETRIES, MAX_RETRY_AFTER};
+
+/// Response of `GET /gateway/bot`
+///
+/// More: <https://discord.com/developers/docs/events/gateway#get-gateway-bot>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GatewayResponse {
+    /// WSS URL that can be used for connecting to the Gateway
+    pub url: String,
+
+    /// Recommended number of shards to use when connecting
+    #[serde(default = "default_shards")]
+    pub shards: usize,
+
+    /// Information on the current session start limit
+    pub session_start_limit: Option<SessionStartLimit>,
+}
+
+fn default_shards() -> usize {
+    1
+}
+
+/// How many sessions the bot may still start
+///
+/// More: <https://discord.com/developers/docs/events/gateway#session-start-limit-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionStartLimit {
+    /// Total number of session starts the current user is allowed
+    pub total: u32,
+
+    /// Remaining number of session starts the current user is allowed
+    pub remaining: u32,
+
+    /// Number of milliseconds after which the limit resets
+    pub reset_after: u64,
+
+    /// Number of identify requests allowed per 5 seconds
+    pub max_concurrency: usize,
+}
+
+/// Default base url of the REST API
+pub const DEFAULT_API_URL: &str = "https://discord.com/api";
+
+/// Default version of the REST API and the gateway
+pub const DEFAULT_API_VERSION: u8 = 10;
+
+/// Where the library reaches Discord.
+///
+/// Point these at a local server to run a bot against a stand-in of the
+/// Discord API.
+///
+/// # Examples
+///
+/// ```rust
+/// use rustycord::http::Endpoints;
+///
+/// let endpoints = Endpoints {
+///     api_url: "http://127.0.0.1:8080/api".to_string(),
+///     gateway_url: Some("ws://127.0.0.1:8081".to_string()),
+///     ..Endpoints::default()
+/// };
+/// assert_eq!(endpoints.versioned_api_url(), "http://127.0.0.1:8080/api/v10");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    /// Base url of the REST API, without version
+    pub api_url: String,
+
+    /// Version of the REST API and the gateway
+    pub api_version: u8,
+
+    /// Gateway url used instead of the one returned by `GET /gateway/bot`
+    pub gateway_url: Option<String>,
+}
+
+impl Endpoints {
+    /// Base url of the REST API including the version, e.g. `https://discord.com/api/v10`
+    pub fn versioned_api_url(&self) -> String {
+        format!(
+            "{}/v{}",
+            self.api_url.trim_end_matches('/'),
+            self.api_version
+        )
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            api_url: DEFAULT_API_URL.to_string(),
+            api_version: DEFAULT_API_VERSION,
+            gateway_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MessageResponse {
+    pub id: String,
+    pub channel_id: String,
+    pub content: String,
+    pub timestamp: String,
+    pub author: serde_json::Value,
+}
+
+/// Json body of a new message, built with [`CreateMessage`]
+///
+/// More: <https://discord.com/developers/docs/resources/message#create-message-jsonform-params>
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MessagePayload {
+    /// message contents, up to 2000 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+
+    /// up to 10 rich embeds, up to 6000 characters in total
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeds: Option<Vec<Embed>>,
+
+    /// true if this is a TTS message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts: Option<bool>,
+
+    /// used to verify a message was sent, up to 25 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+
+    /// if true and a nonce is present, it will be checked for uniqueness in
+    /// the past few minutes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforce_nonce: Option<bool>,
+
+    /// allowed mentions for the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_mentions: Option<AllowedMentions>,
+
+    /// include to make the message a reply or a forward
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_reference: Option<MessageReference>,
+
+    /// components to include with the message, up to 5 action rows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Value>>,
+
+    /// ids of up to 3 stickers in the server to send in the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticker_ids: Option<Vec<String>>,
+
+    /// message flags combined as a bitfield, only
+    /// [`SUPPRESS_EMBEDS`](create_message::SUPPRESS_EMBEDS) and
+    /// [`SUPPRESS_NOTIFICATIONS`](create_message::SUPPRESS_NOTIFICATIONS) can be set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u64>,
+
+    /// a poll
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poll: Option<PollCreate>,
+
+    /// filenames and descriptions of the files uploaded with the message,
+    /// up to 10
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<PartialAttachment>>,
+}
+
+/// Reference to the message a message replies to
+///
+/// More: <https://discord.com/developers/docs/resources/message#message-reference-structure>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MessageReference {
+    /// id of the originating message
+    pub message_id: String,
+
+    /// id of the originating message's channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+
+    /// id of the originating message's guild
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
+
+    /// when sending, whether to error if the referenced message doesn't
+    /// exist instead of sending as a normal (non-reply) message, default true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_if_not_exists: Option<bool>,
+}
+
+impl MessageReference {
+    /// Reference to the message `message_id` in `channel_id`
+    pub fn new(channel_id: &str, message_id: &str) -> Self {
+        Self {
+            message_id: message_id.to_string(),
+            channel_id: Some(channel_id.to_string()),
+            guild_id: None,
+            fail_if_not_exists: None,
+        }
+    }
+
+    /// Set whether sending fails when the referenced message was deleted
+    pub fn set_fail_if_not_exists(mut self, fail_if_not_exists: bool) -> Self {
+        self.fail_if_not_exists = Some(fail_if_not_exists);
+        self
+    }
+}
+
+/// Which mentions of a message notify
+///
+/// More: <https://discord.com/developers/docs/resources/message#allowed-mentions-object>
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AllowedMentions {
+    /// mention types to parse from the content: `roles`, `users` and `everyone`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse: Option<Vec<String>>,
+
+    /// role ids to mention, up to 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<String>>,
+
+    /// user ids to mention, up to 100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users: Option<Vec<String>>,
+
+    /// for replies, whether to mention the author of the message being replied to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replied_user: Option<bool>,
+}
+
+impl AllowedMentions {
+    /// Mention nobody, not even the author of a replied message
+    pub fn none() -> Self {
+        Self {
+            parse: Some(Vec::new()),
+            replied_user: Some(false),
+            ..Self::default()
+        }
+    }
+}
+
+/// A request to the REST API, sent with [`HTTPClient::request`]
+///
+/// # Examples
+///
+/// ```rust
+/// use rustycord::http::{Request, Route};
+///
+/// let request = Request::new(Route::CreateMessage { channel_id: "1" })
+///     .json(&serde_json::json!({ "content": "Hello" }))
+///     .unwrap()
+///     .reason("Welcome message");
+/// assert_eq!(request.route().path(), "/channels/1/messages");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Request<'a> {
+    route: Route<'a>,
+    body: Option<Body>,
+    reason: Option<String>,
+}
+
+/// Body of a [`Request`], kept so the request can be sent again after a 429
+#[derive(Debug, Clone)]
+enum Body {
+    Json(Vec<u8>),
+    /// `multipart/form-data` with the json body in `payload_json` and the
+    /// files in `files[n]`
+    Multipart {
+        payload_json: Vec<u8>,
+        files: Vec<AttachmentFile>,
+    },
+}
+
+impl Body {
+    fn form(payload_json: &[u8], files: &[AttachmentFile]) -> Result<Form> {
+        let mut form = Form::new().part(
+            "payload_json",
+            Part::bytes(payload_json.to_vec()).mime_str("application/json")?,
+        );
+        for (index, file) in files.iter().enumerate() {
+            form = form.part(
+                format!("files[{}]", index),
+                Part::bytes(file.data.clone()).file_name(file.upload_filename()),
+            );
+        }
+        Ok(form)
+    }
+}
+
+impl<'a> Request<'a> {
+    /// Request to `route` without body
+    pub fn new(route: Route<'a>) -> Self {
+        Self {
+            route,
+            body: None,
+            reason: None,
+        }
+    }
+
+    /// Send `body` as json
+    pub fn json<T: Serialize + ?Sized>(mut self, body: &T) -> Result<Self> {
+        self.body = Some(Body::Json(serde_json::to_vec(body)?));
+        Ok(self)
+    }
+
+    /// Send `body` as `payload_json` of a `multipart/form-data` body, with
+    /// `files` uploaded as `files[0]`, `files[1]`, ...
+    ///
+    /// More: <https://discord.com/developers/docs/reference#uploading-files>
+    pub fn multipart<T: Serialize + ?Sized>(
+        mut self,
+        body: &T,
+        files: Vec<AttachmentFile>,
+    ) -> Result<Self> {
+        self.body = Some(Body::Multipart {
+            payload_json: serde_json::to_vec(body)?,
+            files,
+        });
+        Ok(self)
+    }
+
+    /// Set the reason shown in the audit log of the guild
+    ///
+    /// More: <https://discord.com/developers/docs/resources/audit-log#audit-log-entry-object>
+    pub fn reason(mut self, reason: &str) -> Self {
+        self.reason = Some(reason.to_string());
+        self
+    }
+
+    /// Route of the request
+    pub fn route(&self) -> &Route<'a> {
+        &self.route
+    }
+}
+
+/// Observes the requests sent by an [`HTTPClient`], e.g. to collect metrics
+///
+/// # Examples
+///
+/// ```rust
+/// use std::{sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+/// use rustycord::http::{RequestObserver, Route};
+///
+/// #[derive(Default)]
+/// struct CountRequests(AtomicUsize);
+///
+/// impl RequestObserver for CountRequests {
+///     fn on_response(&self, route: &Route<'_>, status: u16, elapsed: Duration) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///         println!("{} {} -> {} in {:?}", route.method(), route.template(), status, elapsed);
+///     }
+/// }
+/// ```
+pub trait RequestObserver: Send + Sync {
+    /// Called for every response, including 429 responses that are retried
+    fn on_response(&self, route: &Route<'_>, status: u16, elapsed: Duration);
+
+    /// Called when a request failed without response, e.g. on network errors
+    fn on_error(&self, _route: &Route<'_>, _error: &Error) {}
+}
+
+#[derive(Clone)]
+pub struct HTTPClient {
+    client: Client,
+    // ws: Option<WebSocket<MaybeTlsStream<TcpStream>>>,
+    token: String,
+    endpoints: Endpoints,
+    ratelimiter: Arc<RateLimiter>,
+    observers: Vec<Arc<dyn RequestObserver>>,
+}
+
+impl fmt::Debug for HTTPClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the token is left out so it does not end up in logs
+        f.debug_struct("HTTPClient")
+            .field("endpoints", &self.endpoints)
+            .field("ratelimiter", &self.ratelimiter)
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+impl HTTPClient {
+    const USER_AGENT: &'static str = "rustycord dev";
+
+    pub fn new() -> Self {
+        HTTPClient {
+            client: Client::new(),
+            // ws: None,
+            token: String::new(),
+            endpoints: Endpoints::default(),
+            ratelimiter: Arc::new(RateLimiter::new()),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Endpoints used to reach Discord
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    /// Set the endpoints used to reach Discord
+    pub fn set_endpoints(&mut self, endpoints: Endpoints) {
+        self.endpoints = endpoints;
+    }
+
+    /// Rate limit state of the REST API, e.g. for diagnostics
+    pub fn ratelimiter(&self) -> &RateLimiter {
+        &self.ratelimiter
+    }
+
+    /// Notify `observer` of every request sent by this client and clones
+    /// created afterwards
+    pub fn add_observer(&mut self, observer: Arc<dyn RequestObserver>) {
+        self.observers.push(observer);
+    }
+
+    fn api_url(&self) -> String {
+        self.endpoints.versioned_api_url()
+    }
+
+    /// Send a request and decode the json response
+    pub async fn request<T: DeserializeOwned>(&self, request: Request<'_>) -> Result<T> {
+        let res = self.execute(&request).await?;
+        decode(res).await
+    }
+
+    /// Send a request answered without body, e.g. with `204 No Content`
+    pub async fn request_empty(&self, request: Request<'_>) -> Result<()> {
+        let res = self.execute(&request).await?;
+        let status = res.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        Err(error_from_response(status, &res.text().await?))
+    }
+
+    /// Send a request once its rate limit bucket has room.
+    ///
+    /// Requests answered with 429 are sent again after `Retry-After`, up to
+    /// [`MAX_RATE_LIMIT_RETRIES`] times.
+    async fn execute(&self, request: &Request<'_>) -> Result<Response> {
+        let route = &request.route;
+        let key = RouteKey::from(route);
+        let ticket = self.ratelimiter.acquire(&key).await;
+        let mut retries = 0;
+        loop {
+            let started = Instant::now();
+            let res = match self.build(request)?.send().await {
+                Ok(res) => res,
+                Err(err) => {
+                    let err = Error::from(err);
+                    log::error!("❌ Request to {} failed: {}", key, err);
+                    for observer in &self.observers {
+                        observer.on_error(route, &err);
+                    }
+                    return Err(err);
+                }
+            };
+            log::debug!("📡 {} answered with status: {}", key, res.status());
+            for observer in &self.observers {
+                observer.on_response(route, res.status().as_u16(), started.elapsed());
+            }
+            self.ratelimiter.update(&key, &ticket, res.headers());
+            if res.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(res);
+            }
+
+            let header = |name: &str| {
+                res.headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+            };
+            let retry_after_header = header("retry-after");
+            let global_header = header("x-ratelimit-global");
+            let scope = header("x-ratelimit-scope");
+            let body = serde_json::from_str::<Value>(&res.text().await?).unwrap_or_default();
+            let retry_after = body["retry_after"]
+                .as_f64()
+                .or_else(|| retry_after_header?.parse().ok())
+                .unwrap_or(1.0);
+            let global = body["global"].as_bool().unwrap_or_default()
+                || global_header.as_deref() == Some("true");
+            let wait = match Duration::try_from_secs_f64(retry_after.max(0.0)) {
+                Ok(wait) => wait,
+                Err(_) => {
+                    log::error!(
+                        "❌ Rate limited on {} with unusable retry_after {}, giving up",
+                        key,
+                        retry_after
+                    );
+                    return Err(Error::RateLimited {
+                        retry_after,
+                        global,
+                    });
+                }
+            };
+            self.ratelimiter.rate_limited(&ticket, wait, global);
+
+            if retries >= MAX_RATE_LIMIT_RETRIES || wait > MAX_RETRY_AFTER {
+                log::error!(
+                    "❌ Rate limited on {} (scope: {}), giving up",
+                    key,
+                    scope.as_deref().unwrap_or("user")
+                );
+                return Err(Error::RateLimited {
+                    retry_after,
+                    global,
+                });
+            }
+            retries += 1;
+            log::warn!(
+                "⏳ Rate limited on {} (scope: {}), retrying in {}s",
+                key,
+                scope.as_deref().unwrap_or("user"),
+                retry_after
+            );
+            self.ratelimiter.wait(&key, &ticket).await;
+        }
+    }
+
+    /// Build the HTTP request with the headers shared by all routes
+    fn build(&self, request: &Request<'_>) -> Result<reqwest::RequestBuilder> {
+        let url = format!("{}{}", self.api_url(), request.route.path());
+        let mut builder = self
+            .client
+            .request(request.route.method(), url)
+            .header("Authorization", format!("Bot {}", self.token))
+            .header("User-Agent", Self::USER_AGENT);
+        if let Some(reason) = &request.reason {
+            builder = builder.header("X-Audit-Log-Reason", encode_reason(reason));
+        }
+        match &request.body {
+            Some(Body::Json(body)) => {
+                builder = builder
+                    .header("Content-Type", "application/json")
+                    .body(body.clone());
+            }
+            Some(Body::Multipart {
+                payload_json,
+                files,
+            }) => builder = builder.multipart(Body::form(payload_json, files)?),
+            None => {}
+        }
+        Ok(builder)
+    }
+
+    /// Check the token and fetch the bot user
+    ///
+    /// Fails with [`Error::Unauthorized`] when Discord rejects the token.
+    pub async fn login(&mut self, token: String) -> Result<UserResponse> {
+        log::info!("🔑 Attempting to login with provided token");
+        self.token = token;
+        match self
+            .request::<UserResponse>(Request::new(Route::GetCurrentUser))
+            .await
+        {
+            Ok(user_response) => {
+                log::info!(
+                    "✅ Successfully authenticated as: {}",
+                    user_response.username
+                );
+                Ok(user_response)
+            }
+            Err(err) => {
+                log::error!("❌ Authentication failed: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Fetch the gateway url, including version and encoding
+    pub async fn get_gateway(&self) -> Result<String> {
+        log::debug!("🚪 Fetching gateway URL from Discord API");
+        let gateway_response = self
+            .request::<GatewayResponse>(Request::new(Route::GetGatewayBot))
+            .await
+            .inspect_err(|err| log::error!("❌ Failed to get gateway URL: {}", err))?;
+        let gateway_url = format!(
+            "{}/?encoding=json&v={}",
+            self.endpoints
+                .gateway_url
+                .as_deref()
+                .unwrap_or(&gateway_response.url),
+            self.endpoints.api_version
+        );
+        log::info!("🚪 Gateway URL obtained: {}", gateway_url);
+        Ok(gateway_url)
+    }
+
+    /// Fetch the gateway url together with the recommended shard count and
+    /// the session start limit of the bot
+    pub async fn get_gateway_bot(&self) -> Result<GatewayResponse> {
+        log::debug!("🚪 Fetching gateway information from Discord API");
+        let gateway = self
+            .request::<GatewayResponse>(Request::new(Route::GetGatewayBot))
+            .await
+            .inspect_err(|err| log::error!("❌ Failed to get gateway information: {}", err))?;
+        log::info!(
+            "🚪 Gateway recommends {} shard(s) (session starts: {:?})",
+            gateway.shards,
+            gateway.session_start_limit
+        );
+        Ok(gateway)
+    }
+
+    pub async fn logout(&self) -> bool {
+        true
+        // self.client
+        // .post(format!("{}/auth/logout", self.api_url()))
+        // .header("Authorization",  format!("Bot {}", self.token))
+    }
+
+    pub async fn send_message(
+        &self,
+        channel_id: &str,
+        content: &str,
+        embeds: Option<Vec<Embed>>,
+    ) -> Result<MessageResponse> {
+        log::debug!("📤 Preparing message payload for channel: {}", channel_id);
+        self.create_message(channel_id, text_message(content, embeds))
+            .await
+    }
+
+    /// Reply to a message
+    ///
+    /// # Arguments
+    ///
+    /// * `channel_id` - the channel of the message
+    /// * `message_id` - the message to reply to
+    /// * `content` - content of the reply
+    /// * `embeds` - embeds of the reply
+    /// * `fail_if_not_exists` - fail when the message was deleted, instead of
+    ///   sending a normal message
+    pub async fn reply(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+        embeds: Option<Vec<Embed>>,
+        fail_if_not_exists: bool,
+    ) -> Result<MessageResponse> {
+        log::debug!(
+            "↩️ Preparing reply to message {} in channel: {}",
+            message_id,
+            channel_id
+        );
+        let reference = MessageReference::new(channel_id, message_id)
+            .set_fail_if_not_exists(fail_if_not_exists);
+        self.create_message(
+            channel_id,
+            text_message(content, embeds).reference(reference),
+        )
+        .await
+    }
+
+    /// Send a message with files to a channel
+    ///
+    /// Files can be shown in `embeds` through [`AttachmentFile::url`].
+    pub async fn send_files(
+        &self,
+        channel_id: &str,
+        content: &str,
+        embeds: Option<Vec<Embed>>,
+        files: Vec<AttachmentFile>,
+    ) -> Result<MessageResponse> {
+        log::debug!(
+            "📎 Preparing message with {} file(s) for channel: {}",
+            files.len(),
+            channel_id
+        );
+        self.create_message(channel_id, text_message(content, embeds).attachments(files))
+            .await
+    }
+
+    /// Send a message built with [`CreateMessage`] to a channel
+    ///
+    /// Messages with files are sent as `multipart/form-data`. Fails with
+    /// [`Error::Validation`] without sending a request when the message
+    /// breaks a limit of Discord.
+    pub async fn create_message(
+        &self,
+        channel_id: &str,
+        message: CreateMessage,
+    ) -> Result<MessageResponse> {
+        let (payload, files) = message
+            .into_parts()
+            .inspect_err(|err| log::error!("❌ Refusing to send message: {}", err))?;
+        log::info!(
+            "📤 Sending message to channel {}: {}",
+            channel_id,
+            payload.content.as_deref().unwrap_or_default()
+        );
+        let request = Request::new(Route::CreateMessage { channel_id });
+        let request = if files.is_empty() {
+            request.json(&payload)?
+        } else {
+            log::debug!("📎 Uploading {} file(s)", files.len());
+            request.multipart(&payload, files)?
+        };
+        log::trace!("🔍 Full payload: {:?}", payload);
+        let response = self
+            .request::<MessageResponse>(request)
+            .await
+            .inspect_err(|err| log::error!("❌ Failed to send message: {}", err))?;
+        log::info!("✅ Message sent successfully: {}", response.id);
+        Ok(response)
+    }
+
+    /// Edit a message sent by the bot
+    ///
+    /// Fields left `None` keep their value, an empty `embeds` removes all embeds.
+    /// Fails with [`Error::Validation`] without sending a request when the
+    /// edit breaks a limit of Discord.
+    pub async fn edit_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: Option<&str>,
+        embeds: Option<Vec<Embed>>,
+    ) -> Result<MessageResponse> {
+        let mut message = CreateMessage::new();
+        if let Some(content) = content {
+            message = message.content(content);
+        }
+        if let Some(embeds) = embeds {
+            message = message.embeds(embeds);
+        }
+        let payload = message
+            .build_edit()
+            .inspect_err(|err| log::error!("❌ Refusing to edit message: {}", err))?;
+
+        log::info!(
+            "✏️ Editing message {} in channel {}",
+            message_id,
+            channel_id
+        );
+        log::trace!("🔍 Full payload: {:?}", payload);
+        let request = Request::new(Route::EditMessage {
+            channel_id,
+            message_id,
+        })
+        .json(&payload)?;
+        let response = self
+            .request::<MessageResponse>(request)
+            .await
+            .inspect_err(|err| log::error!("❌ Failed to edit message: {}", err))?;
+        log::info!("✅ Message edited successfully: {}", response.id);
+        Ok(response)
+    }
+
+    /// Delete a message
+    ///
+    /// Deleting messages of other users needs the `MANAGE_MESSAGES`
+    /// permission, `reason` is shown in the audit log in that case.
+    pub async fn delete_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        log::info!(
+            "🗑️ Deleting message {} in channel {}",
+            message_id,
+            channel_id
+        );
+        let mut request = Request::new(Route::DeleteMessage {
+            channel_id,
+            message_id,
+        });
+        if let Some(reason) = reason {
+            request = request.reason(reason);
+        }
+        self.request_empty(request)
+            .await
+            .inspect_err(|err| log::error!("❌ Failed to delete message: {}", err))?;
+        log::info!("✅ Message deleted successfully: {}", message_id);
+        Ok(())
+    }
+}
+
+/// A message with text and embeds, empty content is left out
+fn text_message(content: &str, embeds: Option<Vec<Embed>>) -> CreateMessage {
+    let mut message = CreateMessage::new();
+    if !content.is_empty() {
+        message = message.content(content);
+    }
+    if let Some(embeds) = embeds {
+        log::debug!("📎 Adding {} embed(s) to message", embeds.len());
+        message = message.embeds(embeds);
+    }
+    message
+}
+
+/// Percent-encode an audit log reason, headers only carry ASCII
+fn encode_reason(reason: &str) -> String {
+    let mut encoded = String::with_capacity(reason.len());
+    for byte in reason.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Decode a successful response, or turn an error response into an [`Error`]
+async fn decode<T: DeserializeOwned>(res: Response) -> Result<T> {
+    let status = res.status();
+    let body = res.text().await?;
+    if !status.is_success() {
+        return Err(error_from_response(status, &body));
+    }
+    serde_json::from_str(&body).map_err(Error::from)
+}
+
+/// Map an error response of the REST API to an [`Error`]
+///
+/// More: <https://discord.com/developers/docs/reference#error-messages>
+fn error_from_response(status: StatusCode, body: &str) -> Error {
+    let body = serde_json::from_str::<Value>(body).unwrap_or_else(|_| json!({ "message": body }));
+    match status {
+        StatusCode::UNAUTHORIZED => Error::Unauthorized,
+        StatusCode::TOO_MANY_REQUESTS => Error::RateLimited {
+            retry_after: body["retry_after"].as_f64().unwrap_or_default(),
+            global: body["global"].as_bool().unwrap_or_default(),
+        },
+        _ => Error::Api {
+            status: status.as_u16(),
+            code: body["code"].as_u64(),
+            message: body["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| status.to_string()),
+        },
+    }
+}
+
+impl Default for HTTPClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http_body_util::BodyExt;
+
+    use super::*;
+
+    /// A part of a `multipart/form-data` body
+    struct FormPart {
+        name: String,
+        filename: Option<String>,
+        data: String,
+    }
+
+    /// Build `form` into a request body and split it into its parts
+    async fn parts(form: Form) -> Vec<FormPart> {
+        let boundary = format!("--{}", form.boundary());
+        let mut request = reqwest::Client::new()
+            .post("http://localhost/")
+            .multipart(form)
+            .build()
+            .unwrap();
+        let body = request.body_mut().take().unwrap();
+        let body = body.collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        body.split(&boundary)
+            .filter_map(|part| {
+                let (headers, data) = part.split_once("\r\n\r\n")?;
+                let disposition = headers
+                    .lines()
+                    .find(|line| line.starts_with("Content-Disposition"))?;
+                let param = |key: &str| {
+                    let (_, value) = disposition.split_once(&format!("{}=\"", key))?;
+                    value.split('"').next().map(str::to_string)
+                };
+                Some(FormPart {
+                    name: param("name")?,
+                    filename: param("filename"),
+                    data: data.trim_end_matches("\r\n").to_string(),
+                })
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn files_are_uploaded_as_numbered_parts_matching_the_attachments() {
+        let message = CreateMessage::new()
+            .content("report")
+            .attachment(AttachmentFile::from_bytes("a.png", "png"))
+            .attachment(
+                AttachmentFile::from_bytes("b.txt", "text")
+                    .spoiler(true)
+                    .description("notes"),
+            );
+        let (payload, files) = message.into_parts().unwrap();
+        let payload_json = serde_json::to_vec(&payload).unwrap();
+
+        let parts = parts(Body::form(&payload_json, &files).unwrap()).await;
+        let names: Vec<&str> = parts.iter().map(|part| part.name.as_str()).collect();
+        assert_eq!(names, ["payload_json", "files[0]", "files[1]"]);
+
+        assert_eq!(parts[0].filename, None);
+        assert_eq!(parts[1].filename.as_deref(), Some("a.png"));
+        assert_eq!(parts[1].data, "png");
+        assert_eq!(parts[2].filename.as_deref(), Some("SPOILER_b.txt"));
+        assert_eq!(parts[2].data, "text");
+
+        // the id of every attachment is the index of its `files[n]` part
+        let payload: Value = serde_json::from_str(&parts[0].data).unwrap();
+        assert_eq!(payload["content"], "report");
+        assert_eq!(
+            payload["attachments"],
+            json!([
+                { "id": "0", "filename": "a.png" },
+                { "id": "1", "filename": "SPOILER_b.txt", "description": "notes" },
+            ])
+        );
+    }
+}
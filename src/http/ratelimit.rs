@@ -0,0 +1,546 @@
+// http/ratelimit.rs
+
+//! Rate limits of the REST API.
+//!
+//! Discord groups routes into buckets, named by the `X-RateLimit-Bucket`
+//! header, and limits each bucket separately per major parameter (channel,
+//! guild or webhook id). Requests of a bucket are sent one after another and
+//! wait for the bucket to reset once its remaining requests are used up.
+//! All requests together stay below the global limit of 50 per second.
+//!
+//! More: <https://discord.com/developers/docs/topics/rate-limits>
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use reqwest::{header::HeaderMap, Method};
+use tokio::{
+    sync::{Mutex, OwnedMutexGuard},
+    time::{sleep_until, Instant},
+};
+
+use super::routing::Route;
+
+/// Requests a bot may send per period across all routes
+const GLOBAL_REQUESTS_PER_PERIOD: u32 = 50;
+
+/// Period of the global limit
+const GLOBAL_PERIOD: Duration = Duration::from_secs(1);
+
+/// How often a request is sent again after a 429 response
+pub const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Longest `Retry-After` waited for before giving up with
+/// [`Error::RateLimited`](crate::Error::RateLimited)
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// The route of a request, identifies its bucket until Discord named it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RouteKey {
+    /// HTTP method, requests of the same path can have different buckets
+    pub method: Method,
+
+    /// Path with ids replaced by placeholders, e.g. `/channels/:id/messages/:id`
+    pub route: String,
+
+    /// The channel, guild or webhook id the bucket is limited by, empty for
+    /// routes without major parameter
+    pub major: String,
+}
+
+impl From<&Route<'_>> for RouteKey {
+    fn from(route: &Route<'_>) -> Self {
+        Self {
+            method: route.method(),
+            route: route.template().to_string(),
+            major: route.major_parameter().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RouteKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.major.is_empty() {
+            write!(f, "{} {}", self.method, self.route)
+        } else {
+            write!(f, "{} {} ({})", self.method, self.route, self.major)
+        }
+    }
+}
+
+/// Rate limit state of a bucket, as reported by
+/// [`RateLimiter::buckets`]
+#[derive(Debug, Clone)]
+pub struct BucketInfo {
+    /// Bucket hash from `X-RateLimit-Bucket`, `None` until Discord sent it
+    pub bucket: Option<String>,
+
+    /// Route of the first request of the bucket
+    pub route: String,
+
+    /// The channel, guild or webhook id the bucket is limited by
+    pub major: String,
+
+    /// Requests allowed per reset
+    pub limit: Option<u32>,
+
+    /// Requests left until the reset
+    pub remaining: Option<u32>,
+
+    /// Time until the bucket resets
+    pub reset_after: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct BucketState {
+    limit: Option<u32>,
+    remaining: Option<u32>,
+    reset_at: Option<Instant>,
+}
+
+impl BucketState {
+    /// When the next request can be sent, `None` if it can be sent now
+    fn next_slot(&mut self, now: Instant) -> Option<Instant> {
+        if let Some(reset_at) = self.reset_at {
+            if reset_at <= now {
+                self.remaining = self.limit;
+                self.reset_at = None;
+            }
+        }
+        match (self.remaining, self.reset_at) {
+            (Some(0), Some(reset_at)) => Some(reset_at),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    hash: StdMutex<Option<String>>,
+    route: String,
+    major: String,
+    /// Held while a request of the bucket is in flight, queues the others
+    queue: Arc<Mutex<()>>,
+    state: StdMutex<BucketState>,
+}
+
+impl Bucket {
+    fn new(key: &RouteKey, hash: Option<String>) -> Self {
+        Self {
+            hash: StdMutex::new(hash),
+            route: format!("{} {}", key.method, key.route),
+            major: key.major.clone(),
+            queue: Arc::new(Mutex::new(())),
+            state: StdMutex::new(BucketState::default()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct GlobalState {
+    /// Set by a global 429 response
+    blocked_until: Option<Instant>,
+    period_start: Instant,
+    sent: u32,
+}
+
+impl GlobalState {
+    /// Count a request and return `None`, or return when it can be sent
+    fn take_slot(&mut self, now: Instant) -> Option<Instant> {
+        if let Some(blocked_until) = self.blocked_until {
+            if blocked_until > now {
+                return Some(blocked_until);
+            }
+            self.blocked_until = None;
+        }
+        if now.duration_since(self.period_start) >= GLOBAL_PERIOD {
+            self.period_start = now;
+            self.sent = 0;
+        }
+        if self.sent >= GLOBAL_REQUESTS_PER_PERIOD {
+            return Some(self.period_start + GLOBAL_PERIOD);
+        }
+        self.sent += 1;
+        None
+    }
+}
+
+/// Permission to send a request, the next request of the bucket waits until
+/// the ticket is dropped
+pub(crate) struct Ticket {
+    bucket: Arc<Bucket>,
+    _queue: OwnedMutexGuard<()>,
+}
+
+/// Tracks the REST rate limits of a bot, shared by all clones of its
+/// [`HTTPClient`](crate::http::HTTPClient)
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Bucket hash of each method and route seen so far, shared by all
+    /// major parameters
+    routes: StdMutex<HashMap<(Method, String), String>>,
+    /// Buckets by hash and major parameter, or by route until the hash is known
+    buckets: StdMutex<HashMap<String, Arc<Bucket>>>,
+    global: StdMutex<GlobalState>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            routes: StdMutex::new(HashMap::new()),
+            buckets: StdMutex::new(HashMap::new()),
+            global: StdMutex::new(GlobalState {
+                blocked_until: None,
+                period_start: Instant::now(),
+                sent: 0,
+            }),
+        }
+    }
+
+    /// State of every bucket used so far
+    pub fn buckets(&self) -> Vec<BucketInfo> {
+        let now = Instant::now();
+        let mut buckets: Vec<Arc<Bucket>> =
+            self.buckets.lock().unwrap().values().cloned().collect();
+        // a bucket is listed under its route until the hash is known
+        buckets.sort_by_key(|bucket| Arc::as_ptr(bucket) as usize);
+        buckets.dedup_by(|a, b| Arc::ptr_eq(a, b));
+        buckets
+            .into_iter()
+            .map(|bucket| {
+                let state = bucket.state.lock().unwrap();
+                BucketInfo {
+                    bucket: bucket.hash.lock().unwrap().clone(),
+                    route: bucket.route.clone(),
+                    major: bucket.major.clone(),
+                    limit: state.limit,
+                    remaining: state.remaining,
+                    reset_after: state
+                        .reset_at
+                        .map(|reset_at| reset_at.saturating_duration_since(now)),
+                }
+            })
+            .collect()
+    }
+
+    /// Time until requests may be sent again after hitting the global limit
+    pub fn global_reset_after(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.global
+            .lock()
+            .unwrap()
+            .blocked_until
+            .filter(|blocked_until| *blocked_until > now)
+            .map(|blocked_until| blocked_until - now)
+    }
+
+    fn bucket(&self, key: &RouteKey) -> Arc<Bucket> {
+        let hash = self
+            .routes
+            .lock()
+            .unwrap()
+            .get(&(key.method.clone(), key.route.clone()))
+            .cloned();
+        let bucket_key = match &hash {
+            Some(hash) => format!("{}:{}", hash, key.major),
+            None => key.to_string(),
+        };
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(bucket_key)
+            .or_insert_with(|| Arc::new(Bucket::new(key, hash)))
+            .clone()
+    }
+
+    /// Wait until a request to `key` can be sent
+    pub(crate) async fn acquire(&self, key: &RouteKey) -> Ticket {
+        let bucket = self.bucket(key);
+        let queue = bucket.queue.clone().lock_owned().await;
+        let ticket = Ticket {
+            bucket,
+            _queue: queue,
+        };
+        self.wait(key, &ticket).await;
+        ticket
+    }
+
+    /// Wait until the bucket of `ticket` and the global limit have room for
+    /// another request, e.g. to retry a request after a 429 response
+    pub(crate) async fn wait(&self, key: &RouteKey, ticket: &Ticket) {
+        loop {
+            let next_slot = ticket
+                .bucket
+                .state
+                .lock()
+                .unwrap()
+                .next_slot(Instant::now());
+            let next_slot =
+                next_slot.or_else(|| self.global.lock().unwrap().take_slot(Instant::now()));
+            match next_slot {
+                Some(next_slot) => {
+                    log::debug!(
+                        "⏳ Waiting {:?} for the rate limit of {}",
+                        next_slot.saturating_duration_since(Instant::now()),
+                        key
+                    );
+                    sleep_until(next_slot).await;
+                }
+                None => break,
+            }
+        }
+        if let Some(remaining) = &mut ticket.bucket.state.lock().unwrap().remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+
+    /// Update the bucket of a sent request from the `X-RateLimit-*` headers
+    /// of its response
+    pub(crate) fn update(&self, key: &RouteKey, ticket: &Ticket, headers: &HeaderMap) {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let now = Instant::now();
+
+        if let Some(hash) = header("x-ratelimit-bucket") {
+            self.routes
+                .lock()
+                .unwrap()
+                .insert((key.method.clone(), key.route.clone()), hash.to_string());
+            let mut bucket_hash = ticket.bucket.hash.lock().unwrap();
+            if bucket_hash.as_deref() != Some(hash) {
+                *bucket_hash = Some(hash.to_string());
+                // later requests of the route find the bucket by its hash
+                self.buckets
+                    .lock()
+                    .unwrap()
+                    .entry(format!("{}:{}", hash, key.major))
+                    .or_insert_with(|| ticket.bucket.clone());
+            }
+        }
+
+        let mut state = ticket.bucket.state.lock().unwrap();
+        if let Some(limit) = header("x-ratelimit-limit").and_then(|limit| limit.parse().ok()) {
+            state.limit = Some(limit);
+        }
+        if let Some(remaining) =
+            header("x-ratelimit-remaining").and_then(|remaining| remaining.parse().ok())
+        {
+            state.remaining = Some(remaining);
+        }
+        // an unusable value such as `inf` is ignored like a missing header
+        if let Some(reset_after) = header("x-ratelimit-reset-after")
+            .and_then(|reset_after| reset_after.parse::<f64>().ok())
+            .and_then(|reset_after| Duration::try_from_secs_f64(reset_after.max(0.0)).ok())
+        {
+            state.reset_at = Some(now + reset_after);
+        }
+    }
+
+    /// Block the bucket, or every request when `global`, after a 429 response
+    pub(crate) fn rate_limited(&self, ticket: &Ticket, retry_after: Duration, global: bool) {
+        let until = Instant::now() + retry_after;
+        if global {
+            self.global.lock().unwrap().blocked_until = Some(until);
+        } else {
+            let mut state = ticket.bucket.state.lock().unwrap();
+            state.remaining = Some(0);
+            state.reset_at = Some(until);
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    use super::*;
+
+    fn key(route: &str, major: &str) -> RouteKey {
+        RouteKey {
+            method: Method::GET,
+            route: route.to_string(),
+            major: major.to_string(),
+        }
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(
+                HeaderName::from_static(name),
+                HeaderValue::from_str(value).unwrap(),
+            );
+        }
+        headers
+    }
+
+    /// Send a request to `key` and answer it with `response` headers
+    async fn request(limiter: &RateLimiter, key: &RouteKey, response: &HeaderMap) {
+        let ticket = limiter.acquire(key).await;
+        limiter.update(key, &ticket, response);
+    }
+
+    /// Headers of a response that used up bucket `hash` for `reset_after` seconds
+    fn exhausted(hash: &str, reset_after: &str) -> HeaderMap {
+        headers(&[
+            ("x-ratelimit-bucket", hash),
+            ("x-ratelimit-limit", "5"),
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset-after", reset_after),
+        ])
+    }
+
+    #[test]
+    fn bucket_state_refills_once_reset() {
+        let now = Instant::now();
+        let mut state = BucketState {
+            limit: Some(5),
+            remaining: Some(0),
+            reset_at: Some(now + Duration::from_secs(1)),
+        };
+        assert_eq!(state.next_slot(now), Some(now + Duration::from_secs(1)));
+
+        assert_eq!(state.next_slot(now + Duration::from_secs(1)), None);
+        assert_eq!(state.remaining, Some(5));
+        assert_eq!(state.reset_at, None);
+    }
+
+    #[test]
+    fn bucket_state_with_remaining_requests_does_not_wait() {
+        let now = Instant::now();
+        let mut state = BucketState {
+            limit: Some(5),
+            remaining: Some(1),
+            reset_at: Some(now + Duration::from_secs(1)),
+        };
+        assert_eq!(state.next_slot(now), None);
+        assert_eq!(BucketState::default().next_slot(now), None);
+    }
+
+    #[test]
+    fn global_limit_rolls_over_each_period() {
+        let start = Instant::now();
+        let mut global = GlobalState {
+            blocked_until: None,
+            period_start: start,
+            sent: 0,
+        };
+        for _ in 0..GLOBAL_REQUESTS_PER_PERIOD {
+            assert_eq!(global.take_slot(start), None);
+        }
+        assert_eq!(global.take_slot(start), Some(start + GLOBAL_PERIOD));
+
+        let next_period = start + GLOBAL_PERIOD;
+        assert_eq!(global.take_slot(next_period), None);
+        assert_eq!(global.period_start, next_period);
+        assert_eq!(global.sent, 1);
+    }
+
+    #[test]
+    fn global_block_holds_every_request() {
+        let start = Instant::now();
+        let mut global = GlobalState {
+            blocked_until: Some(start + Duration::from_secs(2)),
+            period_start: start,
+            sent: 0,
+        };
+        assert_eq!(
+            global.take_slot(start),
+            Some(start + Duration::from_secs(2))
+        );
+        assert_eq!(global.sent, 0);
+
+        assert_eq!(global.take_slot(start + Duration::from_secs(2)), None);
+        assert_eq!(global.blocked_until, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn routes_with_the_same_hash_share_a_bucket() {
+        let limiter = RateLimiter::new();
+        let messages = key("/channels/:id/messages", "1");
+        let message = key("/channels/:id/messages/:id", "1");
+        request(
+            &limiter,
+            &messages,
+            &headers(&[("x-ratelimit-bucket", "abc")]),
+        )
+        .await;
+        request(
+            &limiter,
+            &message,
+            &headers(&[("x-ratelimit-bucket", "abc")]),
+        )
+        .await;
+        assert_eq!(limiter.buckets().len(), 2);
+
+        // both routes now resolve to the bucket first named "abc"
+        request(&limiter, &messages, &exhausted("abc", "10")).await;
+        let start = Instant::now();
+        request(&limiter, &message, &headers(&[])).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+
+        let info = limiter
+            .buckets()
+            .into_iter()
+            .find(|bucket| bucket.route == "GET /channels/:id/messages")
+            .unwrap();
+        assert_eq!(info.bucket.as_deref(), Some("abc"));
+        assert_eq!(info.limit, Some(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn major_parameters_are_limited_separately() {
+        let limiter = RateLimiter::new();
+        let channel_1 = key("/channels/:id/messages", "1");
+        let channel_2 = key("/channels/:id/messages", "2");
+        request(&limiter, &channel_1, &exhausted("abc", "10")).await;
+
+        let start = Instant::now();
+        request(&limiter, &channel_2, &headers(&[])).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        request(&limiter, &channel_1, &headers(&[])).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn global_rate_limit_blocks_other_routes() {
+        let limiter = RateLimiter::new();
+        let messages = key("/channels/:id/messages", "1");
+        let ticket = limiter.acquire(&messages).await;
+        limiter.rate_limited(&ticket, Duration::from_secs(2), true);
+        drop(ticket);
+        assert_eq!(limiter.global_reset_after(), Some(Duration::from_secs(2)));
+
+        let start = Instant::now();
+        request(&limiter, &key("/users/@me", ""), &headers(&[])).await;
+        assert_eq!(start.elapsed(), Duration::from_secs(2));
+        assert_eq!(limiter.global_reset_after(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn unusable_reset_after_is_ignored() {
+        let limiter = RateLimiter::new();
+        let messages = key("/channels/:id/messages", "1");
+        for reset_after in ["inf", "1e300"] {
+            request(&limiter, &messages, &exhausted("abc", reset_after)).await;
+        }
+        let bucket = limiter.buckets().pop().unwrap();
+        assert_eq!(bucket.remaining, Some(0));
+        assert_eq!(bucket.reset_after, None);
+
+        // without a reset time the bucket does not hold requests back
+        let start = Instant::now();
+        request(&limiter, &messages, &headers(&[])).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,204 @@
+// http/routing.rs
+
+use reqwest::Method;
+
+/// An endpoint of the REST API
+///
+/// More: <https://discord.com/developers/docs/reference#http-api>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route<'a> {
+    /// `GET /users/@me`
+    ///
+    /// More: <https://discord.com/developers/docs/resources/user#get-current-user>
+    GetCurrentUser,
+
+    /// `GET /gateway`
+    ///
+    /// More: <https://discord.com/developers/docs/events/gateway#get-gateway>
+    GetGateway,
+
+    /// `GET /gateway/bot`
+    ///
+    /// More: <https://discord.com/developers/docs/events/gateway#get-gateway-bot>
+    GetGatewayBot,
+
+    /// `POST /channels/{channel.id}/messages`
+    ///
+    /// More: <https://discord.com/developers/docs/resources/message#create-message>
+    CreateMessage { channel_id: &'a str },
+
+    /// `PATCH /channels/{channel.id}/messages/{message.id}`
+    ///
+    /// More: <https://discord.com/developers/docs/resources/message#edit-message>
+    EditMessage {
+        channel_id: &'a str,
+        message_id: &'a str,
+    },
+
+    /// `DELETE /channels/{channel.id}/messages/{message.id}`
+    ///
+    /// More: <https://discord.com/developers/docs/resources/message#delete-message>
+    DeleteMessage {
+        channel_id: &'a str,
+        message_id: &'a str,
+    },
+}
+
+impl Route<'_> {
+    /// HTTP method of the route
+    pub fn method(&self) -> Method {
+        match self {
+            Self::GetCurrentUser | Self::GetGateway | Self::GetGatewayBot => Method::GET,
+            Self::CreateMessage { .. } => Method::POST,
+            Self::EditMessage { .. } => Method::PATCH,
+            Self::DeleteMessage { .. } => Method::DELETE,
+        }
+    }
+
+    /// Path relative to the versioned API url, e.g. `/channels/1/messages`
+    pub fn path(&self) -> String {
+        match self {
+            Self::GetCurrentUser => "/users/@me".to_string(),
+            Self::GetGateway => "/gateway".to_string(),
+            Self::GetGatewayBot => "/gateway/bot".to_string(),
+            Self::CreateMessage { channel_id } => format!("/channels/{}/messages", channel_id),
+            Self::EditMessage {
+                channel_id,
+                message_id,
+            }
+            | Self::DeleteMessage {
+                channel_id,
+                message_id,
+            } => format!("/channels/{}/messages/{}", channel_id, message_id),
+        }
+    }
+
+    /// Path with the parameters left out, e.g. `/channels/:id/messages`.
+    ///
+    /// Requests to the same template share a rate limit bucket per major parameter.
+    pub fn template(&self) -> &'static str {
+        match self {
+            Self::GetCurrentUser => "/users/@me",
+            Self::GetGateway => "/gateway",
+            Self::GetGatewayBot => "/gateway/bot",
+            Self::CreateMessage { .. } => "/channels/:id/messages",
+            Self::EditMessage { .. } | Self::DeleteMessage { .. } => "/channels/:id/messages/:id",
+        }
+    }
+
+    /// The channel, guild or webhook id the rate limits of the route are split by
+    ///
+    /// More: <https://discord.com/developers/docs/topics/rate-limits#rate-limits>
+    pub fn major_parameter(&self) -> Option<&str> {
+        match self {
+            Self::GetCurrentUser | Self::GetGateway | Self::GetGatewayBot => None,
+            Self::CreateMessage { channel_id }
+            | Self::EditMessage { channel_id, .. }
+            | Self::DeleteMessage { channel_id, .. } => Some(channel_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every route with its method, path, template and major parameter
+    fn routes() -> Vec<(
+        Route<'static>,
+        Method,
+        &'static str,
+        &'static str,
+        Option<&'static str>,
+    )> {
+        vec![
+            (
+                Route::GetCurrentUser,
+                Method::GET,
+                "/users/@me",
+                "/users/@me",
+                None,
+            ),
+            (Route::GetGateway, Method::GET, "/gateway", "/gateway", None),
+            (
+                Route::GetGatewayBot,
+                Method::GET,
+                "/gateway/bot",
+                "/gateway/bot",
+                None,
+            ),
+            (
+                Route::CreateMessage { channel_id: "10" },
+                Method::POST,
+                "/channels/10/messages",
+                "/channels/:id/messages",
+                Some("10"),
+            ),
+            (
+                Route::EditMessage {
+                    channel_id: "10",
+                    message_id: "20",
+                },
+                Method::PATCH,
+                "/channels/10/messages/20",
+                "/channels/:id/messages/:id",
+                Some("10"),
+            ),
+            (
+                Route::DeleteMessage {
+                    channel_id: "10",
+                    message_id: "20",
+                },
+                Method::DELETE,
+                "/channels/10/messages/20",
+                "/channels/:id/messages/:id",
+                Some("10"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn routes_map_to_method_path_template_and_major_parameter() {
+        for (route, method, path, template, major) in routes() {
+            assert_eq!(route.method(), method, "{:?}", route);
+            assert_eq!(route.path(), path, "{:?}", route);
+            assert_eq!(route.template(), template, "{:?}", route);
+            assert_eq!(route.major_parameter(), major, "{:?}", route);
+        }
+    }
+
+    #[test]
+    fn templates_replace_every_id_of_the_path() {
+        for (route, ..) in routes() {
+            let path = route.path();
+            let path: Vec<&str> = path.split('/').collect();
+            let template: Vec<&str> = route.template().split('/').collect();
+            assert_eq!(path.len(), template.len(), "{:?}", route);
+            for (segment, placeholder) in path.iter().zip(&template) {
+                if *placeholder == ":id" {
+                    assert!(segment.chars().all(|c| c.is_ascii_digit()), "{:?}", route);
+                } else {
+                    assert_eq!(segment, placeholder, "{:?}", route);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn messages_share_a_template_across_channels_and_ids() {
+        let edit = |channel_id, message_id| Route::EditMessage {
+            channel_id,
+            message_id,
+        };
+        // the message id does not split the bucket, the channel id does
+        assert_eq!(edit("10", "20").template(), edit("11", "21").template());
+        assert_eq!(
+            edit("10", "20").major_parameter(),
+            edit("10", "21").major_parameter()
+        );
+        assert_ne!(
+            edit("10", "20").major_parameter(),
+            edit("11", "20").major_parameter()
+        );
+    }
+}
@@ -16,7 +16,7 @@
 //! ✅ Message handling implemented  
 //! ✅ Prefix command system functional  
 //! ❌ Slash commands not implemented  
-//! ❌ Voice support not available  
+//! 🚧 Voice support experimental (sending Opus audio)  
 //! ❌ API stability not guaranteed  
 //!
 //! ## Example Usage
@@ -30,9 +30,11 @@
 //!     let intents = intents::GUILDS | intents::GUILD_MESSAGES | intents::MESSAGE_CONTENT;
 //!     
 //!     let mut bot = BotBase::new(Some(intents)).await;
-//!     bot.login(token).await;
-//!     bot.connect(Some(intents), Some(true)).await;
-//!     
+//!     bot.login(token).await?;
+//!     bot.connect(Some(intents), Some(true)).await?;
+//!
+//!     // runs until the shards are shut down or one hits a fatal error
+//!     bot.wait_for_shards().await?;
 //!     Ok(())
 //! }
 //! ```
@@ -41,6 +43,7 @@
 
 pub mod bot;
 pub mod client;
+pub mod error;
 pub mod http;
 pub mod logger;
 pub mod response;
@@ -55,3 +58,9 @@ pub mod handlers;
 pub mod message;
 pub mod models;
 pub mod prefix;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "voice")]
+pub mod voice;
+
+pub use error::{Error, Result};
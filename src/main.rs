@@ -3,7 +3,7 @@
 use rustycord::{client::Client, logger::setup_logger};
 
 #[tokio::main]
-async fn main() {
+async fn main() -> rustycord::Result<()> {
     // Initialize logging (optional)
     let _ = setup_logger("debug".to_string());
 
@@ -12,10 +12,10 @@ async fn main() {
 
     // Login to Discord
     let token = "Token".to_string();
-    client.login(token).await;
+    client.login(token).await?;
 
     // Fetch gateway information
-    let gateway_url = client.get_gateway().await;
+    let gateway_url = client.get_gateway().await?;
     log::info!("Gateway URL: {}", gateway_url);
 
     // Determine the number of shards
@@ -23,13 +23,11 @@ async fn main() {
     let total_shards = 2; // Example: 2 shards
 
     // Initialize shards
-    client.initialize_shards(total_shards).await;
+    client.initialize_shards(total_shards).await?;
 
     // Start shards
-    client.start_shards(Some(513), None).await;
+    client.start_shards(Some(513), None).await?;
 
-    // Keep the main task alive
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
-    }
+    // Keep the main task alive until the shards stop
+    client.wait_for_shards().await
 }
@@ -1,11 +1,14 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
-use crate::{application::Application, embeds::Embed, models::user::User};
+use crate::{
+    application::Application, client::Client, embeds::Embed, error::Result, http::MessageResponse,
+    models::user::User,
+};
 
 /// Represent a role tags
 ///
 /// More: <https://discord.com/developers/docs/topics/permissions#role-object-role-tags-structure>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RoleTags {
     /// the id of the bot this role belongs to
     pub bot_id: Option<String>,
@@ -20,7 +23,7 @@ pub struct RoleTags {
 /// Represent a role in a guild
 ///
 /// More: <https://discord.com/developers/docs/topics/permissions#role-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Role {
     /// The id of the role
     pub id: String,
@@ -44,7 +47,7 @@ pub struct Role {
     pub position: i32,
 
     /// permission bit set
-    pub permissions: i32,
+    pub permissions: String,
 
     /// whether this role is managed by an integration
     pub managed: bool,
@@ -63,7 +66,7 @@ pub struct Role {
 /// Represent an attachment in a message
 ///
 /// More: <https://discord.com/developers/docs/resources/channel#attachment-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Attachments {
     /// the id of the attachment
     pub id: String,
@@ -109,7 +112,7 @@ pub struct Attachments {
 }
 
 /// Represent a emoji reaction to a message
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Reaction {
     /// Total number of times this emoji has been used to react (including super reacts)
     pub count: i32,
@@ -131,7 +134,7 @@ pub struct Reaction {
 }
 
 /// Reaction count details object
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReactionCountDetails {
     /// count of super reacts
     pub burst: i32,
@@ -142,7 +145,7 @@ pub struct ReactionCountDetails {
 
 /// Emoji information
 /// More: <https://discord.com/developers/docs/resources/emoji#emoji-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Emoji {
     /// emoji id
     pub id: Option<String>,
@@ -170,7 +173,7 @@ pub struct Emoji {
 }
 
 /// Represent a message activity
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MessageActivity {
     /// type of message activity
     ///
@@ -188,16 +191,30 @@ pub struct MessageActivity {
     pub party_id: Option<String>,
 }
 
+/// Deserialize a field of a message that is not needed to handle it, a
+/// payload that does not fit the model falls back to the default instead of
+/// failing the whole message
+fn lenient<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value)
+        .inspect_err(|err| log::warn!("⚠️ Ignoring message field that failed to parse: {}", err))
+        .unwrap_or_default())
+}
+
 /// Represent a message sent in a channel
 ///
 ///
 ///
 /// - Note: Field specific to the `MESSAGE_CREATE` and `MESSAGE_UPDATE` events are listed in the Gateway Documentation
 /// - Note: An app will receive empty values in the `content`, `embeds`, `attachement` and `component` fields while `poll`
-/// will be ommited if they have not configured the `MESSAGE_CONTENT` intent
+///   will be ommited if they have not configured the `MESSAGE_CONTENT` intent
 ///
 /// More: <https://discord.com/developers/docs/resources/message#message-object>
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ChannelMessage {
     /// The id of the message
     pub id: String,
@@ -237,21 +254,27 @@ pub struct ChannelMessage {
     pub mention_everyone: bool,
 
     /// users specifically mentioned in the message
+    #[serde(default, deserialize_with = "lenient")]
     pub mentions: Vec<User>,
 
-    /// roles specifically mentioned in this message
-    pub mention_roles: Vec<Role>,
+    /// ids of the roles specifically mentioned in this message
+    #[serde(default, deserialize_with = "lenient")]
+    pub mention_roles: Vec<String>,
 
     /// attachments sent with the message
+    #[serde(default, deserialize_with = "lenient")]
     pub attachments: Vec<Attachments>,
 
     /// any embedded content
+    #[serde(default, deserialize_with = "lenient")]
     pub embeds: Vec<Embed>,
 
     /// reactions to the message
+    #[serde(default, deserialize_with = "lenient")]
     pub reactions: Option<Vec<Reaction>>,
 
     /// used for validating a message was sent
+    #[serde(default, deserialize_with = "lenient")]
     pub nonce: Option<String>,
 
     /// whether this message is pinned
@@ -266,9 +289,11 @@ pub struct ChannelMessage {
     pub r#type: i32,
 
     /// sent with Rich Presence-related chat embeds
+    #[serde(default, deserialize_with = "lenient")]
     pub activity: Option<MessageActivity>,
 
     /// application of the message
+    #[serde(default, deserialize_with = "lenient")]
     pub application: Option<Application>,
 
     /// application id
@@ -287,6 +312,7 @@ pub struct ChannelMessage {
 /// - Note: Field specific to the `MESSAGE_CREATE` and `MESSAGE_UPDATE` events are listed in the Gateway Documentation
 /// - Note: An app will receive empty values in the `content`, `embeds`, `attachement` and `component` fields while `poll`
 impl ChannelMessage {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: String,
         channel_id: String,
@@ -296,7 +322,7 @@ impl ChannelMessage {
         tts: bool,
         mention_everyone: bool,
         mentions: Vec<User>,
-        mention_roles: Vec<Role>,
+        mention_roles: Vec<String>,
         attachments: Vec<Attachments>,
         embeds: Vec<Embed>,
         reactions: Option<Vec<Reaction>>,
@@ -334,7 +360,130 @@ impl ChannelMessage {
         }
     }
 
-    pub async fn send(&self, message: String) -> () {
-        println!("Message sent: {}", message);
+    /// Send a message to the channel of this message
+    pub async fn send(&self, client: &Client, content: &str) -> Result<MessageResponse> {
+        client.send_message(&self.channel_id, content, None).await
+    }
+
+    /// Reply to this message
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use rustycord::{client::Client, message::ChannelMessage};
+    ///
+    /// # async fn on_message(message: &ChannelMessage, client: &Client) -> rustycord::Result<()> {
+    /// let reply = message.reply(client, "Checking...").await?;
+    /// client
+    ///     .edit_message(&reply.channel_id, &reply.id, Some("All good ✅"), None)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reply(&self, client: &Client, content: &str) -> Result<MessageResponse> {
+        client
+            .reply(&self.channel_id, &self.id, content, None)
+            .await
+    }
+
+    /// Edit the content of this message, only messages of the bot can be edited
+    pub async fn edit(&self, client: &Client, content: &str) -> Result<MessageResponse> {
+        client
+            .edit_message(&self.channel_id, &self.id, Some(content), None)
+            .await
+    }
+
+    /// Delete this message
+    pub async fn delete(&self, client: &Client) -> Result<()> {
+        client.delete_message(&self.channel_id, &self.id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn message() -> Value {
+        json!({
+            "id": "1235246372938752010",
+            "channel_id": "1041103498457534464",
+            "author": {
+                "id": "80351110224678912",
+                "username": "nelly",
+                "discriminator": "0",
+                "global_name": "Nelly",
+                "avatar": null,
+                "public_flags": 64,
+            },
+            "content": "!ping",
+            "timestamp": "2024-05-01T10:00:00.000000+00:00",
+            "edited_timestamp": null,
+            "tts": false,
+            "mention_everyone": false,
+            "mentions": [],
+            "mention_roles": [],
+            "attachments": [],
+            "embeds": [],
+            "nonce": "1235246372451909632",
+            "pinned": false,
+            "type": 0,
+            "flags": 0,
+        })
+    }
+
+    #[test]
+    fn keeps_optional_fields_that_fit_the_model() {
+        let message: ChannelMessage = serde_json::from_value(message()).unwrap();
+        assert_eq!(message.content, "!ping");
+        assert_eq!(message.nonce.as_deref(), Some("1235246372451909632"));
+    }
+
+    #[test]
+    fn falls_back_to_defaults_for_fields_that_do_not_fit_the_model() {
+        let mut voice_message = message();
+        // integer nonces and attachments without content type are sent by
+        // Discord but not covered by the model
+        voice_message["nonce"] = json!(1235246372451909632u64);
+        voice_message["attachments"] = json!([{
+            "id": "1235246373194600528",
+            "filename": "voice-message.ogg",
+            "size": 8253,
+            "url": "https://cdn.discordapp.com/attachments/1/2/voice-message.ogg",
+            "duration_secs": 2.1,
+            "waveform": "AAAAAAAAAAAA",
+        }]);
+        voice_message["mentions"] = json!([{ "id": 1 }]);
+
+        let message: ChannelMessage = serde_json::from_value(voice_message).unwrap();
+        assert_eq!(message.id, "1235246372938752010");
+        assert!(message.nonce.is_none());
+        assert!(message.attachments.is_empty());
+        assert!(message.mentions.is_empty());
+    }
+
+    #[test]
+    fn missing_optional_fields_use_their_default() {
+        let mut sparse = message();
+        for field in [
+            "mentions",
+            "mention_roles",
+            "attachments",
+            "embeds",
+            "nonce",
+        ] {
+            sparse.as_object_mut().unwrap().remove(field);
+        }
+        let message: ChannelMessage = serde_json::from_value(sparse).unwrap();
+        assert!(message.embeds.is_empty());
+        assert!(message.nonce.is_none());
+    }
+
+    #[test]
+    fn required_fields_still_fail_the_message() {
+        let mut broken = message();
+        broken["author"] = json!("nelly");
+        assert!(serde_json::from_value::<ChannelMessage>(broken).is_err());
     }
 }
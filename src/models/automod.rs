@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+/// Represent an Auto Moderation rule of a guild
+///
+/// More: <https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-rule-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutoModerationRule {
+    /// the id of this rule
+    pub id: String,
+
+    /// the id of the guild which this rule belongs to
+    pub guild_id: String,
+
+    /// the rule name
+    pub name: String,
+
+    /// the user which first created this rule
+    pub creator_id: String,
+
+    /// the rule event type
+    pub event_type: u8,
+
+    /// the rule trigger type
+    pub trigger_type: u8,
+
+    /// the rule trigger metadata
+    pub trigger_metadata: Option<serde_json::Value>,
+
+    /// the actions which will execute when the rule is triggered
+    #[serde(default)]
+    pub actions: Vec<AutoModerationAction>,
+
+    /// whether the rule is enabled
+    pub enabled: bool,
+
+    /// the role ids that should not be affected by the rule (Maximum of 20)
+    #[serde(default)]
+    pub exempt_roles: Vec<String>,
+
+    /// the channel ids that should not be affected by the rule (Maximum of 50)
+    #[serde(default)]
+    pub exempt_channels: Vec<String>,
+}
+
+/// An action which will execute whenever a rule is triggered
+///
+/// More: <https://discord.com/developers/docs/resources/auto-moderation#auto-moderation-action-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AutoModerationAction {
+    /// the type of action
+    pub r#type: u8,
+
+    /// additional metadata needed during execution for this specific action type
+    pub metadata: Option<serde_json::Value>,
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{guild::GuildMember, user::User};
+
+/// Represent a guild or DM channel within Discord, threads are channels too.
+///
+/// More: <https://discord.com/developers/docs/resources/channel#channel-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Channel {
+    /// the id of this channel
+    pub id: String,
+
+    /// the type of channel
+    ///
+    /// More: <https://discord.com/developers/docs/resources/channel#channel-object-channel-types>
+    pub r#type: u8,
+
+    /// the id of the guild (may be missing for some channel objects received over gateway guild dispatches)
+    pub guild_id: Option<String>,
+
+    /// sorting position of the channel
+    pub position: Option<i32>,
+
+    /// explicit permission overwrites for members and roles
+    #[serde(default)]
+    pub permission_overwrites: Vec<PermissionOverwrite>,
+
+    /// the name of the channel (1-100 characters)
+    pub name: Option<String>,
+
+    /// the channel topic
+    pub topic: Option<String>,
+
+    /// whether the channel is nsfw
+    pub nsfw: Option<bool>,
+
+    /// the id of the last message sent in this channel (or thread for forum channels)
+    pub last_message_id: Option<String>,
+
+    /// the bitrate (in bits) of the voice channel
+    pub bitrate: Option<u32>,
+
+    /// the user limit of the voice channel
+    pub user_limit: Option<u32>,
+
+    /// amount of seconds a user has to wait before sending another message (0-21600)
+    pub rate_limit_per_user: Option<u32>,
+
+    /// the recipients of the DM
+    #[serde(default)]
+    pub recipients: Vec<User>,
+
+    /// icon hash of the group DM
+    pub icon: Option<String>,
+
+    /// id of the creator of the group DM or thread
+    pub owner_id: Option<String>,
+
+    /// application id of the group DM creator if it is bot-created
+    pub application_id: Option<String>,
+
+    /// for guild channels: id of the parent category, for threads: id of the text channel this thread was created in
+    pub parent_id: Option<String>,
+
+    /// when the last pinned message was pinned
+    pub last_pin_timestamp: Option<String>,
+
+    /// voice region id for the voice channel, automatic when set to null
+    pub rtc_region: Option<String>,
+
+    /// number of messages (not including the initial message or deleted messages) in a thread
+    pub message_count: Option<u32>,
+
+    /// an approximate count of users in a thread, stops counting at 50
+    pub member_count: Option<u32>,
+
+    /// thread-specific fields not needed by other channels
+    pub thread_metadata: Option<ThreadMetadata>,
+
+    /// thread member object for the current user, if they have joined the thread
+    pub member: Option<ThreadMember>,
+
+    /// default duration, in minutes, that threads will stop showing in the channel list after inactivity
+    pub default_auto_archive_duration: Option<u32>,
+
+    /// computed permissions for the invoking user in the channel, including overwrites
+    pub permissions: Option<String>,
+
+    /// channel flags combined as a bitfield
+    #[serde(default)]
+    pub flags: i32,
+
+    /// the IDs of the set of tags that have been applied to a thread in a forum channel
+    #[serde(default)]
+    pub applied_tags: Vec<String>,
+
+    /// whether the thread was just created, only sent in THREAD_CREATE
+    pub newly_created: Option<bool>,
+}
+
+/// Explicit permission overwrite for a role or member
+///
+/// More: <https://discord.com/developers/docs/resources/channel#overwrite-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PermissionOverwrite {
+    /// role or user id
+    pub id: String,
+
+    /// either 0 (role) or 1 (member)
+    pub r#type: u8,
+
+    /// permission bit set
+    pub allow: String,
+
+    /// permission bit set
+    pub deny: String,
+}
+
+/// Thread-specific fields
+///
+/// More: <https://discord.com/developers/docs/resources/channel#thread-metadata-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadMetadata {
+    /// whether the thread is archived
+    pub archived: bool,
+
+    /// the thread will stop showing in the channel list after `auto_archive_duration` minutes of inactivity
+    pub auto_archive_duration: u32,
+
+    /// timestamp when the thread's archive status was last changed
+    pub archive_timestamp: String,
+
+    /// whether the thread is locked
+    pub locked: bool,
+
+    /// whether non-moderators can add other non-moderators to a private thread
+    pub invitable: Option<bool>,
+
+    /// timestamp when the thread was created
+    pub create_timestamp: Option<String>,
+}
+
+/// A user that has joined a thread
+///
+/// More: <https://discord.com/developers/docs/resources/channel#thread-member-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadMember {
+    /// id of the thread
+    pub id: Option<String>,
+
+    /// id of the user
+    pub user_id: Option<String>,
+
+    /// time the user last joined the thread
+    pub join_timestamp: String,
+
+    /// user-thread settings, currently only used for notifications
+    #[serde(default)]
+    pub flags: i32,
+
+    /// guild member object of the user
+    pub member: Option<GuildMember>,
+
+    /// id of the guild, only sent in THREAD_MEMBER_UPDATE
+    pub guild_id: Option<String>,
+}
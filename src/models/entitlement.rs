@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Represent that a user or guild has access to a premium offering in your application
+///
+/// More: <https://discord.com/developers/docs/resources/entitlement#entitlement-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Entitlement {
+    /// ID of the entitlement
+    pub id: String,
+
+    /// ID of the SKU
+    pub sku_id: String,
+
+    /// ID of the parent application
+    pub application_id: String,
+
+    /// ID of the user that is granted access to the entitlement's sku
+    pub user_id: Option<String>,
+
+    /// type of entitlement
+    pub r#type: u8,
+
+    /// entitlement was deleted
+    #[serde(default)]
+    pub deleted: bool,
+
+    /// start date at which the entitlement is valid
+    pub starts_at: Option<String>,
+
+    /// date at which the entitlement is no longer valid
+    pub ends_at: Option<String>,
+
+    /// ID of the guild that is granted access to the entitlement's sku
+    pub guild_id: Option<String>,
+
+    /// for consumable items, whether or not the entitlement has been consumed
+    pub consumed: Option<bool>,
+}
@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    application::Application,
+    message::{Emoji, Role},
+    models::{
+        channel::Channel,
+        user::{Presence, User},
+        voice::VoiceState,
+    },
+};
+
+/// Represent an isolated collection of users and channels, often referred to as "servers" in the UI.
+///
+/// Fields only sent in `GUILD_CREATE` are empty or `None` otherwise.
+///
+/// More: <https://discord.com/developers/docs/resources/guild#guild-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Guild {
+    /// guild id
+    pub id: String,
+
+    /// guild name (2-100 characters, excluding trailing and leading whitespace)
+    pub name: String,
+
+    /// icon hash
+    pub icon: Option<String>,
+
+    /// splash hash
+    pub splash: Option<String>,
+
+    /// discovery splash hash, only present for guilds with the "DISCOVERABLE" feature
+    pub discovery_splash: Option<String>,
+
+    /// id of owner
+    pub owner_id: Option<String>,
+
+    /// id of afk channel
+    pub afk_channel_id: Option<String>,
+
+    /// afk timeout in seconds
+    pub afk_timeout: Option<u32>,
+
+    /// verification level required for the guild
+    pub verification_level: Option<u8>,
+
+    /// default message notifications level
+    pub default_message_notifications: Option<u8>,
+
+    /// explicit content filter level
+    pub explicit_content_filter: Option<u8>,
+
+    /// roles in the guild
+    #[serde(default)]
+    pub roles: Vec<Role>,
+
+    /// custom guild emojis
+    #[serde(default)]
+    pub emojis: Vec<Emoji>,
+
+    /// enabled guild features
+    #[serde(default)]
+    pub features: Vec<String>,
+
+    /// required MFA level for the guild
+    pub mfa_level: Option<u8>,
+
+    /// application id of the guild creator if it is bot-created
+    pub application_id: Option<String>,
+
+    /// the id of the channel where guild notices such as welcome messages and boost events are posted
+    pub system_channel_id: Option<String>,
+
+    /// the id of the channel where Community guilds can display rules and/or guidelines
+    pub rules_channel_id: Option<String>,
+
+    /// the description of a guild
+    pub description: Option<String>,
+
+    /// banner hash
+    pub banner: Option<String>,
+
+    /// premium tier (Server Boost level)
+    pub premium_tier: Option<u8>,
+
+    /// the number of boosts this guild currently has
+    pub premium_subscription_count: Option<u32>,
+
+    /// the preferred locale of a Community guild
+    pub preferred_locale: Option<String>,
+
+    /// guild NSFW level
+    pub nsfw_level: Option<u8>,
+
+    /// custom guild stickers
+    #[serde(default)]
+    pub stickers: Vec<Sticker>,
+
+    /// when this guild was joined at, only sent in GUILD_CREATE
+    pub joined_at: Option<String>,
+
+    /// true if this is considered a large guild, only sent in GUILD_CREATE
+    pub large: Option<bool>,
+
+    /// true if this guild is unavailable due to an outage
+    pub unavailable: Option<bool>,
+
+    /// total number of members in this guild, only sent in GUILD_CREATE
+    pub member_count: Option<u64>,
+
+    /// states of members currently in voice channels, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub voice_states: Vec<VoiceState>,
+
+    /// users in the guild, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub members: Vec<GuildMember>,
+
+    /// channels in the guild, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub channels: Vec<Channel>,
+
+    /// all active threads in the guild that current user has permission to view, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub threads: Vec<Channel>,
+
+    /// presences of the members in the guild, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub presences: Vec<Presence>,
+
+    /// stage instances in the guild, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub stage_instances: Vec<StageInstance>,
+
+    /// scheduled events in the guild, only sent in GUILD_CREATE
+    #[serde(default)]
+    pub guild_scheduled_events: Vec<GuildScheduledEvent>,
+}
+
+/// A partial guild object, sent when a guild is not available yet or was left
+///
+/// More: <https://discord.com/developers/docs/resources/guild#unavailable-guild-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnavailableGuild {
+    /// guild id
+    pub id: String,
+
+    /// true when the guild is unavailable due to an outage,
+    /// not set when the user was removed from the guild
+    pub unavailable: Option<bool>,
+}
+
+/// Represent a user within a guild
+///
+/// More: <https://discord.com/developers/docs/resources/guild#guild-member-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildMember {
+    /// the user this guild member represents
+    pub user: Option<User>,
+
+    /// this user's guild nickname
+    pub nick: Option<String>,
+
+    /// the member's guild avatar hash
+    pub avatar: Option<String>,
+
+    /// array of role object ids
+    #[serde(default)]
+    pub roles: Vec<String>,
+
+    /// when the user joined the guild
+    pub joined_at: Option<String>,
+
+    /// when the user started boosting the guild
+    pub premium_since: Option<String>,
+
+    /// whether the user is deafened in voice channels
+    pub deaf: Option<bool>,
+
+    /// whether the user is muted in voice channels
+    pub mute: Option<bool>,
+
+    /// guild member flags represented as a bit set
+    #[serde(default)]
+    pub flags: i32,
+
+    /// whether the user has not yet passed the guild's Membership Screening requirements
+    pub pending: Option<bool>,
+
+    /// total permissions of the member in the channel, including overwrites
+    pub permissions: Option<String>,
+
+    /// when the user's timeout will expire and the user will be able to communicate in the guild again
+    pub communication_disabled_until: Option<String>,
+
+    /// id of the guild, only sent in GUILD_MEMBER_ADD and GUILD_MEMBER_UPDATE
+    pub guild_id: Option<String>,
+}
+
+/// Represent a sticker that can be sent in messages
+///
+/// More: <https://discord.com/developers/docs/resources/sticker#sticker-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Sticker {
+    /// id of the sticker
+    pub id: String,
+
+    /// for standard stickers, id of the pack the sticker is from
+    pub pack_id: Option<String>,
+
+    /// name of the sticker
+    pub name: String,
+
+    /// description of the sticker
+    pub description: Option<String>,
+
+    /// autocomplete/suggestion tags for the sticker (max 200 characters)
+    #[serde(default)]
+    pub tags: String,
+
+    /// type of sticker
+    pub r#type: u8,
+
+    /// type of sticker format
+    pub format_type: u8,
+
+    /// whether this guild sticker can be used, may be false due to loss of Server Boosts
+    pub available: Option<bool>,
+
+    /// id of the guild that owns this sticker
+    pub guild_id: Option<String>,
+
+    /// the user that uploaded the guild sticker
+    pub user: Option<User>,
+
+    /// the standard sticker's sort order within its pack
+    pub sort_value: Option<i32>,
+}
+
+/// Represent a live stage
+///
+/// More: <https://discord.com/developers/docs/resources/stage-instance#stage-instance-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StageInstance {
+    /// the id of this stage instance
+    pub id: String,
+
+    /// the guild id of the associated stage channel
+    pub guild_id: String,
+
+    /// the id of the associated stage channel
+    pub channel_id: String,
+
+    /// the topic of the stage instance (1-120 characters)
+    pub topic: String,
+
+    /// the privacy level of the stage instance
+    pub privacy_level: u8,
+
+    /// whether or not stage discovery is disabled (deprecated)
+    pub discoverable_disabled: Option<bool>,
+
+    /// the id of the scheduled event for this stage instance
+    pub guild_scheduled_event_id: Option<String>,
+}
+
+/// Represent a scheduled event in a guild
+///
+/// More: <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildScheduledEvent {
+    /// the id of the scheduled event
+    pub id: String,
+
+    /// the guild id which the scheduled event belongs to
+    pub guild_id: String,
+
+    /// the channel id in which the scheduled event will be hosted
+    pub channel_id: Option<String>,
+
+    /// the id of the user that created the scheduled event
+    pub creator_id: Option<String>,
+
+    /// the name of the scheduled event (1-100 characters)
+    pub name: String,
+
+    /// the description of the scheduled event (1-1000 characters)
+    pub description: Option<String>,
+
+    /// the time the scheduled event will start
+    pub scheduled_start_time: String,
+
+    /// the time the scheduled event will end, required if entity_type is EXTERNAL
+    pub scheduled_end_time: Option<String>,
+
+    /// the privacy level of the scheduled event
+    pub privacy_level: u8,
+
+    /// the status of the scheduled event
+    pub status: u8,
+
+    /// the type of the scheduled event
+    pub entity_type: u8,
+
+    /// the id of an entity associated with a guild scheduled event
+    pub entity_id: Option<String>,
+
+    /// additional metadata for the guild scheduled event
+    pub entity_metadata: Option<GuildScheduledEventMetadata>,
+
+    /// the user that created the scheduled event
+    pub creator: Option<User>,
+
+    /// the number of users subscribed to the scheduled event
+    pub user_count: Option<u32>,
+
+    /// the cover image hash of the scheduled event
+    pub image: Option<String>,
+}
+
+/// Additional metadata of a guild scheduled event
+///
+/// More: <https://discord.com/developers/docs/resources/guild-scheduled-event#guild-scheduled-event-object-guild-scheduled-event-entity-metadata>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildScheduledEventMetadata {
+    /// location of the event (1-100 characters)
+    pub location: Option<String>,
+}
+
+/// Represent a guild integration
+///
+/// More: <https://discord.com/developers/docs/resources/guild#integration-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Integration {
+    /// integration id
+    pub id: String,
+
+    /// integration name
+    pub name: String,
+
+    /// integration type (twitch, youtube, discord, or guild_subscription)
+    pub r#type: String,
+
+    /// is this integration enabled
+    pub enabled: Option<bool>,
+
+    /// is this integration syncing
+    pub syncing: Option<bool>,
+
+    /// id that this integration uses for "subscribers"
+    pub role_id: Option<String>,
+
+    /// whether emoticons should be synced for this integration (twitch only currently)
+    pub enable_emoticons: Option<bool>,
+
+    /// the behavior of expiring subscribers
+    pub expire_behavior: Option<u8>,
+
+    /// the grace period (in days) before expiring subscribers
+    pub expire_grace_period: Option<u32>,
+
+    /// user for this integration
+    pub user: Option<User>,
+
+    /// integration account information
+    pub account: IntegrationAccount,
+
+    /// when this integration was last synced
+    pub synced_at: Option<String>,
+
+    /// how many subscribers this integration has
+    pub subscriber_count: Option<u32>,
+
+    /// has this integration been revoked
+    pub revoked: Option<bool>,
+
+    /// the bot/OAuth2 application for discord integrations
+    pub application: Option<Application>,
+
+    /// the scopes the application has been authorized for
+    #[serde(default)]
+    pub scopes: Vec<String>,
+
+    /// id of the guild, only sent in INTEGRATION_CREATE and INTEGRATION_UPDATE
+    pub guild_id: Option<String>,
+}
+
+/// Account of an integration
+///
+/// More: <https://discord.com/developers/docs/resources/guild#integration-account-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntegrationAccount {
+    /// id of the account
+    pub id: String,
+
+    /// name of the account
+    pub name: String,
+}
+
+/// Represent an administrative action performed in a guild
+///
+/// More: <https://discord.com/developers/docs/resources/audit-log#audit-log-entry-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLogEntry {
+    /// id of the entry
+    pub id: String,
+
+    /// id of the affected entity (webhook, user, role, etc.)
+    pub target_id: Option<String>,
+
+    /// changes made to the target_id
+    #[serde(default)]
+    pub changes: Vec<AuditLogChange>,
+
+    /// user or app that made the changes
+    pub user_id: Option<String>,
+
+    /// type of action that occurred
+    pub action_type: u16,
+
+    /// additional info for certain event types
+    pub options: Option<serde_json::Value>,
+
+    /// reason for the change (1-512 characters)
+    pub reason: Option<String>,
+
+    /// id of the guild, only sent in GUILD_AUDIT_LOG_ENTRY_CREATE
+    pub guild_id: Option<String>,
+}
+
+/// A single change of an audit log entry
+///
+/// More: <https://discord.com/developers/docs/resources/audit-log#audit-log-change-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLogChange {
+    /// new value of the key
+    pub new_value: Option<serde_json::Value>,
+
+    /// old value of the key
+    pub old_value: Option<serde_json::Value>,
+
+    /// name of the changed entity
+    pub key: String,
+}
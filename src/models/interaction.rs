@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    message::ChannelMessage,
+    models::{channel::Channel, entitlement::Entitlement, guild::GuildMember, user::User},
+};
+
+/// Represent the message an application receives when a user uses an
+/// application command or a message component.
+///
+/// The interaction `data` is kept as raw json until slash commands are implemented.
+///
+/// More: <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Interaction {
+    /// ID of the interaction
+    pub id: String,
+
+    /// ID of the application this interaction is for
+    pub application_id: String,
+
+    /// type of interaction
+    ///
+    /// More: <https://discord.com/developers/docs/interactions/receiving-and-responding#interaction-object-interaction-type>
+    pub r#type: u8,
+
+    /// interaction data payload
+    pub data: Option<serde_json::Value>,
+
+    /// guild that the interaction was sent from
+    pub guild_id: Option<String>,
+
+    /// channel that the interaction was sent from
+    pub channel: Option<Channel>,
+
+    /// channel that the interaction was sent from
+    pub channel_id: Option<String>,
+
+    /// guild member data for the invoking user, including permissions
+    pub member: Option<GuildMember>,
+
+    /// user object for the invoking user, if invoked in a DM
+    pub user: Option<User>,
+
+    /// continuation token for responding to the interaction
+    pub token: String,
+
+    /// read-only property, always 1
+    pub version: u8,
+
+    /// for components, the message they were attached to
+    pub message: Option<ChannelMessage>,
+
+    /// bitwise set of permissions the app has in the source location of the interaction
+    pub app_permissions: Option<String>,
+
+    /// selected language of the invoking user
+    pub locale: Option<String>,
+
+    /// guild's preferred locale, if invoked in a guild
+    pub guild_locale: Option<String>,
+
+    /// for monetized apps, any entitlements for the invoking user
+    #[serde(default)]
+    pub entitlements: Vec<Entitlement>,
+
+    /// context where the interaction was triggered from
+    pub context: Option<u8>,
+}
+
+/// Permissions of an application command in a guild
+///
+/// More: <https://discord.com/developers/docs/interactions/application-commands#application-command-permissions-object-guild-application-command-permissions-structure>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuildApplicationCommandPermissions {
+    /// ID of the command or the application ID
+    pub id: String,
+
+    /// ID of the application the command belongs to
+    pub application_id: String,
+
+    /// ID of the guild
+    pub guild_id: String,
+
+    /// permissions for the command in the guild, max of 100
+    #[serde(default)]
+    pub permissions: Vec<ApplicationCommandPermission>,
+}
+
+/// A single application command permission
+///
+/// More: <https://discord.com/developers/docs/interactions/application-commands#application-command-permissions-object-application-command-permissions-structure>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApplicationCommandPermission {
+    /// ID of the role, user, or channel
+    pub id: String,
+
+    /// role (1), user (2), or channel (3)
+    pub r#type: u8,
+
+    /// true to allow, false, to disallow
+    pub permission: bool,
+}
@@ -1 +1,7 @@
+pub mod automod;
+pub mod channel;
+pub mod entitlement;
+pub mod guild;
+pub mod interaction;
 pub mod user;
+pub mod voice;
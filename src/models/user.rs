@@ -1,5 +1,7 @@
 use serde::{self, Deserialize, Serialize};
 
+use crate::gateway::gateway::Activity;
+
 /// User model for the Discord API.
 ///
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -13,7 +15,7 @@ pub struct User {
 
     pub global_name: Option<String>,
 
-    pub avatar: String,
+    pub avatar: Option<String>,
 
     pub avatar_description: Option<String>,
 
@@ -37,10 +39,13 @@ pub struct User {
     pub flags: i32,
     #[serde(default)]
     pub premium_type: PremiumType,
+    #[serde(default)]
     pub public_flags: i32,
 }
 
+/// Discord sends the premium type as an integer
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(from = "u8", into = "u8")]
 pub enum PremiumType {
     #[default]
     None = 0,
@@ -49,6 +54,67 @@ pub enum PremiumType {
     NitroBasic = 3,
 }
 
+impl From<u8> for PremiumType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => PremiumType::NitroClassic,
+            2 => PremiumType::Nitro,
+            3 => PremiumType::NitroBasic,
+            _ => PremiumType::None,
+        }
+    }
+}
+
+impl From<PremiumType> for u8 {
+    fn from(value: PremiumType) -> Self {
+        value as u8
+    }
+}
+
+/// The user a presence belongs to, only the id is guaranteed to be sent.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PresenceUser {
+    /// id of the user
+    pub id: String,
+}
+
+/// Active sessions of a user, indicated by an application-level status.
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#client-status-object>
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ClientStatus {
+    /// status on a desktop application session
+    pub desktop: Option<String>,
+
+    /// status on a mobile application session
+    pub mobile: Option<String>,
+
+    /// status on a web browser or bot user session
+    pub web: Option<String>,
+}
+
+/// A user's presence in a guild.
+///
+/// More: <https://discord.com/developers/docs/events/gateway-events#presence-update>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Presence {
+    /// user whose presence is being updated
+    pub user: PresenceUser,
+
+    /// id of the guild
+    pub guild_id: Option<String>,
+
+    /// either "idle", "dnd", "online", or "offline"
+    pub status: String,
+
+    /// user's current activities
+    #[serde(default)]
+    pub activities: Vec<Activity>,
+
+    /// user's platform-dependent status
+    pub client_status: Option<ClientStatus>,
+}
+
 // #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 // pub enum UserPublicFlags {
 //     DiscordEmployee = 1 << 0,
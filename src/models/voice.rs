@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::guild::GuildMember;
+
+/// Represent a user's voice connection status
+///
+/// More: <https://discord.com/developers/docs/resources/voice#voice-state-object>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VoiceState {
+    /// the guild id this voice state is for
+    pub guild_id: Option<String>,
+
+    /// the channel id this user is connected to, `None` when the user left
+    pub channel_id: Option<String>,
+
+    /// the user id this voice state is for
+    pub user_id: String,
+
+    /// the guild member this voice state is for
+    pub member: Option<GuildMember>,
+
+    /// the session id for this voice state
+    pub session_id: String,
+
+    /// whether this user is deafened by the server
+    pub deaf: bool,
+
+    /// whether this user is muted by the server
+    pub mute: bool,
+
+    /// whether this user is locally deafened
+    pub self_deaf: bool,
+
+    /// whether this user is locally muted
+    pub self_mute: bool,
+
+    /// whether this user is streaming using "Go Live"
+    pub self_stream: Option<bool>,
+
+    /// whether this user's camera is enabled
+    pub self_video: bool,
+
+    /// whether this user's permission to speak is denied
+    pub suppress: bool,
+
+    /// the time at which the user requested to speak
+    pub request_to_speak_timestamp: Option<String>,
+}
@@ -0,0 +1,186 @@
+// testing/gateway.rs
+
+use std::sync::{atomic::Ordering, Arc};
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_tungstenite::{
+    accept_async,
+    tungstenite::{
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
+};
+
+use super::{bot_user, MockSession, MockState};
+
+/// Accept gateway connections until the mock is dropped
+pub(super) async fn serve(listener: TcpListener, state: Arc<MockState>, gateway_url: String) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        let gateway_url = gateway_url.clone();
+        tokio::spawn(async move {
+            handle_connection(stream, state, &gateway_url).await;
+        });
+    }
+}
+
+/// Run one gateway connection: HELLO, then answer heartbeats, IDENTIFY and RESUME
+async fn handle_connection(stream: TcpStream, state: Arc<MockState>, gateway_url: &str) {
+    let ws = match accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(err) => {
+            log::debug!("🧪 Mock gateway handshake failed: {}", err);
+            return;
+        }
+    };
+    let connection_id = state.next_id();
+    state.connections.fetch_add(1, Ordering::Relaxed);
+    let (mut sink, mut stream) = ws.split();
+    let (sender, mut outgoing) = mpsc::unbounded_channel::<Message>();
+
+    // dispatches of the test are sent from other tasks
+    let writer = tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            let is_close = matches!(message, Message::Close(_));
+            if sink.send(message).await.is_err() || is_close {
+                break;
+            }
+        }
+    });
+
+    let interval = state.heartbeat_interval.load(Ordering::Relaxed);
+    send(
+        &sender,
+        json!({ "op": 10, "d": { "heartbeat_interval": interval } }),
+    );
+
+    while let Some(Ok(message)) = stream.next().await {
+        let payload: Value = match message {
+            Message::Text(text) => match serde_json::from_str(text.as_str()) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    log::debug!("🧪 Mock gateway got invalid json: {}", err);
+                    continue;
+                }
+            },
+            Message::Close(frame) => {
+                let code = frame.map(|frame| u16::from(frame.code));
+                log::debug!("🧪 Mock gateway received close frame {:?}", code);
+                state.close_codes.lock().await.push(code);
+                break;
+            }
+            _ => continue,
+        };
+        log::debug!("🧪 Mock gateway received op {}", payload["op"]);
+
+        match payload["op"].as_u64() {
+            // heartbeat
+            Some(1) => send(&sender, json!({ "op": 11 })),
+            // identify
+            Some(2) => {
+                let session_id = format!("mock-session-{}", state.next_id());
+                state.session_ids.lock().await.insert(session_id.clone());
+                let ready = json!({
+                    "v": 10,
+                    "user": bot_user(),
+                    "guilds": [],
+                    "session_id": session_id,
+                    "resume_gateway_url": gateway_url,
+                    "shard": payload["d"]["shard"],
+                    "application": { "id": bot_user()["id"], "flags": 0 },
+                });
+                start_session(&state, connection_id, &sender, 0, "READY", ready).await;
+            }
+            // resume
+            Some(6) => {
+                let session_id = payload["d"]["session_id"].as_str().unwrap_or_default();
+                if state.session_ids.lock().await.contains(session_id) {
+                    let sequence = payload["d"]["seq"].as_i64().unwrap_or(0);
+                    start_session(
+                        &state,
+                        connection_id,
+                        &sender,
+                        sequence,
+                        "RESUMED",
+                        json!({}),
+                    )
+                    .await;
+                } else {
+                    send(&sender, json!({ "op": 9, "d": false }));
+                }
+            }
+            _ => {}
+        }
+        state.commands.lock().await.push(payload);
+        state.changed.notify_waiters();
+    }
+
+    state
+        .sessions
+        .lock()
+        .await
+        .retain(|session| session.id != connection_id);
+    state.connections.fetch_sub(1, Ordering::Relaxed);
+    state.changed.notify_waiters();
+    drop(sender);
+    let _ = writer.await;
+}
+
+/// Send READY or RESUMED and make the connection receive dispatches
+async fn start_session(
+    state: &MockState,
+    connection_id: u64,
+    sender: &mpsc::UnboundedSender<Message>,
+    sequence: i64,
+    event_name: &str,
+    data: Value,
+) {
+    let mut sessions = state.sessions.lock().await;
+    sessions.retain(|session| session.id != connection_id);
+    let mut session = MockSession {
+        id: connection_id,
+        sender: sender.clone(),
+        sequence,
+    };
+    send_dispatch(&mut session, event_name, data);
+    sessions.push(session);
+}
+
+/// Send a dispatch to every session, returns how many sessions got it
+pub(super) async fn dispatch(state: &MockState, event_name: &str, data: Value) -> usize {
+    let mut sessions = state.sessions.lock().await;
+    sessions.retain(|session| !session.sender.is_closed());
+    for session in sessions.iter_mut() {
+        send_dispatch(session, event_name, data.clone());
+    }
+    sessions.len()
+}
+
+/// Close every connection with `code`
+pub(super) async fn close_sessions(state: &MockState, code: u16) {
+    for session in state.sessions.lock().await.drain(..) {
+        let _ = session.sender.send(Message::Close(Some(CloseFrame {
+            code: CloseCode::from(code),
+            reason: "closed by mock".into(),
+        })));
+    }
+    state.changed.notify_waiters();
+}
+
+fn send_dispatch(session: &mut MockSession, event_name: &str, data: Value) {
+    session.sequence += 1;
+    send(
+        &session.sender,
+        json!({ "op": 0, "s": session.sequence, "t": event_name, "d": data }),
+    );
+}
+
+fn send(sender: &mpsc::UnboundedSender<Message>, payload: Value) {
+    // the connection may already be closed
+    let _ = sender.send(Message::Text(payload.to_string().into()));
+}
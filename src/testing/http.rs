@@ -0,0 +1,431 @@
+// testing/http.rs
+
+use std::{collections::HashMap, sync::Arc};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+use super::{bot_user, message, route_key, MockState};
+
+/// Canned response of the mock REST API
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// HTTP status code
+    pub status: u16,
+
+    /// Additional headers, e.g. rate limit headers
+    pub headers: Vec<(String, String)>,
+
+    /// Json body
+    pub body: Value,
+}
+
+impl MockResponse {
+    /// Response with a json body
+    pub fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// Error response in the format of the Discord API
+    ///
+    /// More: <https://discord.com/developers/docs/reference#error-messages>
+    pub fn error(status: u16, code: u32, message: &str) -> Self {
+        Self::json(status, json!({ "code": code, "message": message }))
+    }
+
+    /// Add a header to the response
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A request received by the mock REST API
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// HTTP method, e.g. `POST`
+    pub method: String,
+
+    /// Path relative to the versioned API url, e.g. `/channels/1/messages`
+    pub path: String,
+
+    /// Query string without the leading `?`
+    pub query: Option<String>,
+
+    /// Headers with lowercase names
+    pub headers: HashMap<String, String>,
+
+    /// Raw request body
+    pub body: Vec<u8>,
+}
+
+/// A part of a `multipart/form-data` request body
+#[derive(Debug, Clone)]
+pub struct FormPart {
+    /// Name of the form field, e.g. `payload_json` or `files[0]`
+    pub name: String,
+
+    /// Filename of an uploaded file
+    pub filename: Option<String>,
+
+    /// Content type of the part
+    pub content_type: Option<String>,
+
+    /// Content of the part
+    pub data: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// The body parsed as json, `None` if it is not json.
+    ///
+    /// For `multipart/form-data` bodies this is the `payload_json` part.
+    pub fn json(&self) -> Option<Value> {
+        match self
+            .parts()
+            .into_iter()
+            .find(|part| part.name == "payload_json")
+        {
+            Some(part) => serde_json::from_slice(&part.data).ok(),
+            None => serde_json::from_slice(&self.body).ok(),
+        }
+    }
+
+    /// Parts of a `multipart/form-data` body, empty for other bodies
+    pub fn parts(&self) -> Vec<FormPart> {
+        let Some(boundary) = self
+            .header("content-type")
+            .filter(|content_type| content_type.starts_with("multipart/form-data"))
+            .and_then(|content_type| content_type.split_once("boundary="))
+            .map(|(_, boundary)| format!("--{}", boundary.trim_matches('"')))
+        else {
+            return Vec::new();
+        };
+
+        let mut parts = Vec::new();
+        // every part is framed by `--boundary\r\n` and `\r\n--boundary`
+        for section in split_bytes(&self.body, boundary.as_bytes())
+            .into_iter()
+            .skip(1)
+        {
+            let Some(section) = section.strip_prefix(b"\r\n") else {
+                // the closing `--boundary--`
+                break;
+            };
+            let Some(header_end) = find_bytes(section, b"\r\n\r\n") else {
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&section[..header_end]);
+            let data = &section[header_end + 4..];
+            let data = data.strip_suffix(b"\r\n").unwrap_or(data);
+
+            let mut part = FormPart {
+                name: String::new(),
+                filename: None,
+                content_type: None,
+                data: data.to_vec(),
+            };
+            for line in headers.lines() {
+                let Some((name, value)) = line.split_once(':') else {
+                    continue;
+                };
+                match name.trim().to_ascii_lowercase().as_str() {
+                    "content-disposition" => {
+                        part.name = disposition_param(value, "name").unwrap_or_default();
+                        part.filename = disposition_param(value, "filename");
+                    }
+                    "content-type" => part.content_type = Some(value.trim().to_string()),
+                    _ => {}
+                }
+            }
+            parts.push(part);
+        }
+        parts
+    }
+
+    /// Value of a header, the name is matched case insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// Position of `needle` in `haystack`
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Split `bytes` at every occurrence of `separator`
+fn split_bytes<'a>(mut bytes: &'a [u8], separator: &[u8]) -> Vec<&'a [u8]> {
+    let mut sections = Vec::new();
+    while let Some(position) = find_bytes(bytes, separator) {
+        sections.push(&bytes[..position]);
+        bytes = &bytes[position + separator.len()..];
+    }
+    sections.push(bytes);
+    sections
+}
+
+/// Quoted parameter of a `Content-Disposition` header, e.g. `name="files[0]"`
+fn disposition_param(value: &str, param: &str) -> Option<String> {
+    value.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        (name == param).then(|| value.trim_matches('"').to_string())
+    })
+}
+
+/// Accept REST connections until the mock is dropped
+pub(super) async fn serve(listener: TcpListener, state: Arc<MockState>, gateway_url: String) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let state = state.clone();
+        let gateway_url = gateway_url.clone();
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(stream, &state, &gateway_url).await {
+                log::debug!("🧪 Mock HTTP connection closed: {}", err);
+            }
+        });
+    }
+}
+
+/// Answer the requests of a keep-alive connection
+async fn handle_connection(
+    stream: TcpStream,
+    state: &MockState,
+    gateway_url: &str,
+) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    while let Some(request) = read_request(&mut stream).await? {
+        log::debug!("🧪 Mock HTTP {} {}", request.method, request.path);
+        let response = respond(&request, state, gateway_url).await;
+        state.requests.lock().await.push(request);
+        state.changed.notify_waiters();
+
+        let body = match response.status {
+            204 => String::new(),
+            _ => response.body.to_string(),
+        };
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n",
+            response.status,
+            reason(response.status),
+            body.len()
+        );
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        let stream = stream.get_mut();
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(body.as_bytes()).await?;
+        stream.flush().await?;
+    }
+    Ok(())
+}
+
+/// Read the next request, `None` once the client closed the connection
+async fn read_request(
+    stream: &mut BufReader<TcpStream>,
+) -> std::io::Result<Option<RecordedRequest>> {
+    let mut request_line = String::new();
+    if stream.read_line(&mut request_line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_string())),
+        None => (target, None),
+    };
+    Ok(Some(RecordedRequest {
+        method,
+        path: strip_api_prefix(path).to_string(),
+        query,
+        headers,
+        body,
+    }))
+}
+
+/// Remove the `/api/v{version}` prefix of a request path
+fn strip_api_prefix(path: &str) -> &str {
+    let path = path.strip_prefix("/api").unwrap_or(path);
+    match path.strip_prefix("/v") {
+        Some(rest) => {
+            let version_end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if version_end == 0 {
+                path
+            } else {
+                &rest[version_end..]
+            }
+        }
+        None => path,
+    }
+}
+
+/// Pick the canned response for a request
+async fn respond(request: &RecordedRequest, state: &MockState, gateway_url: &str) -> MockResponse {
+    let key = route_key(&request.method, &request.path);
+    if let Some(response) = state
+        .queued_responses
+        .lock()
+        .await
+        .get_mut(&key)
+        .and_then(|queue| queue.pop_front())
+    {
+        return response;
+    }
+    if let Some(response) = state.responses.lock().await.get(&key) {
+        return response.clone();
+    }
+    default_response(request, state, gateway_url)
+}
+
+/// Responses of the routes the library needs to start a bot and manage messages
+fn default_response(
+    request: &RecordedRequest,
+    state: &MockState,
+    gateway_url: &str,
+) -> MockResponse {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["users", "@me"]) => MockResponse::json(200, bot_user()),
+        ("GET", ["gateway"]) => MockResponse::json(200, json!({ "url": gateway_url })),
+        ("GET", ["gateway", "bot"]) => MockResponse::json(
+            200,
+            json!({
+                "url": gateway_url,
+                "shards": 1,
+                "session_start_limit": {
+                    "total": 1000,
+                    "remaining": 1000,
+                    "reset_after": 0,
+                    "max_concurrency": 1,
+                },
+            }),
+        ),
+        ("POST", ["channels", channel_id, "messages"]) => {
+            let content = request
+                .json()
+                .and_then(|body| body["content"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            let mut sent = message(state.next_id(), channel_id, &content, bot_user());
+            if let Some(body) = request.json() {
+                if body["embeds"].is_array() {
+                    sent["embeds"] = body["embeds"].clone();
+                }
+                if body["message_reference"].is_object() {
+                    sent["message_reference"] = body["message_reference"].clone();
+                    sent["type"] = json!(19);
+                }
+                sent["attachments"] = uploaded_attachments(request, &body, state);
+            }
+            MockResponse::json(200, sent)
+        }
+        ("PATCH", ["channels", channel_id, "messages", message_id]) => {
+            let body = request.json().unwrap_or_default();
+            let id = message_id.parse().unwrap_or_default();
+            let content = body["content"].as_str().unwrap_or_default();
+            let mut edited = message(id, channel_id, content, bot_user());
+            if body["embeds"].is_array() {
+                edited["embeds"] = body["embeds"].clone();
+            }
+            edited["edited_timestamp"] = json!("2024-01-01T00:00:01.000000+00:00");
+            MockResponse::json(200, edited)
+        }
+        ("DELETE", ["channels", _, "messages", _]) => MockResponse::json(204, Value::Null),
+        _ => MockResponse::error(404, 0, "404: Not Found"),
+    }
+}
+
+/// Attachments of the files uploaded as `files[n]`, described by the
+/// `attachments` of the json body
+fn uploaded_attachments(request: &RecordedRequest, body: &Value, state: &MockState) -> Value {
+    let attachments = request
+        .parts()
+        .into_iter()
+        .filter_map(|part| {
+            let index = part.name.strip_prefix("files[")?.strip_suffix(']')?;
+            let metadata = body["attachments"]
+                .as_array()
+                .and_then(|attachments| {
+                    attachments
+                        .iter()
+                        .find(|attachment| attachment["id"].as_str() == Some(index))
+                })
+                .cloned()
+                .unwrap_or_default();
+            let filename = metadata["filename"]
+                .as_str()
+                .map(str::to_string)
+                .or(part.filename)
+                .unwrap_or_default();
+            let id = state.next_id();
+            let url = format!("https://cdn.discordapp.com/attachments/{}/{}", id, filename);
+            let mut attachment = json!({
+                "id": id.to_string(),
+                "filename": filename,
+                "content_type": part
+                    .content_type
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+                "size": part.data.len(),
+                "url": url,
+                "proxy_url": url,
+            });
+            if metadata["description"].is_string() {
+                attachment["description"] = metadata["description"].clone();
+            }
+            Some(attachment)
+        })
+        .collect();
+    Value::Array(attachments)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        _ => "Unknown",
+    }
+}
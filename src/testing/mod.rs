@@ -0,0 +1,412 @@
+//! In-process stand-in of the Discord API for tests
+//!
+//! [`MockDiscord`] runs a local HTTP server answering REST requests with
+//! canned responses and a gateway server going through HELLO, IDENTIFY and
+//! READY, acknowledging heartbeats and sending scripted dispatch events.
+//! Point a [`Client`](crate::client::Client) at it with
+//! [`MockDiscord::endpoints`] to test message handlers and prefix commands
+//! end to end without network access.
+//!
+//! The gateway speaks uncompressed json, leave transport compression and the
+//! ETF encoding disabled on clients connected to it.
+//!
+//! Enabled by the `testing` feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use rustycord::{client::Client, handlers::message_handler::PingPongHandler, testing::MockDiscord};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mock = MockDiscord::start().await;
+//! let mut client = Client::new();
+//! client.set_endpoints(mock.endpoints());
+//! client.login("token".to_string()).await.unwrap();
+//! client
+//!     .get_event_dispatcher()
+//!     .get_message_handlers()
+//!     .add_handler(PingPongHandler)
+//!     .await;
+//! client.ws_connect(Some(0), None, None).await.unwrap();
+//!
+//! assert!(mock.wait_for_ready().await);
+//! mock.dispatch_message_create("1000", "ping").await;
+//! let reply = mock.wait_for_request("POST", "/channels/1000/messages").await.unwrap();
+//! assert_eq!(reply.json().unwrap()["content"], "Pong! 🏓");
+//! # }
+//! ```
+
+mod gateway;
+mod http;
+
+pub use http::{FormPart, MockResponse, RecordedRequest};
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc::UnboundedSender, Mutex, Notify},
+    task::JoinHandle,
+    time::timeout,
+};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::http::Endpoints;
+
+/// How long the `wait_for_*` methods wait before giving up
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// ID of the bot user returned by the mock server
+pub const BOT_USER_ID: &str = "100000000000000001";
+
+/// ID of the user authoring messages of [`MockDiscord::dispatch_message_create`]
+pub const USER_ID: &str = "100000000000000002";
+
+/// ID of the guild of messages of [`MockDiscord::dispatch_message_create`]
+pub const GUILD_ID: &str = "100000000000000003";
+
+/// A gateway session that received READY
+struct MockSession {
+    id: u64,
+    sender: UnboundedSender<Message>,
+    sequence: i64,
+}
+
+/// State shared by the servers and the test
+#[derive(Default)]
+struct MockState {
+    /// Responses for `(method, path)`, used for every matching request
+    responses: Mutex<HashMap<(String, String), MockResponse>>,
+    /// Responses for `(method, path)`, each used for one request before the persistent ones
+    queued_responses: Mutex<HashMap<(String, String), VecDeque<MockResponse>>>,
+    /// REST requests received so far
+    requests: Mutex<Vec<RecordedRequest>>,
+    /// Gateway payloads received so far
+    commands: Mutex<Vec<Value>>,
+    /// Codes of the close frames sent by clients, `None` for a frame without code
+    close_codes: Mutex<Vec<Option<u16>>>,
+    /// Sessions that completed IDENTIFY or RESUME
+    sessions: Mutex<Vec<MockSession>>,
+    /// Session ids handed out in READY, accepted when resuming
+    session_ids: Mutex<HashSet<String>>,
+    /// Gateway connections currently open
+    connections: AtomicUsize,
+    /// Heartbeat interval sent in HELLO, in milliseconds
+    heartbeat_interval: AtomicU64,
+    /// Counter for connection ids, session ids and message ids
+    next_id: AtomicU64,
+    /// Woken whenever something was recorded
+    changed: Notify,
+}
+
+impl MockState {
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Local stand-in of the Discord REST API and gateway.
+///
+/// Both servers stop when the mock is dropped.
+pub struct MockDiscord {
+    http_addr: SocketAddr,
+    gateway_addr: SocketAddr,
+    state: Arc<MockState>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MockDiscord {
+    /// Heartbeat interval sent in HELLO unless changed
+    pub const DEFAULT_HEARTBEAT_INTERVAL: u64 = 41250;
+
+    /// Start the servers on random local ports
+    pub async fn start() -> Self {
+        let http_listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock HTTP server");
+        let gateway_listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock gateway server");
+        let http_addr = http_listener
+            .local_addr()
+            .expect("Mock HTTP server address");
+        let gateway_addr = gateway_listener
+            .local_addr()
+            .expect("Mock gateway server address");
+
+        let state = Arc::new(MockState::default());
+        state
+            .heartbeat_interval
+            .store(Self::DEFAULT_HEARTBEAT_INTERVAL, Ordering::Relaxed);
+
+        let gateway_url = format!("ws://{}", gateway_addr);
+        let tasks = vec![
+            tokio::spawn(http::serve(
+                http_listener,
+                state.clone(),
+                gateway_url.clone(),
+            )),
+            tokio::spawn(gateway::serve(gateway_listener, state.clone(), gateway_url)),
+        ];
+        log::debug!(
+            "🧪 Mock Discord listening on http://{} and ws://{}",
+            http_addr,
+            gateway_addr
+        );
+        Self {
+            http_addr,
+            gateway_addr,
+            state,
+            tasks,
+        }
+    }
+
+    /// Base url of the mock REST API, without version
+    pub fn api_url(&self) -> String {
+        format!("http://{}/api", self.http_addr)
+    }
+
+    /// Url of the mock gateway
+    pub fn gateway_url(&self) -> String {
+        format!("ws://{}", self.gateway_addr)
+    }
+
+    /// Endpoints pointing a client at the mock servers
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints {
+            api_url: self.api_url(),
+            gateway_url: Some(self.gateway_url()),
+            ..Endpoints::default()
+        }
+    }
+
+    /// Set the heartbeat interval sent in HELLO to new connections
+    pub fn set_heartbeat_interval(&self, interval: Duration) {
+        self.state
+            .heartbeat_interval
+            .store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Answer every `method` request to `path` with `response`.
+    ///
+    /// `path` is relative to the versioned API url, e.g. `/channels/1/messages`.
+    pub async fn set_response(&self, method: &str, path: &str, response: MockResponse) {
+        self.state
+            .responses
+            .lock()
+            .await
+            .insert(route_key(method, path), response);
+    }
+
+    /// Answer the next `method` request to `path` with `response`.
+    ///
+    /// Queued responses are used in order before the one set with
+    /// [`set_response`](Self::set_response), e.g. to answer with a rate
+    /// limit once.
+    pub async fn push_response(&self, method: &str, path: &str, response: MockResponse) {
+        self.state
+            .queued_responses
+            .lock()
+            .await
+            .entry(route_key(method, path))
+            .or_default()
+            .push_back(response);
+    }
+
+    /// REST requests received so far
+    pub async fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests.lock().await.clone()
+    }
+
+    /// Gateway payloads received so far
+    pub async fn commands(&self) -> Vec<Value> {
+        self.state.commands.lock().await.clone()
+    }
+
+    /// Codes of the close frames clients sent so far, `None` for a frame without code
+    pub async fn close_codes(&self) -> Vec<Option<u16>> {
+        self.state.close_codes.lock().await.clone()
+    }
+
+    /// Wait until a client sent a close frame, already received ones included
+    pub async fn wait_for_close(&self) -> Option<Option<u16>> {
+        self.wait_until(|| async { self.state.close_codes.lock().await.first().copied() })
+            .await
+            .flatten()
+    }
+
+    /// Number of gateway connections currently open, ready or not
+    pub fn connection_count(&self) -> usize {
+        self.state.connections.load(Ordering::Relaxed)
+    }
+
+    /// Number of gateway sessions that received READY or RESUMED and are still connected
+    pub async fn session_count(&self) -> usize {
+        self.state.sessions.lock().await.len()
+    }
+
+    /// Wait until a gateway session received READY or RESUMED
+    pub async fn wait_for_ready(&self) -> bool {
+        self.wait_until(|| async { self.session_count().await > 0 })
+            .await
+            .is_some()
+    }
+
+    /// Wait for a REST request, already received ones included
+    pub async fn wait_for_request(&self, method: &str, path: &str) -> Option<RecordedRequest> {
+        self.wait_until(|| async {
+            self.state
+                .requests
+                .lock()
+                .await
+                .iter()
+                .find(|request| request.method.eq_ignore_ascii_case(method) && request.path == path)
+                .cloned()
+        })
+        .await
+        .flatten()
+    }
+
+    /// Wait for a gateway payload with opcode `op`, already received ones included
+    pub async fn wait_for_command(&self, op: u8) -> Option<Value> {
+        self.wait_until(|| async {
+            self.state
+                .commands
+                .lock()
+                .await
+                .iter()
+                .find(|command| command["op"] == op)
+                .cloned()
+        })
+        .await
+        .flatten()
+    }
+
+    /// Send a dispatch event to every ready session, returns how many sessions got it
+    pub async fn dispatch(&self, event_name: &str, data: Value) -> usize {
+        gateway::dispatch(&self.state, event_name, data).await
+    }
+
+    /// Dispatch a MESSAGE_CREATE of a user in `channel_id`, returns the message
+    pub async fn dispatch_message_create(&self, channel_id: &str, content: &str) -> Value {
+        let message = message(self.state.next_id(), channel_id, content, user());
+        self.dispatch("MESSAGE_CREATE", message.clone()).await;
+        message
+    }
+
+    /// Close every gateway connection with `code`, e.g. to test reconnecting
+    pub async fn close_sessions(&self, code: u16) {
+        gateway::close_sessions(&self.state, code).await
+    }
+
+    /// Check `condition` whenever something was recorded, until it holds or
+    /// [`WAIT_TIMEOUT`] passed
+    async fn wait_until<F, Fut, T>(&self, condition: F) -> Option<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = T>,
+        T: Satisfied,
+    {
+        timeout(WAIT_TIMEOUT, async {
+            loop {
+                let changed = self.state.changed.notified();
+                let value = condition().await;
+                if value.is_satisfied() {
+                    return value;
+                }
+                changed.await;
+            }
+        })
+        .await
+        .ok()
+    }
+}
+
+impl Drop for MockDiscord {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Whether a `wait_until` condition holds
+trait Satisfied {
+    fn is_satisfied(&self) -> bool;
+}
+
+impl Satisfied for bool {
+    fn is_satisfied(&self) -> bool {
+        *self
+    }
+}
+
+impl<T> Satisfied for Option<T> {
+    fn is_satisfied(&self) -> bool {
+        self.is_some()
+    }
+}
+
+fn route_key(method: &str, path: &str) -> (String, String) {
+    (method.to_ascii_uppercase(), path.to_string())
+}
+
+/// The bot user, as returned by `GET /users/@me`
+pub fn bot_user() -> Value {
+    json!({
+        "id": BOT_USER_ID,
+        "username": "rustycord-test",
+        "discriminator": "0",
+        "global_name": null,
+        "avatar": "",
+        "bot": true,
+        "flags": 0,
+        "public_flags": 0,
+        "mfa_enabled": false,
+        "locale": "en-US",
+        "premium_type": 0,
+        "verified": true,
+    })
+}
+
+/// The user authoring messages of [`MockDiscord::dispatch_message_create`]
+pub fn user() -> Value {
+    json!({
+        "id": USER_ID,
+        "username": "tester",
+        "discriminator": "0",
+        "global_name": "Tester",
+        "avatar": null,
+    })
+}
+
+/// A message object as sent in MESSAGE_CREATE and returned by the REST API
+pub fn message(id: u64, channel_id: &str, content: &str, author: Value) -> Value {
+    json!({
+        "id": id.to_string(),
+        "channel_id": channel_id,
+        "guild_id": GUILD_ID,
+        "author": author,
+        "content": content,
+        "timestamp": "2024-01-01T00:00:00.000000+00:00",
+        "edited_timestamp": null,
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": [],
+        "pinned": false,
+        "type": 0,
+    })
+}
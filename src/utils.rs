@@ -0,0 +1,13 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Return a pseudo random value in the range `[0, 1)`.
+///
+/// Good enough to spread heartbeats and reconnects of many shards apart,
+/// it is **not** suitable for anything security related.
+pub(crate) fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    nanos as f64 / 1_000_000_000.0
+}
@@ -0,0 +1,634 @@
+// voice/connection.rs
+
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::{
+    net::{TcpStream, UdpSocket},
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+    time::Instant,
+};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
+
+use crate::{
+    gateway::voice_state::VoiceConnectionInfo,
+    voice::{
+        payload::{
+            opcode, SelectProtocol, SelectProtocolData, SessionDescription, Speaking, VoiceEvent,
+            VoiceHeartbeat, VoiceHello, VoiceIdentify, VoiceMessage, VoiceReady, ENCRYPTION_MODE,
+            VOICE_GATEWAY_VERSION,
+        },
+        udp::{
+            ip_discovery_request, parse_ip_discovery_response, RtpPacketizer, IP_DISCOVERY_LENGTH,
+            SILENCE_FRAME,
+        },
+    },
+};
+
+type VoiceSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// How long to wait for the IP discovery response
+const IP_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Duration of one Opus frame
+const FRAME_DURATION: Duration = Duration::from_millis(20);
+
+/// Error of a voice connection
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoiceError {
+    /// The websocket or UDP socket failed
+    Connection(String),
+
+    /// The voice gateway closed the websocket
+    Closed { code: u16, reason: String },
+
+    /// The voice server sent something unexpected
+    Protocol(String),
+}
+
+impl std::fmt::Display for VoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VoiceError::Connection(reason) => write!(f, "voice connection failed: {}", reason),
+            VoiceError::Closed { code, reason } => {
+                write!(
+                    f,
+                    "voice gateway closed the connection ({}): {}",
+                    code, reason
+                )
+            }
+            VoiceError::Protocol(reason) => write!(f, "voice protocol error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for VoiceError {}
+
+/// Command sent to the task driving the voice websocket
+enum VoiceCommand {
+    Speaking(bool),
+    Disconnect,
+}
+
+/// A connection to the voice server of a guild, able to send Opus audio.
+///
+/// The voice websocket is driven by a background task that sends heartbeats,
+/// audio is sent over UDP as encrypted RTP packets. Opus frames have to be
+/// encoded beforehand, 20ms per frame.
+///
+/// ```rust,no_run
+/// # async fn example(messenger: rustycord::gateway::messenger::ShardMessenger, frames: Vec<Vec<u8>>) {
+/// use rustycord::voice::connection::VoiceConnection;
+///
+/// let info = messenger.join_voice("guild id", "channel id", false, true).await.unwrap();
+/// let mut voice = VoiceConnection::connect(&info).await.unwrap();
+/// voice.play(frames).await.unwrap();
+/// voice.disconnect().await.unwrap();
+/// messenger.leave_voice("guild id");
+/// # }
+/// ```
+pub struct VoiceConnection {
+    guild_id: String,
+    udp: UdpSocket,
+    packetizer: RtpPacketizer,
+    commands: UnboundedSender<VoiceCommand>,
+    task: JoinHandle<Result<(), VoiceError>>,
+}
+
+impl VoiceConnection {
+    /// Connect to the voice server received from the main gateway
+    pub async fn connect(info: &VoiceConnectionInfo) -> Result<Self, VoiceError> {
+        let endpoint = info
+            .endpoint
+            .trim_start_matches("wss://")
+            .trim_end_matches('/');
+        let url = format!("wss://{}/?v={}", endpoint, VOICE_GATEWAY_VERSION);
+        Self::connect_url(&url, info).await
+    }
+
+    /// Connect to the voice gateway at `url`, which is used as given.
+    ///
+    /// Useful to connect to a local voice server, e.g. `ws://127.0.0.1:8080/?v=8`.
+    pub async fn connect_url(url: &str, info: &VoiceConnectionInfo) -> Result<Self, VoiceError> {
+        log::info!("🔊 Connecting to voice gateway for guild {}", info.guild_id);
+        let (mut ws, _) = connect_async(url)
+            .await
+            .map_err(|err| VoiceError::Connection(err.to_string()))?;
+
+        send(
+            &mut ws,
+            opcode::IDENTIFY,
+            VoiceIdentify {
+                server_id: info.guild_id.clone(),
+                user_id: info.user_id.clone(),
+                session_id: info.session_id.clone(),
+                token: info.token.clone(),
+            },
+        )
+        .await?;
+
+        let mut seq_ack = None;
+        let mut hello = None;
+        let mut ready = None;
+        while hello.is_none() || ready.is_none() {
+            let event = recv(&mut ws, &mut seq_ack).await?;
+            match event.op {
+                opcode::HELLO => hello = Some(parse::<VoiceHello>(event)?),
+                opcode::READY => ready = Some(parse::<VoiceReady>(event)?),
+                op => log::trace!("Ignoring voice opcode {} during handshake", op),
+            }
+        }
+        let (Some(hello), Some(ready)) = (hello, ready) else {
+            unreachable!("loop exits once both payloads are received")
+        };
+        log::debug!(
+            "🔊 Voice ready (ssrc: {}, server: {}:{})",
+            ready.ssrc,
+            ready.ip,
+            ready.port
+        );
+
+        if !ready.modes.iter().any(|mode| mode == ENCRYPTION_MODE) {
+            return Err(VoiceError::Protocol(format!(
+                "voice server does not support {}",
+                ENCRYPTION_MODE
+            )));
+        }
+
+        let udp = UdpSocket::bind("0.0.0.0:0")
+            .await
+            .map_err(|err| VoiceError::Connection(err.to_string()))?;
+        udp.connect((ready.ip.as_str(), ready.port))
+            .await
+            .map_err(|err| VoiceError::Connection(err.to_string()))?;
+        let (address, port) = discover_ip(&udp, ready.ssrc).await?;
+        log::debug!("🔊 Voice IP discovery: {}:{}", address, port);
+
+        send(
+            &mut ws,
+            opcode::SELECT_PROTOCOL,
+            SelectProtocol {
+                protocol: "udp".to_string(),
+                data: SelectProtocolData {
+                    address,
+                    port,
+                    mode: ENCRYPTION_MODE.to_string(),
+                },
+            },
+        )
+        .await?;
+
+        let session = loop {
+            let event = recv(&mut ws, &mut seq_ack).await?;
+            if event.op == opcode::SESSION_DESCRIPTION {
+                break parse::<SessionDescription>(event)?;
+            }
+        };
+        if session.mode != ENCRYPTION_MODE {
+            return Err(VoiceError::Protocol(format!(
+                "voice server selected unsupported mode {}",
+                session.mode
+            )));
+        }
+        let packetizer =
+            RtpPacketizer::new(ready.ssrc, &session.secret_key).map_err(VoiceError::Protocol)?;
+
+        let interval = Duration::from_secs_f64(hello.heartbeat_interval / 1000.0);
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(drive(ws, interval, seq_ack, ready.ssrc, receiver));
+
+        log::info!("✅ Voice connected for guild {}", info.guild_id);
+        Ok(Self {
+            guild_id: info.guild_id.clone(),
+            udp,
+            packetizer,
+            commands,
+            task,
+        })
+    }
+
+    /// ID of the guild this connection belongs to
+    pub fn guild_id(&self) -> &str {
+        &self.guild_id
+    }
+
+    /// SSRC of the audio stream sent by this connection
+    pub fn ssrc(&self) -> u32 {
+        self.packetizer.ssrc()
+    }
+
+    /// Tell the voice server whether audio is being sent
+    pub fn set_speaking(&self, speaking: bool) -> Result<(), VoiceError> {
+        self.commands
+            .send(VoiceCommand::Speaking(speaking))
+            .map_err(|_| VoiceError::Connection("voice connection was closed".to_string()))
+    }
+
+    /// Send a single 20ms Opus frame, the caller is responsible for pacing
+    pub async fn send_opus_frame(&mut self, frame: &[u8]) -> Result<(), VoiceError> {
+        let packet = self
+            .packetizer
+            .packet(frame)
+            .map_err(VoiceError::Protocol)?;
+        self.udp
+            .send(&packet)
+            .await
+            .map_err(|err| VoiceError::Connection(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Play 20ms Opus frames in real time, followed by five frames of silence
+    pub async fn play<I>(&mut self, frames: I) -> Result<(), VoiceError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.set_speaking(true)?;
+        let mut interval = tokio::time::interval(FRAME_DURATION);
+        for frame in frames {
+            interval.tick().await;
+            self.send_opus_frame(frame.as_ref()).await?;
+        }
+        for _ in 0..5 {
+            interval.tick().await;
+            self.send_opus_frame(&SILENCE_FRAME).await?;
+        }
+        self.set_speaking(false)
+    }
+
+    /// Close the voice websocket with a normal close frame.
+    ///
+    /// Leaving the voice channel is done on the main gateway with
+    /// [`ShardMessenger::leave_voice`](crate::gateway::messenger::ShardMessenger::leave_voice).
+    pub async fn disconnect(self) -> Result<(), VoiceError> {
+        let _ = self.commands.send(VoiceCommand::Disconnect);
+        self.task
+            .await
+            .map_err(|err| VoiceError::Connection(err.to_string()))?
+    }
+}
+
+/// Send a payload to the voice gateway
+async fn send<T: Serialize>(ws: &mut VoiceSocket, op: u8, d: T) -> Result<(), VoiceError> {
+    let text = serde_json::to_string(&VoiceMessage { op, d })
+        .map_err(|err| VoiceError::Protocol(err.to_string()))?;
+    ws.send(Message::Text(text.into()))
+        .await
+        .map_err(|err| VoiceError::Connection(err.to_string()))
+}
+
+/// Receive the next json payload, keeping track of the sequence to acknowledge
+async fn recv(ws: &mut VoiceSocket, seq_ack: &mut Option<i64>) -> Result<VoiceEvent, VoiceError> {
+    loop {
+        let message = match ws.next().await {
+            Some(Ok(message)) => message,
+            Some(Err(err)) => return Err(VoiceError::Connection(err.to_string())),
+            None => {
+                return Err(VoiceError::Connection(
+                    "voice gateway stream ended".to_string(),
+                ))
+            }
+        };
+        match message {
+            Message::Text(text) => match serde_json::from_str::<VoiceEvent>(&text) {
+                Ok(event) => {
+                    if event.seq.is_some() {
+                        *seq_ack = event.seq;
+                    }
+                    return Ok(event);
+                }
+                Err(err) => log::warn!("Failed to parse voice payload: {}", err),
+            },
+            Message::Close(frame) => {
+                let (code, reason) = frame
+                    .map(|frame| (u16::from(frame.code), frame.reason.to_string()))
+                    .unwrap_or((1005, String::new()));
+                return Err(VoiceError::Closed { code, reason });
+            }
+            // binary payloads are only used for end-to-end encryption
+            _ => {}
+        }
+    }
+}
+
+fn parse<T: serde::de::DeserializeOwned>(event: VoiceEvent) -> Result<T, VoiceError> {
+    serde_json::from_value(event.d).map_err(|err| VoiceError::Protocol(err.to_string()))
+}
+
+/// Find the external address and port of the UDP socket.
+///
+/// More: <https://discord.com/developers/docs/topics/voice-connections#ip-discovery>
+async fn discover_ip(udp: &UdpSocket, ssrc: u32) -> Result<(String, u16), VoiceError> {
+    udp.send(&ip_discovery_request(ssrc))
+        .await
+        .map_err(|err| VoiceError::Connection(err.to_string()))?;
+
+    let mut buffer = [0u8; IP_DISCOVERY_LENGTH];
+    let received = tokio::time::timeout(IP_DISCOVERY_TIMEOUT, udp.recv(&mut buffer))
+        .await
+        .map_err(|_| VoiceError::Connection("IP discovery timed out".to_string()))?
+        .map_err(|err| VoiceError::Connection(err.to_string()))?;
+    parse_ip_discovery_response(&buffer[..received])
+        .ok_or_else(|| VoiceError::Protocol("invalid IP discovery response".to_string()))
+}
+
+/// Heartbeat the voice websocket and apply commands until disconnected
+async fn drive(
+    mut ws: VoiceSocket,
+    interval: Duration,
+    mut seq_ack: Option<i64>,
+    ssrc: u32,
+    mut commands: UnboundedReceiver<VoiceCommand>,
+) -> Result<(), VoiceError> {
+    let mut next_heartbeat = Instant::now() + interval;
+    let mut acked = true;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep_until(next_heartbeat) => {
+                if !acked {
+                    return Err(VoiceError::Connection("voice heartbeat was not acknowledged".to_string()));
+                }
+                let nonce = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                log::trace!("💓 Sending voice heartbeat (seq_ack: {:?})", seq_ack);
+                send(&mut ws, opcode::HEARTBEAT, VoiceHeartbeat { t: nonce, seq_ack }).await?;
+                acked = false;
+                next_heartbeat += interval;
+            }
+            event = recv(&mut ws, &mut seq_ack) => {
+                if event?.op == opcode::HEARTBEAT_ACK {
+                    acked = true;
+                }
+            }
+            command = commands.recv() => match command {
+                Some(VoiceCommand::Speaking(speaking)) => {
+                    send(&mut ws, opcode::SPEAKING, Speaking {
+                        speaking: speaking as u8,
+                        delay: 0,
+                        ssrc,
+                    }).await?;
+                }
+                Some(VoiceCommand::Disconnect) | None => {
+                    let frame = CloseFrame {
+                        code: CloseCode::Normal,
+                        reason: "".into(),
+                    };
+                    if let Err(err) = ws.close(Some(frame)).await {
+                        log::debug!("Error closing voice connection: {}", err);
+                    }
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chacha20poly1305::{
+        aead::{Aead, KeyInit, Payload},
+        XChaCha20Poly1305, XNonce,
+    };
+    use serde_json::{json, Value};
+    use tokio::{net::TcpListener, time::timeout};
+    use tokio_tungstenite::accept_async;
+
+    use super::*;
+    use crate::voice::udp::RTP_HEADER_LENGTH;
+
+    const SSRC: u32 = 4242;
+    const KEY: [u8; 32] = [3; 32];
+    const WAIT: Duration = Duration::from_secs(5);
+
+    /// Local voice gateway and UDP server
+    struct MockVoiceServer {
+        url: String,
+        /// Payloads received on the websocket
+        payloads: UnboundedReceiver<Value>,
+        /// RTP packets received on the UDP socket
+        packets: UnboundedReceiver<Vec<u8>>,
+    }
+
+    impl MockVoiceServer {
+        /// Start the servers, `ack_heartbeats` controls whether heartbeats are acknowledged
+        async fn start(ack_heartbeats: bool, modes: Vec<&'static str>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+            let url = format!(
+                "ws://{}/?v={}",
+                listener.local_addr().unwrap(),
+                VOICE_GATEWAY_VERSION
+            );
+            let udp_port = udp.local_addr().unwrap().port();
+            let (payload_sender, payloads) = mpsc::unbounded_channel();
+            let (packet_sender, packets) = mpsc::unbounded_channel();
+
+            tokio::spawn(async move {
+                let mut buffer = [0u8; 1500];
+                while let Ok((received, from)) = udp.recv_from(&mut buffer).await {
+                    let packet = &buffer[..received];
+                    if received == IP_DISCOVERY_LENGTH && packet[0..2] == 1u16.to_be_bytes() {
+                        assert_eq!(packet[2..4], 70u16.to_be_bytes());
+                        assert_eq!(packet[4..8], SSRC.to_be_bytes());
+                        let mut response = [0u8; IP_DISCOVERY_LENGTH];
+                        response[0..2].copy_from_slice(&2u16.to_be_bytes());
+                        response[2..4].copy_from_slice(&70u16.to_be_bytes());
+                        response[4..8].copy_from_slice(&SSRC.to_be_bytes());
+                        response[8..17].copy_from_slice(b"127.0.0.1");
+                        response[72..74].copy_from_slice(&from.port().to_be_bytes());
+                        udp.send_to(&response, from).await.unwrap();
+                    } else {
+                        let _ = packet_sender.send(packet.to_vec());
+                    }
+                }
+            });
+
+            tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                let mut seq = 0;
+                let mut reply = |op: u8, d: Value, sequenced: bool| {
+                    let mut payload = json!({ "op": op, "d": d });
+                    if sequenced {
+                        seq += 1;
+                        payload["seq"] = json!(seq);
+                    }
+                    Message::Text(payload.to_string().into())
+                };
+                ws.send(reply(
+                    opcode::HELLO,
+                    json!({ "heartbeat_interval": 50.0 }),
+                    false,
+                ))
+                .await
+                .unwrap();
+                while let Some(Ok(message)) = ws.next().await {
+                    let Message::Text(text) = message else {
+                        continue;
+                    };
+                    let payload: Value = serde_json::from_str(&text).unwrap();
+                    let answer = match payload["op"].as_u64().map(|op| op as u8) {
+                        Some(opcode::IDENTIFY) => Some(reply(
+                            opcode::READY,
+                            json!({
+                                "ssrc": SSRC,
+                                "ip": "127.0.0.1",
+                                "port": udp_port,
+                                "modes": modes,
+                            }),
+                            true,
+                        )),
+                        Some(opcode::SELECT_PROTOCOL) => Some(reply(
+                            opcode::SESSION_DESCRIPTION,
+                            json!({ "mode": payload["d"]["data"]["mode"], "secret_key": KEY }),
+                            true,
+                        )),
+                        Some(opcode::HEARTBEAT) if ack_heartbeats => Some(reply(
+                            opcode::HEARTBEAT_ACK,
+                            json!({ "t": payload["d"]["t"] }),
+                            false,
+                        )),
+                        _ => None,
+                    };
+                    let _ = payload_sender.send(payload);
+                    if let Some(answer) = answer {
+                        if ws.send(answer).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+
+            Self {
+                url,
+                payloads,
+                packets,
+            }
+        }
+
+        /// Next websocket payload with opcode `op`
+        async fn payload(&mut self, op: u8) -> Value {
+            timeout(WAIT, async {
+                loop {
+                    let payload = self.payloads.recv().await.expect("voice server stopped");
+                    if payload["op"] == op {
+                        return payload;
+                    }
+                }
+            })
+            .await
+            .unwrap_or_else(|_| panic!("voice server did not receive op {}", op))
+        }
+    }
+
+    fn info() -> VoiceConnectionInfo {
+        VoiceConnectionInfo {
+            guild_id: "1000".to_string(),
+            channel_id: "3000".to_string(),
+            user_id: "2000".to_string(),
+            session_id: "voice-session".to_string(),
+            endpoint: "127.0.0.1".to_string(),
+            token: "voice-token".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn connects_heartbeats_and_sends_audio() {
+        let mut server =
+            MockVoiceServer::start(true, vec!["aead_aes256_gcm_rtpsize", ENCRYPTION_MODE]).await;
+        let mut voice = VoiceConnection::connect_url(&server.url, &info())
+            .await
+            .unwrap();
+        assert_eq!(voice.guild_id(), "1000");
+        assert_eq!(voice.ssrc(), SSRC);
+
+        let identify = server.payload(opcode::IDENTIFY).await;
+        assert_eq!(
+            identify["d"],
+            json!({
+                "server_id": "1000",
+                "user_id": "2000",
+                "session_id": "voice-session",
+                "token": "voice-token",
+            })
+        );
+
+        // the address and port found with IP discovery are the ones of the UDP socket
+        let select = server.payload(opcode::SELECT_PROTOCOL).await;
+        assert_eq!(select["d"]["protocol"], "udp");
+        assert_eq!(select["d"]["data"]["address"], "127.0.0.1");
+        assert_eq!(
+            select["d"]["data"]["port"],
+            voice.udp.local_addr().unwrap().port()
+        );
+        assert_eq!(select["d"]["data"]["mode"], ENCRYPTION_MODE);
+
+        // a second heartbeat is only sent when the first one was acknowledged
+        let heartbeat = server.payload(opcode::HEARTBEAT).await;
+        assert_eq!(heartbeat["d"]["seq_ack"], 2);
+        server.payload(opcode::HEARTBEAT).await;
+
+        voice.set_speaking(true).unwrap();
+        let speaking = server.payload(opcode::SPEAKING).await;
+        assert_eq!(
+            speaking["d"],
+            json!({ "speaking": 1, "delay": 0, "ssrc": SSRC })
+        );
+
+        voice.send_opus_frame(b"opus frame").await.unwrap();
+        let packet = timeout(WAIT, server.packets.recv()).await.unwrap().unwrap();
+        let (header, rest) = packet.split_at(RTP_HEADER_LENGTH);
+        assert_eq!(header[8..12], SSRC.to_be_bytes());
+        let (encrypted, nonce_suffix) = rest.split_at(rest.len() - 4);
+        let mut nonce = [0u8; 24];
+        nonce[..4].copy_from_slice(nonce_suffix);
+        let opus = XChaCha20Poly1305::new_from_slice(&KEY)
+            .unwrap()
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: encrypted,
+                    aad: header,
+                },
+            )
+            .unwrap();
+        assert_eq!(opus, b"opus frame");
+
+        assert_eq!(voice.disconnect().await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_heartbeat_ends_the_connection() {
+        let mut server = MockVoiceServer::start(false, vec![ENCRYPTION_MODE]).await;
+        let voice = VoiceConnection::connect_url(&server.url, &info())
+            .await
+            .unwrap();
+        server.payload(opcode::HEARTBEAT).await;
+
+        let result = timeout(WAIT, voice.task).await.unwrap().unwrap();
+        assert_eq!(
+            result,
+            Err(VoiceError::Connection(
+                "voice heartbeat was not acknowledged".to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_server_without_the_encryption_mode() {
+        let server = MockVoiceServer::start(true, vec!["aead_aes256_gcm_rtpsize"]).await;
+        let result = VoiceConnection::connect_url(&server.url, &info()).await;
+        assert!(matches!(result, Err(VoiceError::Protocol(_))));
+    }
+}
@@ -0,0 +1,12 @@
+//! Voice connections
+//!
+//! Join a voice channel with
+//! [`ShardMessenger::join_voice`](crate::gateway::messenger::ShardMessenger::join_voice)
+//! and hand the returned info to [`connection::VoiceConnection::connect`]
+//! to send pre-encoded Opus audio.
+//!
+//! More: <https://discord.com/developers/docs/topics/voice-connections>
+
+pub mod connection;
+pub mod payload;
+pub mod udp;
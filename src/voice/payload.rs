@@ -0,0 +1,133 @@
+// voice/payload.rs
+
+use serde::{Deserialize, Serialize};
+
+/// Opcodes of the voice gateway.
+///
+/// More: <https://discord.com/developers/docs/topics/opcodes-and-status-codes#voice-voice-opcodes>
+pub mod opcode {
+    /// Begin a voice websocket connection
+    pub const IDENTIFY: u8 = 0;
+    /// Select the voice protocol
+    pub const SELECT_PROTOCOL: u8 = 1;
+    /// Complete the websocket handshake
+    pub const READY: u8 = 2;
+    /// Keep the websocket connection alive
+    pub const HEARTBEAT: u8 = 3;
+    /// Describe the session
+    pub const SESSION_DESCRIPTION: u8 = 4;
+    /// Indicate which users are speaking
+    pub const SPEAKING: u8 = 5;
+    /// Sent to acknowledge a received client heartbeat
+    pub const HEARTBEAT_ACK: u8 = 6;
+    /// Resume a connection
+    pub const RESUME: u8 = 7;
+    /// Time to wait between sending heartbeats in milliseconds
+    pub const HELLO: u8 = 8;
+    /// Acknowledge a successful session resume
+    pub const RESUMED: u8 = 9;
+}
+
+/// Version of the voice gateway spoken by the client
+pub const VOICE_GATEWAY_VERSION: u8 = 8;
+
+/// Encryption mode used for RTP packets, supported by every voice server
+pub const ENCRYPTION_MODE: &str = "aead_xchacha20_poly1305_rtpsize";
+
+/// Payload sent to the voice gateway
+#[derive(Debug, Serialize)]
+pub struct VoiceMessage<T: Serialize> {
+    pub op: u8,
+    pub d: T,
+}
+
+/// Payload received from the voice gateway
+#[derive(Debug, Deserialize)]
+pub struct VoiceEvent {
+    pub op: u8,
+    #[serde(default)]
+    pub d: serde_json::Value,
+    /// Sequence number of the message, acknowledged in heartbeats
+    pub seq: Option<i64>,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#establishing-a-voice-websocket-connection>
+#[derive(Debug, Serialize, Clone)]
+pub struct VoiceIdentify {
+    /// ID of the guild, the voice server
+    pub server_id: String,
+    /// ID of the bot user
+    pub user_id: String,
+    /// Session id from the VOICE_STATE_UPDATE
+    pub session_id: String,
+    /// Token from the VOICE_SERVER_UPDATE
+    pub token: String,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#heartbeating>
+#[derive(Debug, Deserialize, Clone)]
+pub struct VoiceHello {
+    /// Interval in milliseconds, sent as a float by the voice gateway
+    pub heartbeat_interval: f64,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#establishing-a-voice-websocket-connection>
+#[derive(Debug, Deserialize, Clone)]
+pub struct VoiceReady {
+    /// SSRC of the bot's audio stream
+    pub ssrc: u32,
+    /// IP of the voice UDP server
+    pub ip: String,
+    /// Port of the voice UDP server
+    pub port: u16,
+    /// Encryption modes supported by the server
+    #[serde(default)]
+    pub modes: Vec<String>,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#heartbeating>
+#[derive(Debug, Serialize, Clone)]
+pub struct VoiceHeartbeat {
+    /// Nonce echoed back in the heartbeat ack
+    pub t: u64,
+    /// Last sequence number received from the voice gateway
+    pub seq_ack: Option<i64>,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#establishing-a-voice-udp-connection>
+#[derive(Debug, Serialize, Clone)]
+pub struct SelectProtocol {
+    pub protocol: String,
+    pub data: SelectProtocolData,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SelectProtocolData {
+    /// External address of the bot, found with IP discovery
+    pub address: String,
+    /// External port of the bot, found with IP discovery
+    pub port: u16,
+    /// Encryption mode to use
+    pub mode: String,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#establishing-a-voice-udp-connection>
+#[derive(Debug, Deserialize, Clone)]
+pub struct SessionDescription {
+    /// Encryption mode selected by the server
+    pub mode: String,
+    /// Key used to encrypt RTP packets
+    pub secret_key: Vec<u8>,
+}
+
+/// More: <https://discord.com/developers/docs/topics/voice-connections#speaking>
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Speaking {
+    /// Bitfield of speaking modes, 1 is microphone
+    pub speaking: u8,
+    /// Should be 0 for bots
+    #[serde(default)]
+    pub delay: u32,
+    /// SSRC of the audio stream
+    pub ssrc: u32,
+}
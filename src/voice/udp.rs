@@ -0,0 +1,224 @@
+// voice/udp.rs
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+/// Size of IP discovery packets
+pub const IP_DISCOVERY_LENGTH: usize = 74;
+
+/// Size of the RTP header, no extensions are sent
+pub const RTP_HEADER_LENGTH: usize = 12;
+
+/// Samples per 20ms Opus frame at 48kHz
+pub const SAMPLES_PER_FRAME: u32 = 960;
+
+/// Opus frame of silence, five of them are sent when audio stops
+pub const SILENCE_FRAME: [u8; 3] = [0xF8, 0xFF, 0xFE];
+
+/// Build the IP discovery request for `ssrc`.
+///
+/// More: <https://discord.com/developers/docs/topics/voice-connections#ip-discovery>
+pub fn ip_discovery_request(ssrc: u32) -> [u8; IP_DISCOVERY_LENGTH] {
+    let mut packet = [0u8; IP_DISCOVERY_LENGTH];
+    packet[0..2].copy_from_slice(&1u16.to_be_bytes());
+    packet[2..4].copy_from_slice(&70u16.to_be_bytes());
+    packet[4..8].copy_from_slice(&ssrc.to_be_bytes());
+    packet
+}
+
+/// Read the external address and port from an IP discovery response
+pub fn parse_ip_discovery_response(packet: &[u8]) -> Option<(String, u16)> {
+    if packet.len() < IP_DISCOVERY_LENGTH || packet[0..2] != 2u16.to_be_bytes() {
+        return None;
+    }
+    let address = &packet[8..72];
+    let end = address
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(address.len());
+    let address = std::str::from_utf8(&address[..end]).ok()?.to_string();
+    let port = u16::from_be_bytes([packet[72], packet[73]]);
+    Some((address, port))
+}
+
+/// Builds encrypted RTP packets from Opus frames.
+///
+/// Uses the `aead_xchacha20_poly1305_rtpsize` mode: the RTP header is sent
+/// in clear and authenticated, the payload is encrypted and followed by the
+/// 4 byte nonce counter.
+///
+/// More: <https://discord.com/developers/docs/topics/voice-connections#transport-encryption-modes>
+pub struct RtpPacketizer {
+    ssrc: u32,
+    sequence: u16,
+    timestamp: u32,
+    nonce: u32,
+    cipher: XChaCha20Poly1305,
+}
+
+impl RtpPacketizer {
+    /// Create a packetizer for `ssrc` with the 32 byte secret key from the
+    /// session description
+    pub fn new(ssrc: u32, secret_key: &[u8]) -> Result<Self, String> {
+        let cipher = XChaCha20Poly1305::new_from_slice(secret_key)
+            .map_err(|_| format!("invalid secret key length {}", secret_key.len()))?;
+        Ok(Self {
+            ssrc,
+            sequence: 0,
+            timestamp: 0,
+            nonce: 0,
+            cipher,
+        })
+    }
+
+    /// SSRC of the audio stream
+    pub fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+
+    /// Build the next RTP packet carrying one 20ms Opus frame
+    pub fn packet(&mut self, opus: &[u8]) -> Result<Vec<u8>, String> {
+        let mut header = [0u8; RTP_HEADER_LENGTH];
+        header[0] = 0x80;
+        header[1] = 0x78;
+        header[2..4].copy_from_slice(&self.sequence.to_be_bytes());
+        header[4..8].copy_from_slice(&self.timestamp.to_be_bytes());
+        header[8..12].copy_from_slice(&self.ssrc.to_be_bytes());
+
+        let nonce_bytes = self.nonce.to_be_bytes();
+        let mut nonce = [0u8; 24];
+        nonce[..4].copy_from_slice(&nonce_bytes);
+
+        let encrypted = self
+            .cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: opus,
+                    aad: &header,
+                },
+            )
+            .map_err(|_| "failed to encrypt voice packet".to_string())?;
+
+        let mut packet = Vec::with_capacity(RTP_HEADER_LENGTH + encrypted.len() + 4);
+        packet.extend_from_slice(&header);
+        packet.extend_from_slice(&encrypted);
+        packet.extend_from_slice(&nonce_bytes);
+
+        self.sequence = self.sequence.wrapping_add(1);
+        self.timestamp = self.timestamp.wrapping_add(SAMPLES_PER_FRAME);
+        self.nonce = self.nonce.wrapping_add(1);
+        Ok(packet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    /// Decrypt a packet the way a voice server does, returns the Opus frame
+    fn open(packet: &[u8]) -> Result<Vec<u8>, chacha20poly1305::Error> {
+        let (header, rest) = packet.split_at(RTP_HEADER_LENGTH);
+        let (encrypted, nonce_suffix) = rest.split_at(rest.len() - 4);
+        let mut nonce = [0u8; 24];
+        nonce[..4].copy_from_slice(nonce_suffix);
+        XChaCha20Poly1305::new_from_slice(&KEY).unwrap().decrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: encrypted,
+                aad: header,
+            },
+        )
+    }
+
+    fn decrypt(packet: &[u8]) -> Vec<u8> {
+        open(packet).expect("packet decrypts with the header as AAD")
+    }
+
+    #[test]
+    fn ip_discovery_round_trip() {
+        let request = ip_discovery_request(0x01020304);
+        assert_eq!(request[..8], [0, 1, 0, 70, 1, 2, 3, 4]);
+        assert!(request[8..].iter().all(|b| *b == 0));
+
+        let mut response = request;
+        response[0..2].copy_from_slice(&2u16.to_be_bytes());
+        response[8..17].copy_from_slice(b"127.0.0.1");
+        response[72..74].copy_from_slice(&50000u16.to_be_bytes());
+        assert_eq!(
+            parse_ip_discovery_response(&response),
+            Some(("127.0.0.1".to_string(), 50000))
+        );
+
+        // a request is not a response, and short packets are rejected
+        assert_eq!(parse_ip_discovery_response(&request), None);
+        assert_eq!(parse_ip_discovery_response(&response[..73]), None);
+    }
+
+    #[test]
+    fn rejects_invalid_key() {
+        assert!(RtpPacketizer::new(1, &[0; 16]).is_err());
+    }
+
+    #[test]
+    fn packet_layout() {
+        let mut packetizer = RtpPacketizer::new(0xDEADBEEF, &KEY).unwrap();
+        let packet = packetizer.packet(&SILENCE_FRAME).unwrap();
+
+        assert_eq!(
+            packet[..RTP_HEADER_LENGTH],
+            [0x80, 0x78, 0, 0, 0, 0, 0, 0, 0xDE, 0xAD, 0xBE, 0xEF]
+        );
+        // header, encrypted frame with its 16 byte tag, nonce suffix
+        assert_eq!(
+            packet.len(),
+            RTP_HEADER_LENGTH + SILENCE_FRAME.len() + 16 + 4
+        );
+        assert_eq!(packet[packet.len() - 4..], [0, 0, 0, 0]);
+        assert_eq!(decrypt(&packet), SILENCE_FRAME);
+    }
+
+    #[test]
+    fn sequence_timestamp_and_nonce_increment() {
+        let mut packetizer = RtpPacketizer::new(1, &KEY).unwrap();
+        packetizer.packet(b"first").unwrap();
+        let packet = packetizer.packet(b"second").unwrap();
+
+        assert_eq!(packet[2..4], 1u16.to_be_bytes());
+        assert_eq!(packet[4..8], SAMPLES_PER_FRAME.to_be_bytes());
+        assert_eq!(packet[packet.len() - 4..], 1u32.to_be_bytes());
+        assert_eq!(decrypt(&packet), b"second");
+    }
+
+    #[test]
+    fn counters_wrap_around() {
+        let mut packetizer = RtpPacketizer::new(1, &KEY).unwrap();
+        packetizer.sequence = u16::MAX;
+        packetizer.timestamp = u32::MAX - 100;
+        packetizer.nonce = u32::MAX;
+
+        let last = packetizer.packet(b"last").unwrap();
+        assert_eq!(last[2..4], u16::MAX.to_be_bytes());
+        assert_eq!(last[4..8], (u32::MAX - 100).to_be_bytes());
+        assert_eq!(last[last.len() - 4..], u32::MAX.to_be_bytes());
+        assert_eq!(decrypt(&last), b"last");
+
+        let wrapped = packetizer.packet(b"wrapped").unwrap();
+        assert_eq!(wrapped[2..4], 0u16.to_be_bytes());
+        assert_eq!(wrapped[4..8], (SAMPLES_PER_FRAME - 101).to_be_bytes());
+        assert_eq!(wrapped[wrapped.len() - 4..], 0u32.to_be_bytes());
+        assert_eq!(decrypt(&wrapped), b"wrapped");
+    }
+
+    #[test]
+    fn tampered_header_fails_authentication() {
+        let mut packetizer = RtpPacketizer::new(1, &KEY).unwrap();
+        let mut packet = packetizer.packet(b"frame").unwrap();
+        packet[3] ^= 1;
+        assert!(open(&packet).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+// tests/mock_discord.rs
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use rustycord::{
+    client::Client,
+    handlers::message_handler::{MessageHandler, MessageHandlerResult, PingPongHandler},
+    message::ChannelMessage,
+    prefix::{EchoPrefixCommand, PingCommand, PrefixListener},
+    testing::{MockDiscord, MockResponse},
+};
+use serde_json::{json, Value};
+
+/// Client logged in to the mock
+async fn login(mock: &MockDiscord) -> Client {
+    let mut client = Client::new();
+    client.set_endpoints(mock.endpoints());
+    client.login("token".to_string()).await.unwrap();
+    client
+}
+
+/// Client with its shard connected to the mock gateway
+async fn connect(mock: &MockDiscord) -> Client {
+    let mut client = login(mock).await;
+    client.ws_connect(Some(513), None, None).await.unwrap();
+    assert!(mock.wait_for_ready().await, "shard did not receive READY");
+    client
+}
+
+/// Wait until the client sent at least `count` gateway payloads with opcode `op`
+async fn wait_for_commands(mock: &MockDiscord, op: u8, count: usize, wait: Duration) -> Vec<Value> {
+    tokio::time::timeout(wait, async {
+        loop {
+            let commands: Vec<Value> = mock
+                .commands()
+                .await
+                .into_iter()
+                .filter(|command| command["op"] == op)
+                .collect();
+            if commands.len() >= count {
+                return commands;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("client did not send {} payloads with op {}", count, op))
+}
+
+/// Answers prefix commands the way the prefix commands example does
+struct PrefixMessageHandler {
+    listener: Arc<PrefixListener>,
+}
+
+#[async_trait]
+impl MessageHandler for PrefixMessageHandler {
+    async fn on_message_create(
+        &self,
+        message: &ChannelMessage,
+        client: &Client,
+    ) -> MessageHandlerResult {
+        if let Some(response) = self.listener.handle_message(message).await? {
+            client
+                .send_message(&message.channel_id, &response, None)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn identifies_and_receives_ready() {
+    let mock = MockDiscord::start().await;
+    let _client = connect(&mock).await;
+
+    assert!(mock.wait_for_request("GET", "/gateway/bot").await.is_some());
+    let identify = mock.wait_for_command(2).await.unwrap();
+    assert_eq!(identify["d"]["token"], "token");
+    assert_eq!(identify["d"]["intents"], 513);
+    assert_eq!(identify["d"]["shard"], json!([0, 1]));
+    assert_eq!(mock.session_count().await, 1);
+}
+
+#[tokio::test]
+async fn ping_pong_handler_replies() {
+    let mock = MockDiscord::start().await;
+    let mut client = login(&mock).await;
+    client
+        .get_event_dispatcher()
+        .get_message_handlers()
+        .add_handler(PingPongHandler)
+        .await;
+    client.ws_connect(Some(513), None, None).await.unwrap();
+    assert!(mock.wait_for_ready().await);
+
+    mock.dispatch_message_create("1000", "ping").await;
+    let reply = mock
+        .wait_for_request("POST", "/channels/1000/messages")
+        .await
+        .expect("PingPongHandler did not reply");
+    assert_eq!(reply.json().unwrap()["content"], "Pong! 🏓");
+}
+
+#[tokio::test]
+async fn prefix_command_round_trip() {
+    let mock = MockDiscord::start().await;
+    let mut client = login(&mock).await;
+    let listener = Arc::new(PrefixListener::new("!"));
+    listener
+        .register_command("ping", Box::new(PingCommand))
+        .await;
+    listener
+        .register_command("echo", Box::new(EchoPrefixCommand))
+        .await;
+    client
+        .get_event_dispatcher()
+        .get_message_handlers()
+        .add_handler(PrefixMessageHandler { listener })
+        .await;
+    client.ws_connect(Some(513), None, None).await.unwrap();
+    assert!(mock.wait_for_ready().await);
+
+    mock.dispatch_message_create("2000", "!echo hello world")
+        .await;
+    let reply = mock
+        .wait_for_request("POST", "/channels/2000/messages")
+        .await
+        .expect("prefix command did not reply");
+    assert_eq!(reply.json().unwrap()["content"], "hello world");
+
+    // messages without the prefix are left alone
+    mock.dispatch_message_create("3000", "echo hello").await;
+    mock.dispatch_message_create("4000", "!ping").await;
+    let reply = mock
+        .wait_for_request("POST", "/channels/4000/messages")
+        .await
+        .expect("prefix command did not reply");
+    assert_eq!(reply.json().unwrap()["content"], "Pong! 🏓");
+    assert!(mock
+        .requests()
+        .await
+        .iter()
+        .all(|request| request.path != "/channels/3000/messages"));
+}
+
+#[tokio::test]
+async fn heartbeats_are_acknowledged() {
+    let mock = MockDiscord::start().await;
+    mock.set_heartbeat_interval(Duration::from_millis(100));
+    let _client = connect(&mock).await;
+
+    let heartbeat = mock.wait_for_command(1).await.unwrap();
+    assert!(heartbeat["d"].is_null() || heartbeat["d"].is_number());
+
+    // a heartbeat without ACK would make the shard drop the connection and resume
+    wait_for_commands(&mock, 1, 3, Duration::from_secs(5)).await;
+    let commands = mock.commands().await;
+    assert_eq!(commands.iter().filter(|c| c["op"] == 2).count(), 1);
+    assert_eq!(commands.iter().filter(|c| c["op"] == 6).count(), 0);
+    assert_eq!(mock.session_count().await, 1);
+}
+
+#[tokio::test]
+async fn resumes_after_resumable_close() {
+    let mock = MockDiscord::start().await;
+    let _client = connect(&mock).await;
+
+    mock.close_sessions(4000).await;
+    let resume = mock
+        .wait_for_command(6)
+        .await
+        .expect("shard did not resume");
+    assert_eq!(resume["d"]["token"], "token");
+    assert!(resume["d"]["session_id"]
+        .as_str()
+        .unwrap()
+        .starts_with("mock-session-"));
+    // READY was the only dispatch
+    assert_eq!(resume["d"]["seq"], 1);
+
+    assert!(mock.wait_for_ready().await, "shard did not receive RESUMED");
+    let identifies = mock
+        .commands()
+        .await
+        .iter()
+        .filter(|c| c["op"] == 2)
+        .count();
+    assert_eq!(identifies, 1);
+}
+
+#[tokio::test]
+async fn identifies_again_after_non_resumable_close() {
+    let mock = MockDiscord::start().await;
+    let _client = connect(&mock).await;
+
+    // session timed out, the session cannot be resumed
+    mock.close_sessions(4009).await;
+    // the identify scheduler allows one IDENTIFY per 5 seconds
+    let identifies = wait_for_commands(&mock, 2, 2, Duration::from_secs(10)).await;
+    assert_eq!(identifies[1]["d"]["token"], "token");
+    assert!(mock.wait_for_ready().await);
+    assert_eq!(
+        mock.commands()
+            .await
+            .iter()
+            .filter(|c| c["op"] == 6)
+            .count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn fatal_close_code_stops_the_shards() {
+    let mock = MockDiscord::start().await;
+    let mut client = login(&mock).await;
+    client.ws_connect(Some(513), None, None).await.unwrap();
+    assert!(mock.wait_for_ready().await);
+
+    // authentication failed, reconnecting cannot fix that
+    mock.close_sessions(4004).await;
+    let result = tokio::time::timeout(Duration::from_secs(5), client.wait_for_shards())
+        .await
+        .expect("shards did not stop after a fatal close code");
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_closes_the_connection_before_returning() {
+    let mock = MockDiscord::start().await;
+    let client = connect(&mock).await;
+
+    client.shutdown_and_wait().await.unwrap();
+    // the shard task only finishes after its close frame went out
+    assert_eq!(mock.wait_for_close().await, Some(Some(1000)));
+    assert_eq!(mock.close_codes().await, [Some(1000)]);
+}
+
+#[tokio::test]
+async fn failed_initial_connect_is_retried() {
+    let mock = MockDiscord::start().await;
+    let mut client = login(&mock).await;
+    // nothing listens on the gateway url
+    let mut endpoints = mock.endpoints();
+    endpoints.gateway_url = Some("ws://127.0.0.1:1".to_string());
+    client.set_endpoints(endpoints);
+    client.ws_connect(Some(513), None, None).await.unwrap();
+
+    // the shard keeps retrying with backoff instead of stopping the cluster
+    let stopped = tokio::time::timeout(Duration::from_secs(2), client.wait_for_shards()).await;
+    assert!(stopped.is_err(), "shards stopped: {:?}", stopped);
+}
+
+#[tokio::test]
+async fn shards_of_one_bucket_connect_once_they_may_identify() {
+    let mock = MockDiscord::start().await;
+    mock.set_response(
+        "GET",
+        "/gateway/bot",
+        MockResponse::json(
+            200,
+            json!({
+                "url": mock.gateway_url(),
+                "shards": 2,
+                "session_start_limit": {
+                    "total": 1000,
+                    "remaining": 1000,
+                    "reset_after": 0,
+                    "max_concurrency": 1,
+                },
+            }),
+        ),
+    )
+    .await;
+    // shorter than the 5 second identify window
+    mock.set_heartbeat_interval(Duration::from_secs(1));
+    let mut client = login(&mock).await;
+    client.ws_connect(Some(513), None, None).await.unwrap();
+
+    wait_for_commands(&mock, 2, 1, Duration::from_secs(5)).await;
+    let first_identify = tokio::time::Instant::now();
+    // shard 1 shares the bucket of shard 0 and must not hold an idle
+    // connection while it waits for its turn
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(mock.connection_count(), 1);
+
+    let identifies = wait_for_commands(&mock, 2, 2, Duration::from_secs(10)).await;
+    assert!(first_identify.elapsed() >= Duration::from_millis(4900));
+    let mut shards: Vec<Value> = identifies.iter().map(|i| i["d"]["shard"].clone()).collect();
+    shards.sort_by_key(|shard| shard[0].as_u64());
+    assert_eq!(shards, vec![json!([0, 2]), json!([1, 2])]);
+    assert_eq!(mock.connection_count(), 2);
+}
+
+#[tokio::test]
+async fn clusters_sharing_a_scheduler_take_turns_in_a_bucket() {
+    let mock = MockDiscord::start().await;
+    let mut cluster_a = login(&mock).await;
+    let mut cluster_b = login(&mock).await;
+    cluster_a.initialize_shard_cluster([0], 2).await.unwrap();
+    cluster_b.initialize_shard_cluster([1], 2).await.unwrap();
+    // with max_concurrency 1 both shards are in the same bucket
+    let scheduler = cluster_a.identify_scheduler().await.unwrap();
+    cluster_b.set_identify_scheduler(scheduler).await.unwrap();
+
+    cluster_a.start_shards(Some(513), None).await.unwrap();
+    cluster_b.start_shards(Some(513), None).await.unwrap();
+
+    wait_for_commands(&mock, 2, 1, Duration::from_secs(5)).await;
+    let first_identify = tokio::time::Instant::now();
+    let identifies = wait_for_commands(&mock, 2, 2, Duration::from_secs(10)).await;
+    assert!(first_identify.elapsed() >= Duration::from_millis(4900));
+    assert_ne!(identifies[0]["d"]["shard"], identifies[1]["d"]["shard"]);
+}
+
+#[tokio::test]
+async fn edit_is_validated_before_sending() {
+    let mock = MockDiscord::start().await;
+    let client = login(&mock).await;
+
+    let err = client
+        .edit_message("1000", "2000", Some(&"a".repeat(2001)), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, rustycord::Error::Validation(_)), "{:?}", err);
+
+    // removing the embeds keeps the content
+    client
+        .edit_message("1000", "2000", None, Some(Vec::new()))
+        .await
+        .unwrap();
+    let edits: Vec<Value> = mock
+        .requests()
+        .await
+        .into_iter()
+        .filter(|request| request.method == "PATCH")
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect();
+    assert_eq!(edits, [json!({ "embeds": [] })]);
+}
+
+#[tokio::test]
+async fn retries_rate_limited_request() {
+    let mock = MockDiscord::start().await;
+    let client = login(&mock).await;
+    mock.push_response(
+        "POST",
+        "/channels/1000/messages",
+        MockResponse::json(
+            429,
+            json!({
+                "message": "You are being rate limited.",
+                "retry_after": 0.05,
+                "global": false,
+            }),
+        ),
+    )
+    .await;
+
+    let message = client.send_message("1000", "hello", None).await.unwrap();
+    assert_eq!(message.content, "hello");
+    let attempts = mock
+        .requests()
+        .await
+        .into_iter()
+        .filter(|request| request.method == "POST" && request.path == "/channels/1000/messages")
+        .count();
+    assert_eq!(attempts, 2);
+}
+
+#[tokio::test]
+async fn unusable_retry_after_gives_up() {
+    let mock = MockDiscord::start().await;
+    let client = login(&mock).await;
+    mock.push_response(
+        "POST",
+        "/channels/1000/messages",
+        MockResponse::json(
+            429,
+            json!({ "message": "You are being rate limited.", "global": false }),
+        )
+        .with_header("retry-after", "inf"),
+    )
+    .await;
+
+    let err = client
+        .send_message("1000", "hello", None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, rustycord::Error::RateLimited { .. }),
+        "{:?}",
+        err
+    );
+}